@@ -49,7 +49,7 @@ pub struct SignedCertificateTimestamp<'a> {
     pub signature: DigitallySigned<'a>,
 }
 
-pub(crate) fn parse_log_id(i: &[u8]) -> IResult<&[u8], CtLogID> {
+pub(crate) fn parse_log_id(i: &[u8]) -> IResult<&[u8], CtLogID<'_>> {
     let (i, key_id) = take(32usize)(i)?;
     Ok((
         i,
@@ -61,7 +61,7 @@ pub(crate) fn parse_log_id(i: &[u8]) -> IResult<&[u8], CtLogID> {
     ))
 }
 
-pub(crate) fn parse_ct_extensions(i: &[u8]) -> IResult<&[u8], CtExtensions> {
+pub(crate) fn parse_ct_extensions(i: &[u8]) -> IResult<&[u8], CtExtensions<'_>> {
     let (i, ext_len) = be_u16(i)?;
     let (i, ext_data) = take(ext_len as usize)(i)?;
     Ok((i, CtExtensions(ext_data)))
@@ -69,7 +69,7 @@ pub(crate) fn parse_ct_extensions(i: &[u8]) -> IResult<&[u8], CtExtensions> {
 
 pub(crate) fn parse_ct_signed_certificate_timestamp_content(
     i: &[u8],
-) -> IResult<&[u8], SignedCertificateTimestamp> {
+) -> IResult<&[u8], SignedCertificateTimestamp<'_>> {
     let (i, version) = be_u8(i)?;
     let (i, id) = parse_log_id(i)?;
     let (i, timestamp) = be_u64(i)?;
@@ -90,7 +90,7 @@ pub(crate) fn parse_ct_signed_certificate_timestamp_content(
 /// Parses as single Signed Certificate Timestamp entry
 pub fn parse_ct_signed_certificate_timestamp(
     i: &[u8],
-) -> IResult<&[u8], SignedCertificateTimestamp> {
+) -> IResult<&[u8], SignedCertificateTimestamp<'_>> {
     map_parser(
         length_data(be_u16),
         parse_ct_signed_certificate_timestamp_content,
@@ -100,7 +100,7 @@ pub fn parse_ct_signed_certificate_timestamp(
 /// Parses a list of Signed Certificate Timestamp entries
 pub fn parse_ct_signed_certificate_timestamp_list(
     i: &[u8],
-) -> IResult<&[u8], Vec<SignedCertificateTimestamp>> {
+) -> IResult<&[u8], Vec<SignedCertificateTimestamp<'_>>> {
     let (i, sct_len) = be_u16(i)?;
     let (i, sct_list) = map_parser(
         take(sct_len as usize),
@@ -4,7 +4,7 @@ use crate::tls::*;
 use crate::TlsMessageAlert;
 use alloc::vec::Vec;
 use nom::bytes::streaming::take;
-use nom::combinator::{complete, cond, map, map_parser, opt, verify};
+use nom::combinator::{complete, cond, map, map_parser, verify};
 use nom::error::{make_error, ErrorKind};
 use nom::multi::{length_data, many1};
 use nom::number::streaming::{be_u16, be_u24, be_u64, be_u8};
@@ -84,6 +84,28 @@ pub struct DTLSHelloVerifyRequest<'a> {
     pub cookie: &'a [u8],
 }
 
+/// DTLS handshake message header
+///
+/// Compared to TLS, the header contains the message sequence number and the fragment offset
+/// and length, used to reassemble handshake messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DTLSHandshakeHeader {
+    pub msg_type: TlsHandshakeType,
+    /// Length of the complete (reassembled) message
+    pub length: u32,
+    pub message_seq: u16,
+    pub fragment_offset: u32,
+    /// Length of the fragment following this header
+    pub fragment_length: u32,
+}
+
+impl DTLSHandshakeHeader {
+    /// Tell if the fragment following this header is only a part of the handshake message
+    pub fn is_fragmented(&self) -> bool {
+        self.fragment_offset > 0 || self.fragment_length < self.length
+    }
+}
+
 /// DTLS Generic handshake message
 #[derive(Debug, PartialEq)]
 pub struct DTLSMessageHandshake<'a> {
@@ -128,6 +150,19 @@ pub enum DTLSMessage<'a> {
     Heartbeat(TlsMessageHeartbeat<'a>),
 }
 
+impl<'a> DTLSMessageHandshake<'a> {
+    /// Return the header of this handshake message
+    pub fn header(&self) -> DTLSHandshakeHeader {
+        DTLSHandshakeHeader {
+            msg_type: self.msg_type,
+            length: self.length,
+            message_seq: self.message_seq,
+            fragment_offset: self.fragment_offset,
+            fragment_length: self.fragment_length,
+        }
+    }
+}
+
 impl<'a> DTLSMessage<'a> {
     /// Tell if this DTLSMessage is a (handshake) fragment that needs combining with other
     /// fragments to be a complete message.
@@ -160,14 +195,32 @@ pub fn parse_dtls_record_header(i: &[u8]) -> IResult<&[u8], DTLSRecordHeader> {
     Ok((i, record))
 }
 
+/// DTLS handshake message header
+// Section 4.2.2 of RFC6347
+pub fn parse_dtls_handshake_header(i: &[u8]) -> IResult<&[u8], DTLSHandshakeHeader> {
+    let (i, msg_type) = map(be_u8, TlsHandshakeType)(i)?;
+    let (i, length) = be_u24(i)?;
+    let (i, message_seq) = be_u16(i)?;
+    let (i, fragment_offset) = be_u24(i)?;
+    let (i, fragment_length) = be_u24(i)?;
+    let header = DTLSHandshakeHeader {
+        msg_type,
+        length,
+        message_seq,
+        fragment_offset,
+        fragment_length,
+    };
+    Ok((i, header))
+}
+
 /// Treat the entire input as an opaque fragment.
-fn parse_dtls_fragment(i: &[u8]) -> IResult<&[u8], DTLSMessageHandshakeBody> {
+fn parse_dtls_fragment(i: &[u8]) -> IResult<&[u8], DTLSMessageHandshakeBody<'_>> {
     Ok((&[], DTLSMessageHandshakeBody::Fragment(i)))
 }
 
 /// DTLS Client Hello
 // Section 4.2 of RFC6347
-fn parse_dtls_client_hello(i: &[u8]) -> IResult<&[u8], DTLSMessageHandshakeBody> {
+fn parse_dtls_client_hello(i: &[u8]) -> IResult<&[u8], DTLSMessageHandshakeBody<'_>> {
     let (i, version) = TlsVersion::parse(i)?;
     let (i, random) = take(32usize)(i)?;
     let (i, sidlen) = verify(be_u8, |&n| n <= 32)(i)?;
@@ -177,7 +230,7 @@ fn parse_dtls_client_hello(i: &[u8]) -> IResult<&[u8], DTLSMessageHandshakeBody>
     let (i, ciphers) = parse_cipher_suites(i, ciphers_len as usize)?;
     let (i, comp_len) = be_u8(i)?;
     let (i, comp) = parse_compressions_algs(i, comp_len as usize)?;
-    let (i, ext) = opt(complete(length_data(be_u16)))(i)?;
+    let (i, ext) = parse_hello_extensions_block(i)?;
     let content = DTLSClientHello {
         version,
         random,
@@ -192,7 +245,7 @@ fn parse_dtls_client_hello(i: &[u8]) -> IResult<&[u8], DTLSMessageHandshakeBody>
 
 /// DTLS Client Hello
 // Section 4.2 of RFC6347
-fn parse_dtls_hello_verify_request(i: &[u8]) -> IResult<&[u8], DTLSMessageHandshakeBody> {
+fn parse_dtls_hello_verify_request(i: &[u8]) -> IResult<&[u8], DTLSMessageHandshakeBody<'_>> {
     let (i, server_version) = TlsVersion::parse(i)?;
     let (i, cookie) = length_data(be_u8)(i)?;
     let content = DTLSHelloVerifyRequest {
@@ -204,7 +257,7 @@ fn parse_dtls_hello_verify_request(i: &[u8]) -> IResult<&[u8], DTLSMessageHandsh
 
 fn parse_dtls_handshake_msg_server_hello_tlsv12(
     i: &[u8],
-) -> IResult<&[u8], DTLSMessageHandshakeBody> {
+) -> IResult<&[u8], DTLSMessageHandshakeBody<'_>> {
     map(
         parse_tls_server_hello_tlsv12::<true>,
         DTLSMessageHandshakeBody::ServerHello,
@@ -214,38 +267,41 @@ fn parse_dtls_handshake_msg_server_hello_tlsv12(
 fn parse_dtls_handshake_msg_serverdone(
     i: &[u8],
     len: usize,
-) -> IResult<&[u8], DTLSMessageHandshakeBody> {
+) -> IResult<&[u8], DTLSMessageHandshakeBody<'_>> {
     map(take(len), DTLSMessageHandshakeBody::ServerDone)(i)
 }
 
 fn parse_dtls_handshake_msg_clientkeyexchange(
     i: &[u8],
     len: usize,
-) -> IResult<&[u8], DTLSMessageHandshakeBody> {
+) -> IResult<&[u8], DTLSMessageHandshakeBody<'_>> {
     map(
         parse_tls_clientkeyexchange(len),
         DTLSMessageHandshakeBody::ClientKeyExchange,
     )(i)
 }
 
-fn parse_dtls_handshake_msg_certificate(i: &[u8]) -> IResult<&[u8], DTLSMessageHandshakeBody> {
+fn parse_dtls_handshake_msg_certificate(i: &[u8]) -> IResult<&[u8], DTLSMessageHandshakeBody<'_>> {
     map(parse_tls_certificate, DTLSMessageHandshakeBody::Certificate)(i)
 }
 
 /// Parse a DTLS handshake message
-pub fn parse_dtls_message_handshake(i: &[u8]) -> IResult<&[u8], DTLSMessage> {
-    let (i, msg_type) = map(be_u8, TlsHandshakeType)(i)?;
-    let (i, length) = be_u24(i)?;
-    let (i, message_seq) = be_u16(i)?;
-    let (i, fragment_offset) = be_u24(i)?;
-    let (i, fragment_length) = be_u24(i)?;
+pub fn parse_dtls_message_handshake(i: &[u8]) -> IResult<&[u8], DTLSMessage<'_>> {
+    let (i, header) = parse_dtls_handshake_header(i)?;
+    let DTLSHandshakeHeader {
+        msg_type,
+        length,
+        message_seq,
+        fragment_offset,
+        fragment_length,
+    } = header;
     // This packet contains fragment_length (which is less than length for fragmentation)
     let (i, raw_msg) = take(fragment_length)(i)?;
 
     // Handshake messages can be fragmented over multiple packets. When fragmented, the user
     // needs the fragment_offset, fragment_length and length to determine whether they received
     // all the fragments. The DTLS spec allows for overlapping and duplicated fragments.
-    let is_fragment = fragment_offset > 0 || fragment_length < length;
+    let is_fragment = header.is_fragmented();
 
     let (_, body) = match msg_type {
         _ if is_fragment => parse_dtls_fragment(raw_msg),
@@ -277,14 +333,14 @@ pub fn parse_dtls_message_handshake(i: &[u8]) -> IResult<&[u8], DTLSMessage> {
 
 /// Parse a DTLS changecipherspec message
 // XXX add extra verification hdr.len == 1
-pub fn parse_dtls_message_changecipherspec(i: &[u8]) -> IResult<&[u8], DTLSMessage> {
+pub fn parse_dtls_message_changecipherspec(i: &[u8]) -> IResult<&[u8], DTLSMessage<'_>> {
     let (i, _) = verify(be_u8, |&tag| tag == 0x01)(i)?;
     Ok((i, DTLSMessage::ChangeCipherSpec))
 }
 
 /// Parse a DTLS alert message
 // XXX add extra verification hdr.len == 2
-pub fn parse_dtls_message_alert(i: &[u8]) -> IResult<&[u8], DTLSMessage> {
+pub fn parse_dtls_message_alert(i: &[u8]) -> IResult<&[u8], DTLSMessage<'_>> {
     let (i, alert) = TlsMessageAlert::parse(i)?;
     Ok((i, DTLSMessage::Alert(alert)))
 }
@@ -308,7 +364,7 @@ pub fn parse_dtls_record_with_header<'i>(
 
 /// Parse one DTLS plaintext record
 // Section 4.1 of RFC6347
-pub fn parse_dtls_plaintext_record(i: &[u8]) -> IResult<&[u8], DTLSPlaintext> {
+pub fn parse_dtls_plaintext_record(i: &[u8]) -> IResult<&[u8], DTLSPlaintext<'_>> {
     let (i, header) = parse_dtls_record_header(i)?;
     // As in TLS 1.2, the length should not exceed 2^14.
     if header.length > MAX_RECORD_LEN {
@@ -322,6 +378,6 @@ pub fn parse_dtls_plaintext_record(i: &[u8]) -> IResult<&[u8], DTLSPlaintext> {
 
 /// Parse multiple DTLS plaintext record
 // Section 4.1 of RFC6347
-pub fn parse_dtls_plaintext_records(i: &[u8]) -> IResult<&[u8], Vec<DTLSPlaintext>> {
+pub fn parse_dtls_plaintext_records(i: &[u8]) -> IResult<&[u8], Vec<DTLSPlaintext<'_>>> {
     many1(complete(parse_dtls_plaintext_record))(i)
 }
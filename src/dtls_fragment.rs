@@ -0,0 +1,283 @@
+//! DTLS handshake fragments reassembly
+//!
+//! DTLS handshake messages can be split into fragments (Section 4.2.3 of RFC6347). Fragments
+//! can be received out of order, overlap, or be duplicated.
+
+use crate::dtls::DTLSHandshakeHeader;
+use crate::tls::TlsHandshakeType;
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Default maximum length of a reassembled handshake message
+pub const DTLS_DEFAULT_MAX_MESSAGE_LEN: u32 = 1 << 20;
+
+/// Default maximum number of incomplete messages
+pub const DTLS_DEFAULT_MAX_PENDING_MESSAGES: usize = 16;
+
+/// Default maximum number of bytes allocated for all incomplete messages
+pub const DTLS_DEFAULT_MAX_BUFFERED_LEN: usize = 4 << 20;
+
+/// Error types for DTLS fragments reassembly
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DTLSReassemblyError {
+    /// Fragment header does not match the header of previous fragments for the same message
+    /// sequence (message type or total length)
+    InconsistentHeader,
+    /// Fragment length does not match the fragment data
+    InvalidFragmentLength,
+    /// Fragment is outside of the message boundaries
+    OutOfBounds,
+    /// Overlapping fragments contain different data
+    ConflictingData,
+    /// Message is longer than the configured maximum length
+    MessageTooLarge,
+    /// Fragment starts a new message, but the maximum number of incomplete messages is reached
+    TooManyPendingMessages,
+    /// Fragment starts a new message, but the buffers of incomplete messages would exceed the
+    /// configured maximum length
+    BufferLimitExceeded,
+}
+
+/// A complete (reassembled) DTLS handshake message
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DTLSReassembledMessage {
+    pub msg_type: TlsHandshakeType,
+    pub message_seq: u16,
+    pub body: Vec<u8>,
+}
+
+impl DTLSReassembledMessage {
+    /// Return the message as an unfragmented handshake message (header and body)
+    ///
+    /// The result can be parsed using `parse_dtls_message_handshake`.
+    pub fn to_handshake_bytes(&self) -> Vec<u8> {
+        let len = (self.body.len() as u32).to_be_bytes();
+        let mut v = Vec::with_capacity(12 + self.body.len());
+        v.push(self.msg_type.0);
+        v.extend_from_slice(&len[1..]);
+        v.extend_from_slice(&self.message_seq.to_be_bytes());
+        v.extend_from_slice(&[0, 0, 0]);
+        v.extend_from_slice(&len[1..]);
+        v.extend_from_slice(&self.body);
+        v
+    }
+}
+
+#[derive(Debug)]
+struct PartialMessage {
+    msg_type: TlsHandshakeType,
+    data: Vec<u8>,
+    /// Received byte ranges (`start..end`), sorted and not overlapping
+    ranges: Vec<(usize, usize)>,
+}
+
+impl PartialMessage {
+    fn is_complete(&self) -> bool {
+        match self.ranges.as_slice() {
+            [] => self.data.is_empty(),
+            [(0, end)] => *end == self.data.len(),
+            _ => false,
+        }
+    }
+
+    fn insert(&mut self, start: usize, fragment: &[u8]) -> Result<(), DTLSReassemblyError> {
+        let end = start + fragment.len();
+        // check that overlapping data is identical
+        for &(s, e) in &self.ranges {
+            let (os, oe) = (s.max(start), e.min(end));
+            if os < oe && self.data[os..oe] != fragment[os - start..oe - start] {
+                return Err(DTLSReassemblyError::ConflictingData);
+            }
+        }
+        if start == end {
+            return Ok(());
+        }
+        self.data[start..end].copy_from_slice(fragment);
+        // merge ranges
+        let mut merged = Vec::with_capacity(self.ranges.len() + 1);
+        let (mut cur_s, mut cur_e) = (start, end);
+        for &(s, e) in &self.ranges {
+            if e < cur_s || s > cur_e {
+                merged.push((s, e));
+            } else {
+                cur_s = cur_s.min(s);
+                cur_e = cur_e.max(e);
+            }
+        }
+        merged.push((cur_s, cur_e));
+        merged.sort_unstable();
+        self.ranges = merged;
+        Ok(())
+    }
+}
+
+/// Reassembler for fragmented DTLS handshake messages
+///
+/// Fragments are grouped by message sequence number. Once all bytes of a message have been
+/// received, the complete message is returned and removed from the reassembler.
+///
+/// Duplicate and overlapping fragments are accepted if their data is identical.
+///
+/// The buffer of a message is allocated when its first fragment is received. To bound memory
+/// usage, the number of incomplete messages and the total size of their buffers are limited
+/// (see `with_max_pending_messages` and `with_max_buffered_len`).
+///
+/// # Example
+///
+/// ```rust
+/// use tls_parser::*;
+///
+/// fn handle_fragment(r: &mut DTLSFragmentReassembler, i: &[u8]) {
+///     if let Ok((rem, hdr)) = parse_dtls_handshake_header(i) {
+///         let fragment = &rem[..(hdr.fragment_length as usize).min(rem.len())];
+///         match r.add_fragment(&hdr, fragment) {
+///             Ok(Some(msg)) => { /* parse msg.to_handshake_bytes() */ }
+///             Ok(None) => (), // wait for more fragments
+///             Err(e) => println!("reassembly error: {:?}", e),
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct DTLSFragmentReassembler {
+    messages: BTreeMap<u16, PartialMessage>,
+    buffered_len: usize,
+    max_message_len: u32,
+    max_pending_messages: usize,
+    max_buffered_len: usize,
+}
+
+impl Default for DTLSFragmentReassembler {
+    fn default() -> Self {
+        DTLSFragmentReassembler::new()
+    }
+}
+
+impl DTLSFragmentReassembler {
+    pub fn new() -> Self {
+        DTLSFragmentReassembler {
+            messages: BTreeMap::new(),
+            buffered_len: 0,
+            max_message_len: DTLS_DEFAULT_MAX_MESSAGE_LEN,
+            max_pending_messages: DTLS_DEFAULT_MAX_PENDING_MESSAGES,
+            max_buffered_len: DTLS_DEFAULT_MAX_BUFFERED_LEN,
+        }
+    }
+
+    /// Set the maximum length of a reassembled message
+    ///
+    /// Fragments of messages longer than this value are rejected.
+    pub fn with_max_message_len(mut self, max_message_len: u32) -> Self {
+        self.max_message_len = max_message_len;
+        self
+    }
+
+    /// Set the maximum number of incomplete messages
+    ///
+    /// Fragments of new messages are rejected when this number is reached.
+    pub fn with_max_pending_messages(mut self, max_pending_messages: usize) -> Self {
+        self.max_pending_messages = max_pending_messages;
+        self
+    }
+
+    /// Set the maximum number of bytes allocated for all incomplete messages
+    ///
+    /// Fragments of new messages are rejected if their buffer would exceed this value.
+    pub fn with_max_buffered_len(mut self, max_buffered_len: usize) -> Self {
+        self.max_buffered_len = max_buffered_len;
+        self
+    }
+
+    /// Add a fragment, and return the complete message if all fragments have been received
+    ///
+    /// `fragment` is the fragment data following the handshake header `hdr`.
+    ///
+    /// Returns `Ok(None)` if the message is not complete yet. If the fragment is rejected, the
+    /// fragments already received for this message are kept.
+    pub fn add_fragment(
+        &mut self,
+        hdr: &DTLSHandshakeHeader,
+        fragment: &[u8],
+    ) -> Result<Option<DTLSReassembledMessage>, DTLSReassemblyError> {
+        if hdr.fragment_length as usize != fragment.len() {
+            return Err(DTLSReassemblyError::InvalidFragmentLength);
+        }
+        if hdr.length > self.max_message_len {
+            return Err(DTLSReassemblyError::MessageTooLarge);
+        }
+        let start = hdr.fragment_offset as usize;
+        if start + fragment.len() > hdr.length as usize {
+            return Err(DTLSReassemblyError::OutOfBounds);
+        }
+        if !self.messages.contains_key(&hdr.message_seq) {
+            if self.messages.len() >= self.max_pending_messages {
+                return Err(DTLSReassemblyError::TooManyPendingMessages);
+            }
+            if self.buffered_len + hdr.length as usize > self.max_buffered_len {
+                return Err(DTLSReassemblyError::BufferLimitExceeded);
+            }
+            self.buffered_len += hdr.length as usize;
+        }
+        let partial = self
+            .messages
+            .entry(hdr.message_seq)
+            .or_insert_with(|| PartialMessage {
+                msg_type: hdr.msg_type,
+                data: vec![0; hdr.length as usize],
+                ranges: Vec::new(),
+            });
+        if partial.msg_type != hdr.msg_type || partial.data.len() != hdr.length as usize {
+            return Err(DTLSReassemblyError::InconsistentHeader);
+        }
+        partial.insert(start, fragment)?;
+        if !partial.is_complete() {
+            return Ok(None);
+        }
+        self.buffered_len -= partial.data.len();
+        let msg = self
+            .messages
+            .remove(&hdr.message_seq)
+            .map(|partial| DTLSReassembledMessage {
+                msg_type: partial.msg_type,
+                message_seq: hdr.message_seq,
+                body: partial.data,
+            });
+        Ok(msg)
+    }
+
+    /// Tell if there is no incomplete message
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Return the sequence numbers of incomplete messages
+    pub fn pending_messages(&self) -> impl Iterator<Item = u16> + '_ {
+        self.messages.keys().copied()
+    }
+
+    /// Return the missing byte ranges (`start..end`) of an incomplete message
+    ///
+    /// Returns `None` if no fragment has been received for this sequence number.
+    pub fn missing_ranges(&self, message_seq: u16) -> Option<Vec<(usize, usize)>> {
+        let partial = self.messages.get(&message_seq)?;
+        let mut v = Vec::new();
+        let mut pos = 0;
+        for &(s, e) in &partial.ranges {
+            if s > pos {
+                v.push((pos, s));
+            }
+            pos = e;
+        }
+        if pos < partial.data.len() {
+            v.push((pos, partial.data.len()));
+        }
+        Some(v)
+    }
+
+    /// Drop all incomplete messages
+    pub fn clear(&mut self) {
+        self.messages.clear();
+        self.buffered_len = 0;
+    }
+}
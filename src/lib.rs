@@ -140,28 +140,49 @@ extern crate std;
 
 extern crate alloc;
 
+#[macro_use]
+mod macros;
+
 mod certificate_transparency;
 mod dtls;
+mod dtls_fragment;
+mod ssl2;
 mod tls;
 mod tls_alert;
+mod tls_audit;
 mod tls_ciphers;
 mod tls_debug;
 mod tls_dh;
 mod tls_ec;
+mod tls_error;
 mod tls_extensions;
+mod tls_grease;
+mod tls_limits;
+mod tls_reassembly;
 mod tls_sign_hash;
 mod tls_states;
+mod tls_stream;
+mod tls_summary;
 
 pub use certificate_transparency::*;
 pub use dtls::*;
+pub use dtls_fragment::*;
+pub use ssl2::*;
 pub use tls::*;
 pub use tls_alert::*;
+pub use tls_audit::*;
 pub use tls_ciphers::*;
 pub use tls_dh::*;
 pub use tls_ec::*;
+pub use tls_error::*;
 pub use tls_extensions::*;
+pub use tls_grease::*;
+pub use tls_limits::*;
+pub use tls_reassembly::*;
 pub use tls_sign_hash::*;
 pub use tls_states::*;
+pub use tls_stream::*;
+pub use tls_summary::*;
 
 #[cfg(all(feature = "serialize", not(feature = "std")))]
 compile_error!("features `serialize` cannot be enable when using `no_std`");
@@ -171,5 +192,13 @@ mod tls_serialize;
 #[cfg(feature = "serialize")]
 pub use tls_serialize::*;
 
+#[cfg(feature = "serde")]
+mod serde_hex;
+
+#[cfg(feature = "fingerprint")]
+mod tls_fingerprint;
+#[cfg(feature = "fingerprint")]
+pub use tls_fingerprint::*;
+
 pub use nom;
 pub use rusticata_macros;
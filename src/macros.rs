@@ -0,0 +1,69 @@
+//! Internal macros
+
+/// Declare the registered values of a newtype (for ex. an IANA registry)
+///
+/// The list of values is written only once, and is used to generate the constants (using
+/// `newtype_enum!`) and the functions depending on the registered values:
+///
+/// - `impl debug Type: from_u8(u8) { ... }` or `impl display ...`: constants, `Display` (and
+///   `Debug`) using the constant name, and a `const fn from_u8(v: u8) -> Option<Type>` returning
+///   `None` if the value is not registered
+/// - `impl names Type { Name = value => "registry name", ... }`: constants, `Display` using the
+///   registry name, `Debug` using the constant name, and a private `names()` function
+///
+/// `newtype_enum` must be imported in the calling module.
+macro_rules! registry_enum {
+    (impl $mode:ident $name:ident : $from:ident($ty:ty) {
+        $($key:ident = $val:expr),* $(,)?
+    }) => {
+        newtype_enum! {
+            impl $mode $name { $($key = $val),* }
+        }
+
+        impl $name {
+            /// Return the value for `v`, if it is registered
+            pub const fn $from(v: $ty) -> Option<$name> {
+                match $name(v) {
+                    $( $name::$key )|* => Some($name(v)),
+                    _ => None,
+                }
+            }
+        }
+    };
+
+    (impl names $name:ident {
+        $($key:ident = $val:expr => $reg:expr),* $(,)?
+    }) => {
+        newtype_enum! {
+            impl $name { $($key = $val),* }
+        }
+
+        impl $name {
+            /// Return the constant name and the registry name, or None if unknown
+            fn names(&self) -> Option<(&'static str, &'static str)> {
+                match *self {
+                    $( $name::$key => Some((stringify!($key), $reg)), )*
+                    _ => None,
+                }
+            }
+        }
+
+        impl core::fmt::Display for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                match self.names() {
+                    Some((_, name)) => f.write_str(name),
+                    None => write!(f, "{}({} / 0x{:x})", stringify!($name), self.0, self.0),
+                }
+            }
+        }
+
+        impl core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                match self.names() {
+                    Some((name, _)) => f.write_str(name),
+                    None => write!(f, "{}({} / 0x{:x})", stringify!($name), self.0, self.0),
+                }
+            }
+        }
+    };
+}
@@ -0,0 +1,61 @@
+//! Helpers to serialize byte slices as hex strings, using `serde`
+//!
+//! This module requires the `serde` feature.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use serde::{Serialize, Serializer};
+
+/// Wrapper to serialize a byte slice as a hex string
+struct Hex<'a>(&'a [u8]);
+
+impl<'a> Serialize for Hex<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = String::with_capacity(2 * self.0.len());
+        for b in self.0 {
+            let _ = write!(s, "{:02x}", b);
+        }
+        serializer.serialize_str(&s)
+    }
+}
+
+pub(crate) fn hex<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    Hex(data).serialize(serializer)
+}
+
+pub(crate) fn hex_opt<S: Serializer>(
+    data: &Option<&[u8]>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    data.map(Hex).serialize(serializer)
+}
+
+pub(crate) fn hex_vec<S: Serializer>(data: &[&[u8]], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(data.iter().map(|d| Hex(d)))
+}
+
+pub(crate) fn hex_vec_opt<S: Serializer>(
+    data: &Option<Vec<&[u8]>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    data.as_ref()
+        .map(|v| v.iter().map(|d| Hex(d)).collect::<Vec<_>>())
+        .serialize(serializer)
+}
+
+pub(crate) fn hex_tagged_opt<S: Serializer, T: Serialize>(
+    data: &Option<(T, &[u8])>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    data.as_ref()
+        .map(|(t, d)| (t, Hex(d)))
+        .serialize(serializer)
+}
+
+pub(crate) fn hex_tagged_vec<S: Serializer, T: Serialize>(
+    data: &[(T, &[u8])],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(data.iter().map(|(t, d)| (t, Hex(d))))
+}
@@ -0,0 +1,95 @@
+//! SSL 2.0 ClientHello
+//!
+//! SSL 2.0 is obsolete (RFC6176), but some clients still send a ClientHello using the SSL 2.0
+//! framing, either to negotiate SSL 2.0 or to be compatible with old servers (Appendix E.2 of
+//! RFC5246). The record header is not a TLS record header: it starts with a 2-bytes length with
+//! the high bit set.
+
+#[cfg(feature = "serde")]
+use crate::serde_hex::*;
+use crate::tls::{TlsCipherSuiteID, TlsVersion};
+use alloc::vec::Vec;
+use nom::bytes::streaming::take;
+use nom::combinator::{all_consuming, complete, map, map_parser, verify};
+use nom::multi::count;
+use nom::number::streaming::{be_u16, be_u24, be_u8};
+use nom::IResult;
+use nom_derive::Parse;
+use rusticata_macros::newtype_enum;
+
+/// SSL 2.0 message type for ClientHello
+pub const SSL2_MT_CLIENT_HELLO: u8 = 1;
+
+/// SSL 2.0 cipher kind
+///
+/// Cipher specs are 3 bytes long. Values with a first byte of 0 are TLS cipher suites (Appendix
+/// E.2 of RFC5246).
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Ssl2CipherSpec(pub u32);
+
+newtype_enum! {
+impl debug Ssl2CipherSpec {
+    Rc4_128WithMd5              = 0x01_0080,
+    Rc4_128Export40WithMd5      = 0x02_0080,
+    Rc2_128CbcWithMd5           = 0x03_0080,
+    Rc2_128CbcExport40WithMd5   = 0x04_0080,
+    Idea128CbcWithMd5           = 0x05_0080,
+    Des64CbcWithMd5             = 0x06_0040,
+    Des192Ede3CbcWithMd5        = 0x07_00c0,
+}
+}
+
+impl Ssl2CipherSpec {
+    /// Return the TLS cipher suite identifier, if this cipher spec is a TLS cipher suite
+    pub const fn tls_cipher_suite_id(&self) -> Option<TlsCipherSuiteID> {
+        if self.0 >> 16 == 0 {
+            Some(TlsCipherSuiteID(self.0 as u16))
+        } else {
+            None
+        }
+    }
+}
+
+/// SSL 2.0 ClientHello
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Ssl2ClientHello<'a> {
+    /// Highest version supported by the client (for ex. 0x0002 for SSL 2.0, or a TLS version)
+    pub version: TlsVersion,
+    pub cipher_specs: Vec<Ssl2CipherSpec>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "hex"))]
+    pub session_id: &'a [u8],
+    #[cfg_attr(feature = "serde", serde(serialize_with = "hex"))]
+    pub challenge: &'a [u8],
+}
+
+fn parse_ssl2_client_hello_content(i: &[u8]) -> IResult<&[u8], Ssl2ClientHello<'_>> {
+    let (i, _) = verify(be_u8, |&t| t == SSL2_MT_CLIENT_HELLO)(i)?;
+    let (i, version) = TlsVersion::parse(i)?;
+    let (i, cipher_specs_len) = verify(be_u16, |&n| n > 0 && n % 3 == 0)(i)?;
+    let (i, session_id_len) = verify(be_u16, |&n| n == 0 || n == 16)(i)?;
+    let (i, challenge_len) = verify(be_u16, |&n| (16..=32).contains(&n))(i)?;
+    let (i, cipher_specs) = count(map(be_u24, Ssl2CipherSpec), (cipher_specs_len / 3) as usize)(i)?;
+    let (i, session_id) = take(session_id_len as usize)(i)?;
+    let (i, challenge) = take(challenge_len as usize)(i)?;
+    let hello = Ssl2ClientHello {
+        version,
+        cipher_specs,
+        session_id,
+        challenge,
+    };
+    Ok((i, hello))
+}
+
+/// Parse a SSL 2.0 ClientHello, including the 2-bytes record header
+///
+/// TLS records are rejected, since the first byte of a TLS record never has the high bit set.
+/// The ClientHello must fill the record: trailing bytes are rejected.
+pub fn parse_ssl2_client_hello(i: &[u8]) -> IResult<&[u8], Ssl2ClientHello<'_>> {
+    let (i, len) = verify(be_u16, |&n| n & 0x8000 != 0)(i)?;
+    map_parser(
+        take((len & 0x7fff) as usize),
+        all_consuming(complete(parse_ssl2_client_hello_content)),
+    )(i)
+}
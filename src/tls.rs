@@ -4,19 +4,28 @@
 use alloc::{vec, vec::Vec};
 use core::convert::TryInto;
 use core::fmt;
+use core::iter::FusedIterator;
 use core::ops::Deref;
 use nom::branch::alt;
 use nom::bytes::streaming::take;
-use nom::combinator::{complete, cond, map, map_parser, opt, verify};
+use nom::combinator::{all_consuming, complete, cond, map, map_parser, verify};
 use nom::error::{make_error, ErrorKind};
 use nom::multi::{length_count, length_data, many0, many1};
 use nom::number::streaming::{be_u16, be_u24, be_u32, be_u8};
 use nom_derive::*;
 use rusticata_macros::newtype_enum;
 
+#[cfg(feature = "serde")]
+use crate::serde_hex::*;
 use crate::tls_alert::*;
 use crate::tls_ciphers::*;
 use crate::tls_ec::ECPoint;
+use crate::tls_extensions::{
+    parse_tls_client_hello_extensions, parse_tls_extensions_iter, CertificateStatusType,
+    ServerName, TlsExtension,
+};
+use crate::tls_grease::is_grease_u16;
+use crate::tls_sign_hash::SignatureScheme;
 
 pub use nom::{Err, IResult};
 
@@ -29,10 +38,11 @@ pub const MAX_RECORD_LEN: u16 = 1 << 14;
 /// the [IANA HandshakeType
 /// Registry](https://www.iana.org/assignments/tls-parameters/tls-parameters.xhtml#tls-parameters-7)
 #[derive(Clone, Copy, PartialEq, Eq, NomBE)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TlsHandshakeType(pub u8);
 
-newtype_enum! {
-impl debug TlsHandshakeType {
+registry_enum! {
+impl debug TlsHandshakeType: from_u8(u8) {
     HelloRequest        = 0x00,
     ClientHello         = 0x01,
     ServerHello         = 0x02,
@@ -51,6 +61,7 @@ impl debug TlsHandshakeType {
     CertificateURL      = 0x15,
     CertificateStatus   = 0x16,
     KeyUpdate           = 0x18,
+    CompressedCertificate = 0x19,
 
     NextProtocol        = 0x43,
 }
@@ -67,6 +78,7 @@ impl From<TlsHandshakeType> for u8 {
 /// Only the TLS version defined in the TLS message header is meaningful, the
 /// version defined in the record should be ignored or set to TLS 1.0
 #[derive(Clone, Copy, Default, PartialEq, Eq, NomBE)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TlsVersion(pub u16);
 
 impl TlsVersion {
@@ -77,6 +89,7 @@ impl TlsVersion {
 
 newtype_enum! {
 impl debug TlsVersion {
+    Ssl20        = 0x0002,
     Ssl30        = 0x0300,
     Tls10        = 0x0301,
     Tls11        = 0x0302,
@@ -96,6 +109,12 @@ impl debug TlsVersion {
 }
 }
 
+impl From<u16> for TlsVersion {
+    fn from(v: u16) -> TlsVersion {
+        TlsVersion(v)
+    }
+}
+
 impl From<TlsVersion> for u16 {
     fn from(v: TlsVersion) -> u16 {
         v.0
@@ -127,10 +146,11 @@ impl From<TlsHeartbeatMessageType> for u8 {
 
 /// Content type, as defined in IANA TLS ContentType registry
 #[derive(Clone, Copy, PartialEq, Eq, NomBE)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TlsRecordType(pub u8);
 
-newtype_enum! {
-impl debug TlsRecordType {
+registry_enum! {
+impl debug TlsRecordType: from_u8(u8) {
     ChangeCipherSpec = 0x14,
     Alert            = 0x15,
     Handshake        = 0x16,
@@ -146,6 +166,7 @@ impl From<TlsRecordType> for u8 {
 }
 
 #[derive(Clone, Copy, Default, PartialEq, Eq, NomBE)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TlsCompressionID(pub u8);
 
 newtype_enum! {
@@ -175,6 +196,7 @@ impl AsRef<u8> for TlsCompressionID {
 }
 
 #[derive(Clone, Copy, Default, PartialEq, Eq, NomBE)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TlsCipherSuiteID(pub u16);
 
 impl TlsCipherSuiteID {
@@ -223,17 +245,36 @@ impl fmt::LowerHex for TlsCipherSuiteID {
     }
 }
 
+fn rand_time(random: &[u8]) -> u32 {
+    random
+        .get(..4)
+        .and_then(|b| b.try_into().ok())
+        .map(u32::from_be_bytes)
+        .unwrap_or(0)
+}
+
+fn extension_ids(ext: Option<&[u8]>) -> Vec<u16> {
+    ext.map(|ext| {
+        parse_tls_extensions_iter(ext)
+            .map_while(|r| r.ok().map(|(ext_type, _)| ext_type))
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
 /// A trait that both TLS & DTLS satisfy
 pub trait ClientHello<'a> {
     /// TLS version of message
     fn version(&self) -> TlsVersion;
+    /// The full random (32 bytes), including the `gmt_unix_time` part
     fn random(&self) -> &'a [u8];
+    /// Get the random as a 32-bytes array, or `None` if the random does not have the expected size
+    fn random_array(&self) -> Option<&'a [u8; 32]> {
+        self.random().try_into().ok()
+    }
     // Get the first part (4 bytes) of random
     fn rand_time(&self) -> u32 {
-        self.random()
-            .try_into()
-            .map(u32::from_be_bytes)
-            .unwrap_or(0)
+        rand_time(self.random())
     }
     // Get the second part (28 bytes) of random
     fn rand_bytes(&self) -> &'a [u8] {
@@ -248,9 +289,79 @@ pub trait ClientHello<'a> {
             .map(|&x| x.get_ciphersuite())
             .collect()
     }
+    /// The ciphers supported by client, with lookup helpers
+    fn cipher_list(&self) -> CipherSuiteList<'_> {
+        CipherSuiteList::new(self.ciphers())
+    }
     /// A list of compression methods supported by client
     fn comp(&self) -> &Vec<TlsCompressionID>;
     fn ext(&self) -> Option<&'a [u8]>;
+
+    /// Parse the extensions
+    ///
+    /// Return `None` if there are no extensions, or if they cannot be parsed.
+    fn extensions(&self) -> Option<Vec<TlsExtension<'a>>> {
+        let (_, v) = parse_tls_client_hello_extensions(self.ext()?).ok()?;
+        Some(v)
+    }
+
+    /// Return the types of the extensions, in wire order
+    ///
+    /// The extension contents are not parsed, so this includes unknown, GREASE, duplicate and
+    /// invalid extensions. If the extensions block is truncated, the types read before the
+    /// error are returned.
+    fn extension_ids(&self) -> Vec<u16> {
+        extension_ids(self.ext())
+    }
+
+    /// Return the server names of the Server Name Indication extension
+    ///
+    /// The extensions are parsed on every call.
+    fn find_sni(&self) -> Option<Vec<ServerName<'a>>> {
+        self.extensions()?.into_iter().find_map(|ext| match ext {
+            TlsExtension::SNI(v) => Some(v),
+            _ => None,
+        })
+    }
+
+    /// Return the protocols of the Application-Layer Protocol Negotiation extension
+    ///
+    /// The extensions are parsed on every call.
+    fn find_alpn(&self) -> Option<Vec<&'a [u8]>> {
+        self.extensions()?.into_iter().find_map(|ext| match ext {
+            TlsExtension::ALPN(v) => Some(v),
+            _ => None,
+        })
+    }
+
+    /// Return the versions of the Supported Versions extension
+    ///
+    /// The extensions are parsed on every call.
+    fn supported_versions(&self) -> Option<Vec<TlsVersion>> {
+        self.extensions()?.into_iter().find_map(|ext| match ext {
+            TlsExtension::SupportedVersions(v) => Some(v),
+            _ => None,
+        })
+    }
+
+    /// Return the versions of the Supported Versions extension, ignoring GREASE values
+    ///
+    /// The wire order is preserved. The extensions are parsed on every call.
+    fn non_grease_versions(&self) -> Option<Vec<TlsVersion>> {
+        let mut v = self.supported_versions()?;
+        v.retain(|v| !is_grease_u16(v.0));
+        Some(v)
+    }
+
+    /// Return the signature schemes of the Signature Algorithms extension
+    ///
+    /// The extensions are parsed on every call.
+    fn signature_algorithms(&self) -> Option<Vec<u16>> {
+        self.extensions()?.into_iter().find_map(|ext| match ext {
+            TlsExtension::SignatureAlgorithms(v) => Some(v),
+            _ => None,
+        })
+    }
 }
 
 /// TLS Client Hello (from TLS 1.0 to TLS 1.2)
@@ -258,16 +369,20 @@ pub trait ClientHello<'a> {
 /// Some fields are unparsed (for performance reasons), for ex to parse `ext`,
 /// call the `parse_tls_extensions` function.
 #[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TlsClientHelloContents<'a> {
     /// TLS version of message
     pub version: TlsVersion,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "hex"))]
     pub random: &'a [u8],
+    #[cfg_attr(feature = "serde", serde(serialize_with = "hex_opt"))]
     pub session_id: Option<&'a [u8]>,
     /// A list of ciphers supported by client
     pub ciphers: Vec<TlsCipherSuiteID>,
     /// A list of compression methods supported by client
     pub comp: Vec<TlsCompressionID>,
 
+    #[cfg_attr(feature = "serde", serde(serialize_with = "hex_opt"))]
     pub ext: Option<&'a [u8]>,
 }
 
@@ -297,6 +412,35 @@ impl<'a> TlsClientHelloContents<'a> {
     pub fn get_ciphers(&self) -> Vec<Option<&'static TlsCipherSuite>> {
         self.ciphers.iter().map(|&x| x.get_ciphersuite()).collect()
     }
+
+    /// Return an iterator over the cipher suites, ignoring GREASE values
+    pub fn non_grease_ciphers(&self) -> impl Iterator<Item = TlsCipherSuiteID> + '_ {
+        self.ciphers.iter().copied().filter(|c| !is_grease_u16(c.0))
+    }
+
+    /// Return the highest version supported by the client
+    ///
+    /// TLS 1.3 clients announce their versions in the Supported Versions extension, while the
+    /// `version` field is set to TLS 1.2. If the extension is present, return the highest
+    /// version of the extension (ignoring GREASE and unknown values, and ordering TLS 1.3 drafts
+    /// between TLS 1.2 and TLS 1.3). Otherwise, return the `version` field.
+    pub fn max_supported_version(&self) -> TlsVersion {
+        // rank versions: (major version, draft number)
+        fn rank(v: TlsVersion) -> Option<(u16, u16)> {
+            match v.0 & 0xff00 {
+                0x0300 => Some((v.0, 0)),
+                0x7f00 => Some((0x0303, 1 + (v.0 & 0xff))),
+                _ => None,
+            }
+        }
+        self.non_grease_versions()
+            .and_then(|v| {
+                v.into_iter()
+                    .filter(|&v| rank(v).is_some())
+                    .max_by_key(|&v| rank(v))
+            })
+            .unwrap_or(self.version)
+    }
 }
 
 impl<'a> ClientHello<'a> for TlsClientHelloContents<'a> {
@@ -327,13 +471,17 @@ impl<'a> ClientHello<'a> for TlsClientHelloContents<'a> {
 
 /// TLS Server Hello (from TLS 1.0 to TLS 1.2)
 #[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TlsServerHelloContents<'a> {
     pub version: TlsVersion,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "hex"))]
     pub random: &'a [u8],
+    #[cfg_attr(feature = "serde", serde(serialize_with = "hex_opt"))]
     pub session_id: Option<&'a [u8]>,
     pub cipher: TlsCipherSuiteID,
     pub compression: TlsCompressionID,
 
+    #[cfg_attr(feature = "serde", serde(serialize_with = "hex_opt"))]
     pub ext: Option<&'a [u8]>,
 }
 
@@ -382,6 +530,109 @@ impl<'a> TlsServerHelloContents<'a> {
     pub fn get_cipher(&self) -> Option<&'static TlsCipherSuite> {
         self.cipher.get_ciphersuite()
     }
+
+    /// Return the downgrade protection sentinel, if present in the last 8 bytes of the random
+    ///
+    /// A TLS 1.3 server negotiating an older version must set this value
+    /// ([RFC8446](https://tools.ietf.org/html/rfc8446) section 4.1.3). If the client supports
+    /// TLS 1.3, the presence of a sentinel may indicate a downgrade attack.
+    pub fn downgrade_sentinel(&self) -> Option<DowngradeSentinel> {
+        let suffix = self
+            .random
+            .len()
+            .checked_sub(8)
+            .map(|n| &self.random[n..])?;
+        match suffix {
+            b"DOWNGRD\x01" => Some(DowngradeSentinel::Tls12),
+            b"DOWNGRD\x00" => Some(DowngradeSentinel::Tls11OrBelow),
+            _ => None,
+        }
+    }
+}
+
+/// Downgrade protection sentinel, as defined in [RFC8446](https://tools.ietf.org/html/rfc8446)
+/// section 4.1.3
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DowngradeSentinel {
+    /// `DOWNGRD\x01`: the server negotiated TLS 1.2
+    Tls12,
+    /// `DOWNGRD\x00`: the server negotiated TLS 1.1 or below
+    Tls11OrBelow,
+}
+
+/// A trait that all TLS ServerHello variants satisfy
+pub trait ServerHello<'a> {
+    /// TLS version of message
+    fn version(&self) -> TlsVersion;
+    /// The full random (32 bytes), including the `gmt_unix_time` part
+    fn random(&self) -> &'a [u8];
+    /// Get the random as a 32-bytes array, or `None` if the random does not have the expected size
+    fn random_array(&self) -> Option<&'a [u8; 32]> {
+        self.random().try_into().ok()
+    }
+    // Get the first part (4 bytes) of random
+    fn rand_time(&self) -> u32 {
+        rand_time(self.random())
+    }
+    // Get the second part (28 bytes) of random
+    fn rand_bytes(&self) -> &'a [u8] {
+        self.random().get(4..).unwrap_or(&[])
+    }
+    /// The cipher selected by the server
+    fn cipher(&self) -> TlsCipherSuiteID;
+    fn ext(&self) -> Option<&'a [u8]>;
+
+    /// Return the types of the extensions, in wire order
+    ///
+    /// See `ClientHello::extension_ids`.
+    fn extension_ids(&self) -> Vec<u16> {
+        extension_ids(self.ext())
+    }
+}
+
+impl<'a> ServerHello<'a> for TlsServerHelloContents<'a> {
+    fn version(&self) -> TlsVersion {
+        self.version
+    }
+
+    fn random(&self) -> &'a [u8] {
+        self.random
+    }
+
+    fn cipher(&self) -> TlsCipherSuiteID {
+        self.cipher
+    }
+
+    fn ext(&self) -> Option<&'a [u8]> {
+        self.ext
+    }
+}
+
+impl<'a> ServerHello<'a> for TlsServerHelloV13Draft18Contents<'a> {
+    fn version(&self) -> TlsVersion {
+        self.version
+    }
+
+    fn random(&self) -> &'a [u8] {
+        self.random
+    }
+
+    fn cipher(&self) -> TlsCipherSuiteID {
+        self.cipher
+    }
+
+    fn ext(&self) -> Option<&'a [u8]> {
+        self.ext
+    }
+}
+
+/// Encrypted extensions (TLS 1.3), as defined in [RFC8446](https://tools.ietf.org/html/rfc8446)
+/// section 4.3.1
+///
+/// This message is encrypted, and can only be parsed after decryption.
+#[derive(Clone, PartialEq)]
+pub struct TlsEncryptedExtensionsContents<'a> {
+    pub ext: &'a [u8],
 }
 
 /// Session ticket, as defined in [RFC5077](https://tools.ietf.org/html/rfc5077)
@@ -391,6 +642,18 @@ pub struct TlsNewSessionTicketContent<'a> {
     pub ticket: &'a [u8],
 }
 
+/// Session ticket (TLS 1.3), as defined in [RFC8446](https://tools.ietf.org/html/rfc8446)
+/// section 4.6.1
+#[derive(Clone, Debug, PartialEq)]
+pub struct TlsNewSessionTicketV13Contents<'a> {
+    /// Ticket lifetime, in seconds
+    pub ticket_lifetime: u32,
+    pub ticket_age_add: u32,
+    pub ticket_nonce: &'a [u8],
+    pub ticket: &'a [u8],
+    pub ext: &'a [u8],
+}
+
 /// A raw certificate, which should be a DER-encoded X.509 certificate.
 ///
 /// See [RFC5280](https://tools.ietf.org/html/rfc5280) for X509v3 certificate format.
@@ -406,6 +669,26 @@ pub struct TlsCertificateContents<'a> {
     pub cert_chain: Vec<RawCertificate<'a>>,
 }
 
+/// A certificate and its extensions (TLS 1.3)
+///
+/// The certificate type depends on the negotiated certificate type (usually a DER-encoded X.509
+/// certificate).
+#[derive(Clone, PartialEq)]
+pub struct CertificateEntry<'a> {
+    pub cert_data: &'a [u8],
+    /// Raw extensions (for ex. OCSP status or SCT list)
+    pub ext: &'a [u8],
+}
+
+/// The certificate message (TLS 1.3), as defined in
+/// [RFC8446](https://tools.ietf.org/html/rfc8446) section 4.4.2
+#[derive(Clone, PartialEq)]
+pub struct TlsCertificateV13Contents<'a> {
+    /// Empty, unless the message is a response to a post-handshake CertificateRequest
+    pub certificate_request_context: &'a [u8],
+    pub cert_list: Vec<CertificateEntry<'a>>,
+}
+
 /// Certificate request, as defined in [RFC5246](https://tools.ietf.org/html/rfc5246) section 7.4.4
 ///
 /// Note: TLS 1.2 adds SignatureAndHashAlgorithm (chapter 7.4.4) but do not declare it in A.4.2
@@ -418,6 +701,17 @@ pub struct TlsCertificateRequestContents<'a> {
     pub unparsed_ca: Vec<&'a [u8]>,
 }
 
+/// Certificate request (TLS 1.3), as defined in [RFC8446](https://tools.ietf.org/html/rfc8446)
+/// section 4.3.2
+///
+/// The requested signature algorithms and certificate authorities are sent as extensions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TlsCertificateRequestV13Contents<'a> {
+    /// Empty, unless the message is a post-handshake CertificateRequest
+    pub certificate_request_context: &'a [u8],
+    pub ext: &'a [u8],
+}
+
 /// Server key exchange parameters
 ///
 /// This is an opaque struct, since the content depends on the selected
@@ -427,6 +721,28 @@ pub struct TlsServerKeyExchangeContents<'a> {
     pub parameters: &'a [u8],
 }
 
+/// Finished message
+///
+/// The length of `verify_data` depends on the cipher suite (12 bytes before TLS 1.3, the size of
+/// the hash in TLS 1.3), so it is not checked.
+#[derive(Clone, PartialEq)]
+pub struct TlsFinishedContents<'a> {
+    pub verify_data: &'a [u8],
+}
+
+/// Certificate verify (TLS 1.2 and 1.3), as defined in
+/// [RFC8446](https://tools.ietf.org/html/rfc8446) section 4.4.3
+///
+/// The message is stored as opaque in `TlsMessageHandshake::CertificateVerify`, since its
+/// content depends on the TLS version: use `parse_tls_certificate_verify` to decode it for TLS
+/// 1.2 and 1.3 (before TLS 1.2, the message has no signature scheme, see
+/// `parse_digitally_signed_old`).
+#[derive(Clone, PartialEq)]
+pub struct TlsCertificateVerifyContents<'a> {
+    pub scheme: SignatureScheme,
+    pub signature: &'a [u8],
+}
+
 /// Client key exchange parameters
 ///
 /// Content depends on the selected key exchange method.
@@ -440,10 +756,23 @@ pub enum TlsClientKeyExchangeContents<'a> {
 /// Certificate status response, as defined in [RFC6066](https://tools.ietf.org/html/rfc6066) section 8
 #[derive(Clone, Debug, PartialEq)]
 pub struct TlsCertificateStatusContents<'a> {
+    /// Should match a `CertificateStatusType` value
     pub status_type: u8,
+    /// The response, for ex. a DER-encoded OCSPResponse if `status_type` is OCSP
     pub blob: &'a [u8],
 }
 
+impl<'a> TlsCertificateStatusContents<'a> {
+    /// Return the DER-encoded OCSP response, if the status type is OCSP
+    pub fn ocsp_response(&self) -> Option<&'a [u8]> {
+        if self.status_type == CertificateStatusType::OCSP.0 {
+            Some(self.blob)
+        } else {
+            None
+        }
+    }
+}
+
 /// Next protocol response, defined in
 /// [draft-agl-tls-nextprotoneg-03](https://tools.ietf.org/html/draft-agl-tls-nextprotoneg-03)
 #[derive(Clone, Debug, PartialEq)]
@@ -457,12 +786,42 @@ pub struct TlsNextProtocolContent<'a> {
 pub struct KeyUpdateRequest(pub u8);
 
 newtype_enum! {
-impl KeyUpdateRequest {
+impl debug KeyUpdateRequest {
     NotRequested  = 0x0,
     Requested     = 0x1,
 }
 }
 
+/// Key update message (TLS 1.3), as defined in [RFC8446](https://tools.ietf.org/html/rfc8446)
+/// section 4.6.3
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TlsKeyUpdateContents {
+    pub request_update: KeyUpdateRequest,
+}
+
+/// Certificate compression algorithm, as defined in [RFC8879](https://tools.ietf.org/html/rfc8879)
+#[derive(Copy, Clone, PartialEq, Eq, NomBE)]
+pub struct CertificateCompressionAlgorithm(pub u16);
+
+newtype_enum! {
+impl debug CertificateCompressionAlgorithm {
+    Zlib   = 1,
+    Brotli = 2,
+    Zstd   = 3,
+}
+}
+
+/// Compressed certificate message, as defined in [RFC8879](https://tools.ietf.org/html/rfc8879)
+///
+/// The compressed data is not decompressed. Once decompressed, the data (of length
+/// `uncompressed_length`) is the body of a Certificate message.
+#[derive(Clone, PartialEq)]
+pub struct TlsCompressedCertificateContents<'a> {
+    pub algorithm: CertificateCompressionAlgorithm,
+    pub uncompressed_length: u32,
+    pub compressed_certificate_message: &'a [u8],
+}
+
 /// Generic handshake message
 #[derive(Clone, Debug, PartialEq)]
 pub enum TlsMessageHandshake<'a> {
@@ -471,18 +830,59 @@ pub enum TlsMessageHandshake<'a> {
     ServerHello(TlsServerHelloContents<'a>),
     ServerHelloV13Draft18(TlsServerHelloV13Draft18Contents<'a>),
     NewSessionTicket(TlsNewSessionTicketContent<'a>),
+    NewSessionTicketV13(TlsNewSessionTicketV13Contents<'a>),
     EndOfEarlyData,
     HelloRetryRequest(TlsHelloRetryRequestContents<'a>),
+    EncryptedExtensions(TlsEncryptedExtensionsContents<'a>),
     Certificate(TlsCertificateContents<'a>),
+    CertificateV13(TlsCertificateV13Contents<'a>),
     ServerKeyExchange(TlsServerKeyExchangeContents<'a>),
     CertificateRequest(TlsCertificateRequestContents<'a>),
+    CertificateRequestV13(TlsCertificateRequestV13Contents<'a>),
     ServerDone(&'a [u8]),
     CertificateVerify(&'a [u8]),
     ClientKeyExchange(TlsClientKeyExchangeContents<'a>),
-    Finished(&'a [u8]),
+    Finished(TlsFinishedContents<'a>),
     CertificateStatus(TlsCertificateStatusContents<'a>),
     NextProtocol(TlsNextProtocolContent<'a>),
-    KeyUpdate(u8),
+    KeyUpdate(TlsKeyUpdateContents),
+    CompressedCertificate(TlsCompressedCertificateContents<'a>),
+}
+
+impl<'a> TlsMessageHandshake<'a> {
+    /// Return the handshake type of the message
+    ///
+    /// Version-specific variants (for ex. `CertificateV13`) share the type of the generic message.
+    pub fn handshake_type(&self) -> TlsHandshakeType {
+        match self {
+            TlsMessageHandshake::HelloRequest => TlsHandshakeType::HelloRequest,
+            TlsMessageHandshake::ClientHello(_) => TlsHandshakeType::ClientHello,
+            TlsMessageHandshake::ServerHello(_) | TlsMessageHandshake::ServerHelloV13Draft18(_) => {
+                TlsHandshakeType::ServerHello
+            }
+            TlsMessageHandshake::NewSessionTicket(_)
+            | TlsMessageHandshake::NewSessionTicketV13(_) => TlsHandshakeType::NewSessionTicket,
+            TlsMessageHandshake::EndOfEarlyData => TlsHandshakeType::EndOfEarlyData,
+            TlsMessageHandshake::HelloRetryRequest(_) => TlsHandshakeType::HelloRetryRequest,
+            TlsMessageHandshake::EncryptedExtensions(_) => TlsHandshakeType::EncryptedExtensions,
+            TlsMessageHandshake::Certificate(_) | TlsMessageHandshake::CertificateV13(_) => {
+                TlsHandshakeType::Certificate
+            }
+            TlsMessageHandshake::ServerKeyExchange(_) => TlsHandshakeType::ServerKeyExchange,
+            TlsMessageHandshake::CertificateRequest(_)
+            | TlsMessageHandshake::CertificateRequestV13(_) => TlsHandshakeType::CertificateRequest,
+            TlsMessageHandshake::ServerDone(_) => TlsHandshakeType::ServerDone,
+            TlsMessageHandshake::CertificateVerify(_) => TlsHandshakeType::CertificateVerify,
+            TlsMessageHandshake::ClientKeyExchange(_) => TlsHandshakeType::ClientKeyExchange,
+            TlsMessageHandshake::Finished(_) => TlsHandshakeType::Finished,
+            TlsMessageHandshake::CertificateStatus(_) => TlsHandshakeType::CertificateStatus,
+            TlsMessageHandshake::NextProtocol(_) => TlsHandshakeType::NextProtocol,
+            TlsMessageHandshake::KeyUpdate(_) => TlsHandshakeType::KeyUpdate,
+            TlsMessageHandshake::CompressedCertificate(_) => {
+                TlsHandshakeType::CompressedCertificate
+            }
+        }
+    }
 }
 
 /// TLS application data
@@ -501,18 +901,33 @@ pub struct TlsMessageApplicationData<'a> {
 #[derive(Clone, Debug, PartialEq)]
 pub struct TlsMessageHeartbeat<'a> {
     pub heartbeat_type: TlsHeartbeatMessageType,
+    /// Payload length, as declared in the message
     pub payload_len: u16,
+    /// Payload data
+    ///
+    /// This can be shorter than `payload_len`, if the declared length exceeds the record length
+    /// (heartbleed).
     pub payload: &'a [u8],
+    /// Remaining bytes of the record, after the payload
+    pub padding: &'a [u8],
 }
 
 /// TLS record header
 #[derive(Clone, Copy, PartialEq, NomBE)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TlsRecordHeader {
     pub record_type: TlsRecordType,
     pub version: TlsVersion,
     pub len: u16,
 }
 
+impl TlsRecordHeader {
+    /// Return the content type of the record
+    pub const fn record_type(&self) -> TlsRecordType {
+        self.record_type
+    }
+}
+
 /// TLS plaintext message
 ///
 /// Plaintext records can only be found during the handshake.
@@ -559,6 +974,36 @@ pub struct TlsRawRecord<'a> {
     pub data: &'a [u8],
 }
 
+impl<'a> TlsRawRecord<'a> {
+    /// Copy the record data, to get a record independent of the input buffer
+    pub fn to_owned_record(&self) -> TlsRecordOwned {
+        TlsRecordOwned {
+            hdr: self.hdr,
+            data: self.data.to_vec(),
+        }
+    }
+}
+
+/// TLS record, owning its data
+///
+/// This is an owned version of `TlsRawRecord`, which can be stored or sent to another thread
+/// without keeping the input buffer alive. Messages are parsed on demand, and borrow the record
+/// data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TlsRecordOwned {
+    pub hdr: TlsRecordHeader,
+    pub data: Vec<u8>,
+}
+
+impl TlsRecordOwned {
+    /// Parse the messages contained in the record
+    ///
+    /// See `parse_tls_record_with_header`.
+    pub fn parse_messages(&self) -> IResult<&[u8], Vec<TlsMessage<'_>>> {
+        parse_tls_record_with_header(&self.data, &self.hdr)
+    }
+}
+
 pub(crate) fn parse_cipher_suites(i: &[u8], len: usize) -> IResult<&[u8], Vec<TlsCipherSuiteID>> {
     if len == 0 {
         return Ok((i, Vec::new()));
@@ -602,7 +1047,7 @@ pub(crate) fn parse_tls_versions(i: &[u8]) -> IResult<&[u8], Vec<TlsVersion>> {
     Ok((&i[len..], v))
 }
 
-fn parse_certs(i: &[u8]) -> IResult<&[u8], Vec<RawCertificate>> {
+fn parse_certs(i: &[u8]) -> IResult<&[u8], Vec<RawCertificate<'_>>> {
     many0(complete(map(length_data(be_u24), |data| RawCertificate {
         data,
     })))(i)
@@ -618,12 +1063,50 @@ pub fn parse_tls_record_header(i: &[u8]) -> IResult<&[u8], TlsRecordHeader> {
     TlsRecordHeader::parse(i)
 }
 
+/// Tell if the first bytes of a stream look like a TLS record
+///
+/// This function checks the record header only: the record type must be one of
+/// `ChangeCipherSpec`, `Alert`, `Handshake` or `ApplicationData`, and the version must be
+/// between SSL 3.0 and TLS 1.3. If the length is present, it must not be 0 and must not exceed
+/// the maximum length of an encrypted record.
+///
+/// At least 3 bytes are required, otherwise `false` is returned. The record content is not
+/// parsed.
+pub fn looks_like_tls(first_bytes: &[u8]) -> bool {
+    let (record_type, major, minor) = match first_bytes {
+        [t, major, minor, ..] => (*t, *major, *minor),
+        _ => return false,
+    };
+    if !(0x14..=0x17).contains(&record_type) || major != 3 || minor > 4 {
+        return false;
+    }
+    match first_bytes.get(3..5) {
+        Some(len) => {
+            let len = u16::from_be_bytes([len[0], len[1]]);
+            len > 0 && len <= MAX_RECORD_LEN + 2048
+        }
+        None => true,
+    }
+}
+
 #[allow(clippy::unnecessary_wraps)]
-fn parse_tls_handshake_msg_hello_request(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake> {
+fn parse_tls_handshake_msg_hello_request(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake<'_>> {
     Ok((i, TlsMessageHandshake::HelloRequest))
 }
 
-fn parse_tls_handshake_msg_client_hello(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake> {
+/// Parse the extensions block of a hello message
+///
+/// The block is optional: if there is no remaining data, return `None`. Otherwise, the length
+/// must be present, and the block must be complete (it can be empty, in this case return
+/// `Some(&[])`).
+pub(crate) fn parse_hello_extensions_block(i: &[u8]) -> IResult<&[u8], Option<&[u8]>> {
+    if i.is_empty() {
+        return Ok((i, None));
+    }
+    map(complete(length_data(be_u16)), Some)(i)
+}
+
+fn parse_tls_handshake_msg_client_hello(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake<'_>> {
     let (i, version) = be_u16(i)?;
     let (i, random) = take(32usize)(i)?;
     let (i, sidlen) = verify(be_u8, |&n| n <= 32)(i)?;
@@ -632,14 +1115,14 @@ fn parse_tls_handshake_msg_client_hello(i: &[u8]) -> IResult<&[u8], TlsMessageHa
     let (i, ciphers) = parse_cipher_suites(i, ciphers_len as usize)?;
     let (i, comp_len) = be_u8(i)?;
     let (i, comp) = parse_compressions_algs(i, comp_len as usize)?;
-    let (i, ext) = opt(complete(length_data(be_u16)))(i)?;
+    let (i, ext) = parse_hello_extensions_block(i)?;
     let content = TlsClientHelloContents::new(version, random, sid, ciphers, comp, ext);
     Ok((i, TlsMessageHandshake::ClientHello(content)))
 }
 
 fn parse_tls_handshake_msg_server_hello_tlsv12<const HAS_EXT: bool>(
     i: &[u8],
-) -> IResult<&[u8], TlsMessageHandshake> {
+) -> IResult<&[u8], TlsMessageHandshake<'_>> {
     map(
         parse_tls_server_hello_tlsv12::<HAS_EXT>,
         TlsMessageHandshake::ServerHello,
@@ -648,7 +1131,7 @@ fn parse_tls_handshake_msg_server_hello_tlsv12<const HAS_EXT: bool>(
 
 pub(crate) fn parse_tls_server_hello_tlsv12<const HAS_EXT: bool>(
     i: &[u8],
-) -> IResult<&[u8], TlsServerHelloContents> {
+) -> IResult<&[u8], TlsServerHelloContents<'_>> {
     let (i, version) = be_u16(i)?;
     let (i, random) = take(32usize)(i)?;
     let (i, sidlen) = verify(be_u8, |&n| n <= 32)(i)?;
@@ -656,7 +1139,7 @@ pub(crate) fn parse_tls_server_hello_tlsv12<const HAS_EXT: bool>(
     let (i, cipher) = be_u16(i)?;
     let (i, comp) = be_u8(i)?;
     let (i, ext) = if HAS_EXT {
-        opt(complete(length_data(be_u16)))(i)?
+        parse_hello_extensions_block(i)?
     } else {
         (i, None)
     };
@@ -666,11 +1149,11 @@ pub(crate) fn parse_tls_server_hello_tlsv12<const HAS_EXT: bool>(
 
 fn parse_tls_handshake_msg_server_hello_tlsv13draft18(
     i: &[u8],
-) -> IResult<&[u8], TlsMessageHandshake> {
+) -> IResult<&[u8], TlsMessageHandshake<'_>> {
     let (i, version) = TlsVersion::parse(i)?;
     let (i, random) = take(32usize)(i)?;
     let (i, cipher) = map(be_u16, TlsCipherSuiteID)(i)?;
-    let (i, ext) = opt(complete(length_data(be_u16)))(i)?;
+    let (i, ext) = parse_hello_extensions_block(i)?;
     let content = TlsServerHelloV13Draft18Contents {
         version,
         random,
@@ -680,7 +1163,7 @@ fn parse_tls_handshake_msg_server_hello_tlsv13draft18(
     Ok((i, TlsMessageHandshake::ServerHelloV13Draft18(content)))
 }
 
-fn parse_tls_handshake_msg_server_hello(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake> {
+fn parse_tls_handshake_msg_server_hello(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake<'_>> {
     let (_, version) = be_u16(i)?;
     match version {
         0x7f12 => parse_tls_handshake_msg_server_hello_tlsv13draft18(i),
@@ -696,7 +1179,7 @@ fn parse_tls_handshake_msg_server_hello(i: &[u8]) -> IResult<&[u8], TlsMessageHa
 fn parse_tls_handshake_msg_newsessionticket(
     i: &[u8],
     len: usize,
-) -> IResult<&[u8], TlsMessageHandshake> {
+) -> IResult<&[u8], TlsMessageHandshake<'_>> {
     if len < 4 {
         return Err(Err::Error(make_error(i, ErrorKind::Verify)));
     }
@@ -709,10 +1192,30 @@ fn parse_tls_handshake_msg_newsessionticket(
     Ok((i, TlsMessageHandshake::NewSessionTicket(content)))
 }
 
-fn parse_tls_handshake_msg_hello_retry_request(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake> {
+fn parse_tls_handshake_msg_newsessionticket_v13(
+    i: &[u8],
+) -> IResult<&[u8], TlsMessageHandshake<'_>> {
+    let (i, ticket_lifetime) = be_u32(i)?;
+    let (i, ticket_age_add) = be_u32(i)?;
+    let (i, ticket_nonce) = length_data(be_u8)(i)?;
+    let (i, ticket) = verify(length_data(be_u16), |t: &[u8]| !t.is_empty())(i)?;
+    let (i, ext) = length_data(be_u16)(i)?;
+    let content = TlsNewSessionTicketV13Contents {
+        ticket_lifetime,
+        ticket_age_add,
+        ticket_nonce,
+        ticket,
+        ext,
+    };
+    Ok((i, TlsMessageHandshake::NewSessionTicketV13(content)))
+}
+
+fn parse_tls_handshake_msg_hello_retry_request(
+    i: &[u8],
+) -> IResult<&[u8], TlsMessageHandshake<'_>> {
     let (i, version) = TlsVersion::parse(i)?;
     let (i, cipher) = map(be_u16, TlsCipherSuiteID)(i)?;
-    let (i, ext) = opt(complete(length_data(be_u16)))(i)?;
+    let (i, ext) = parse_hello_extensions_block(i)?;
     let content = TlsHelloRetryRequestContents {
         version,
         cipher,
@@ -721,37 +1224,75 @@ fn parse_tls_handshake_msg_hello_retry_request(i: &[u8]) -> IResult<&[u8], TlsMe
     Ok((i, TlsMessageHandshake::HelloRetryRequest(content)))
 }
 
-pub(crate) fn parse_tls_certificate(i: &[u8]) -> IResult<&[u8], TlsCertificateContents> {
+fn parse_tls_handshake_msg_encrypted_extensions(
+    i: &[u8],
+) -> IResult<&[u8], TlsMessageHandshake<'_>> {
+    let (i, ext) = length_data(be_u16)(i)?;
+    let content = TlsEncryptedExtensionsContents { ext };
+    Ok((i, TlsMessageHandshake::EncryptedExtensions(content)))
+}
+
+pub(crate) fn parse_tls_certificate(i: &[u8]) -> IResult<&[u8], TlsCertificateContents<'_>> {
     let (i, cert_len) = be_u24(i)?;
     let (i, cert_chain) = map_parser(take(cert_len as usize), parse_certs)(i)?;
     let content = TlsCertificateContents { cert_chain };
     Ok((i, content))
 }
 
-fn parse_tls_handshake_msg_certificate(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake> {
+fn parse_tls_handshake_msg_certificate(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake<'_>> {
     map(parse_tls_certificate, TlsMessageHandshake::Certificate)(i)
 }
 
+fn parse_tls_certificate_entry(i: &[u8]) -> IResult<&[u8], CertificateEntry<'_>> {
+    let (i, cert_data) = length_data(be_u24)(i)?;
+    let (i, ext) = length_data(be_u16)(i)?;
+    Ok((i, CertificateEntry { cert_data, ext }))
+}
+
+fn parse_tls_handshake_msg_certificate_v13(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake<'_>> {
+    let (i, certificate_request_context) = length_data(be_u8)(i)?;
+    let (i, cert_list) = map_parser(
+        length_data(be_u24),
+        many0(complete(parse_tls_certificate_entry)),
+    )(i)?;
+    let content = TlsCertificateV13Contents {
+        certificate_request_context,
+        cert_list,
+    };
+    Ok((i, TlsMessageHandshake::CertificateV13(content)))
+}
+
 fn parse_tls_handshake_msg_serverkeyexchange(
     i: &[u8],
     len: usize,
-) -> IResult<&[u8], TlsMessageHandshake> {
+) -> IResult<&[u8], TlsMessageHandshake<'_>> {
     map(take(len), |ext| {
         TlsMessageHandshake::ServerKeyExchange(TlsServerKeyExchangeContents { parameters: ext })
     })(i)
 }
 
-fn parse_tls_handshake_msg_serverdone(i: &[u8], len: usize) -> IResult<&[u8], TlsMessageHandshake> {
+fn parse_tls_handshake_msg_serverdone(
+    i: &[u8],
+    len: usize,
+) -> IResult<&[u8], TlsMessageHandshake<'_>> {
     map(take(len), TlsMessageHandshake::ServerDone)(i)
 }
 
 fn parse_tls_handshake_msg_certificateverify(
     i: &[u8],
     len: usize,
-) -> IResult<&[u8], TlsMessageHandshake> {
+) -> IResult<&[u8], TlsMessageHandshake<'_>> {
     map(take(len), TlsMessageHandshake::CertificateVerify)(i)
 }
 
+/// Parse the content of a CertificateVerify message (TLS 1.2 and 1.3)
+pub fn parse_tls_certificate_verify(i: &[u8]) -> IResult<&[u8], TlsCertificateVerifyContents<'_>> {
+    let (i, scheme) = SignatureScheme::parse(i)?;
+    let (i, signature) = length_data(be_u16)(i)?;
+    let content = TlsCertificateVerifyContents { scheme, signature };
+    Ok((i, content))
+}
+
 pub(crate) fn parse_tls_clientkeyexchange(
     len: usize,
 ) -> impl FnMut(&[u8]) -> IResult<&[u8], TlsClientKeyExchangeContents> {
@@ -761,14 +1302,14 @@ pub(crate) fn parse_tls_clientkeyexchange(
 fn parse_tls_handshake_msg_clientkeyexchange(
     i: &[u8],
     len: usize,
-) -> IResult<&[u8], TlsMessageHandshake> {
+) -> IResult<&[u8], TlsMessageHandshake<'_>> {
     map(
         parse_tls_clientkeyexchange(len),
         TlsMessageHandshake::ClientKeyExchange,
     )(i)
 }
 
-fn parse_certrequest_nosigalg(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake> {
+fn parse_certrequest_nosigalg(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake<'_>> {
     let (i, cert_types) = length_count(be_u8, be_u8)(i)?;
     let (i, ca_len) = be_u16(i)?;
     let (i, unparsed_ca) =
@@ -782,7 +1323,7 @@ fn parse_certrequest_nosigalg(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake> {
     Ok((i, TlsMessageHandshake::CertificateRequest(content)))
 }
 
-fn parse_certrequest_full(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake> {
+fn parse_certrequest_full(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake<'_>> {
     let (i, cert_types) = length_count(be_u8, be_u8)(i)?;
     let (i, sig_hash_algs_len) = be_u16(i)?;
     let (i, sig_hash_algs) =
@@ -798,24 +1339,42 @@ fn parse_certrequest_full(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake> {
     Ok((i, TlsMessageHandshake::CertificateRequest(content)))
 }
 
+fn parse_tls_handshake_msg_certificaterequest_v13(
+    i: &[u8],
+) -> IResult<&[u8], TlsMessageHandshake<'_>> {
+    let (i, certificate_request_context) = length_data(be_u8)(i)?;
+    let (i, ext) = verify(length_data(be_u16), |d: &[u8]| !d.is_empty())(i)?;
+    let content = TlsCertificateRequestV13Contents {
+        certificate_request_context,
+        ext,
+    };
+    Ok((i, TlsMessageHandshake::CertificateRequestV13(content)))
+}
+
 #[inline]
-fn parse_tls_handshake_msg_certificaterequest(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake> {
+fn parse_tls_handshake_msg_certificaterequest(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake<'_>> {
     alt((
         complete(parse_certrequest_full),
         complete(parse_certrequest_nosigalg),
     ))(i)
 }
 
-fn parse_tls_handshake_msg_finished(i: &[u8], len: usize) -> IResult<&[u8], TlsMessageHandshake> {
-    map(take(len), TlsMessageHandshake::Finished)(i)
+fn parse_tls_handshake_msg_finished(
+    i: &[u8],
+    len: usize,
+) -> IResult<&[u8], TlsMessageHandshake<'_>> {
+    map(take(len), |verify_data| {
+        TlsMessageHandshake::Finished(TlsFinishedContents { verify_data })
+    })(i)
 }
 
 // Defined in [RFC6066]
 // if status_type == 0, blob is a OCSPResponse, as defined in [RFC2560](https://tools.ietf.org/html/rfc2560)
 // Note that the OCSPResponse object is DER-encoded.
-fn parse_tls_handshake_msg_certificatestatus(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake> {
+// The response length must match the message length.
+fn parse_tls_handshake_msg_certificatestatus(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake<'_>> {
     let (i, status_type) = be_u8(i)?;
-    let (i, blob) = length_data(be_u24)(i)?;
+    let (i, blob) = all_consuming(complete(length_data(be_u24)))(i)?;
     let content = TlsCertificateStatusContents { status_type, blob };
     Ok((i, TlsMessageHandshake::CertificateStatus(content)))
 }
@@ -823,7 +1382,7 @@ fn parse_tls_handshake_msg_certificatestatus(i: &[u8]) -> IResult<&[u8], TlsMess
 /// NextProtocol handshake message, as defined in
 /// [draft-agl-tls-nextprotoneg-03](https://tools.ietf.org/html/draft-agl-tls-nextprotoneg-03)
 /// Deprecated in favour of ALPN.
-fn parse_tls_handshake_msg_next_protocol(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake> {
+fn parse_tls_handshake_msg_next_protocol(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake<'_>> {
     let (i, selected_protocol) = length_data(be_u8)(i)?;
     let (i, padding) = length_data(be_u8)(i)?;
     let next_proto = TlsNextProtocolContent {
@@ -833,12 +1392,33 @@ fn parse_tls_handshake_msg_next_protocol(i: &[u8]) -> IResult<&[u8], TlsMessageH
     Ok((i, TlsMessageHandshake::NextProtocol(next_proto)))
 }
 
-fn parse_tls_handshake_msg_key_update(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake> {
-    map(be_u8, TlsMessageHandshake::KeyUpdate)(i)
+fn parse_tls_handshake_msg_key_update(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake<'_>> {
+    let (i, request_update) = verify(map(be_u8, KeyUpdateRequest), |r| r.0 <= 1)(i)?;
+    let content = TlsKeyUpdateContents { request_update };
+    Ok((i, TlsMessageHandshake::KeyUpdate(content)))
+}
+
+fn parse_tls_handshake_msg_compressed_certificate(
+    i: &[u8],
+) -> IResult<&[u8], TlsMessageHandshake<'_>> {
+    let (i, algorithm) = CertificateCompressionAlgorithm::parse_be(i)?;
+    let (i, uncompressed_length) = be_u24(i)?;
+    let (i, compressed_certificate_message) =
+        verify(length_data(be_u24), |d: &[u8]| !d.is_empty())(i)?;
+    let content = TlsCompressedCertificateContents {
+        algorithm,
+        uncompressed_length,
+        compressed_certificate_message,
+    };
+    Ok((i, TlsMessageHandshake::CompressedCertificate(content)))
 }
 
 /// Parse a TLS handshake message
-pub fn parse_tls_message_handshake(i: &[u8]) -> IResult<&[u8], TlsMessage> {
+pub fn parse_tls_message_handshake(i: &[u8]) -> IResult<&[u8], TlsMessage<'_>> {
+    map(parse_handshake_msg, TlsMessage::Handshake)(i)
+}
+
+fn parse_handshake_msg(i: &[u8]) -> IResult<&[u8], TlsMessageHandshake<'_>> {
     let (i, ht) = be_u8(i)?;
     let (i, hl) = be_u24(i)?;
     let (i, raw_msg) = take(hl)(i)?;
@@ -851,6 +1431,9 @@ pub fn parse_tls_message_handshake(i: &[u8]) -> IResult<&[u8], TlsMessage> {
         }
         TlsHandshakeType::EndOfEarlyData => Ok((raw_msg, TlsMessageHandshake::EndOfEarlyData)),
         TlsHandshakeType::HelloRetryRequest => parse_tls_handshake_msg_hello_retry_request(raw_msg),
+        TlsHandshakeType::EncryptedExtensions => {
+            parse_tls_handshake_msg_encrypted_extensions(raw_msg)
+        }
         TlsHandshakeType::Certificate => parse_tls_handshake_msg_certificate(raw_msg),
         TlsHandshakeType::ServerKeyExchange => {
             parse_tls_handshake_msg_serverkeyexchange(raw_msg, hl as usize)
@@ -867,53 +1450,186 @@ pub fn parse_tls_message_handshake(i: &[u8]) -> IResult<&[u8], TlsMessage> {
         // TlsHandshakeType::CertificateURL => parse_tls_handshake_msg_certificateurl(raw_msg),
         TlsHandshakeType::CertificateStatus => parse_tls_handshake_msg_certificatestatus(raw_msg),
         TlsHandshakeType::KeyUpdate => parse_tls_handshake_msg_key_update(raw_msg),
+        TlsHandshakeType::CompressedCertificate => {
+            parse_tls_handshake_msg_compressed_certificate(raw_msg)
+        }
         TlsHandshakeType::NextProtocol => parse_tls_handshake_msg_next_protocol(raw_msg),
         _ => Err(Err::Error(make_error(i, ErrorKind::Switch))),
     }?;
-    Ok((i, TlsMessage::Handshake(msg)))
+    Ok((i, msg))
+}
+
+/// Parse a TLS handshake message, using the negotiated version to select the message layout
+///
+/// Some handshake messages have a different layout in TLS 1.3, which cannot be detected from
+/// the message itself. The negotiated version is the one selected by the server (for TLS 1.3,
+/// found in the `supported_versions` extension of the ServerHello).
+///
+/// For versions other than TLS 1.3, this is the same as `parse_tls_message_handshake`.
+pub fn parse_tls_message_handshake_with_version(
+    i: &[u8],
+    version: TlsVersion,
+) -> IResult<&[u8], TlsMessage<'_>> {
+    if version != TlsVersion::Tls13 {
+        return parse_tls_message_handshake(i);
+    }
+    let (rem, ht) = be_u8(i)?;
+    let (rem, hl) = be_u24(rem)?;
+    let (rem, raw_msg) = take(hl)(rem)?;
+    let (_, msg) = match TlsHandshakeType(ht) {
+        TlsHandshakeType::NewSessionTicket => parse_tls_handshake_msg_newsessionticket_v13(raw_msg),
+        TlsHandshakeType::Certificate => parse_tls_handshake_msg_certificate_v13(raw_msg),
+        TlsHandshakeType::CertificateRequest => {
+            parse_tls_handshake_msg_certificaterequest_v13(raw_msg)
+        }
+        _ => return parse_tls_message_handshake(i),
+    }?;
+    Ok((rem, TlsMessage::Handshake(msg)))
+}
+
+/// A handshake message, with the bytes it was parsed from
+///
+/// See `parse_tls_message_handshake_with_raw`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TlsHandshakeMessageWithRaw<'a> {
+    /// The message bytes, including the header (type and length)
+    pub raw: &'a [u8],
+    pub msg: TlsMessageHandshake<'a>,
+}
+
+/// Parse a TLS handshake message, and also return its raw bytes
+///
+/// The raw bytes can be used to compute the transcript hash (for ex. to verify the `Finished`
+/// message, or for the extended master secret), without serializing the message again.
+pub fn parse_tls_message_handshake_with_raw(
+    i: &[u8],
+) -> IResult<&[u8], TlsHandshakeMessageWithRaw<'_>> {
+    let (rem, msg) = parse_handshake_msg(i)?;
+    let raw = &i[..i.len() - rem.len()];
+    Ok((rem, TlsHandshakeMessageWithRaw { raw, msg }))
+}
+
+/// Iterator over the handshake messages of a buffer
+///
+/// The buffer must contain only complete handshake messages (for ex. the content of one or more
+/// handshake records, or a reassembled TLS 1.3 flight). Messages are parsed using
+/// `parse_tls_message_handshake`.
+///
+/// If a message is invalid or truncated, the iterator returns an error and then stops.
+#[derive(Clone, Debug)]
+pub struct TlsHandshakeMessageIterator<'a> {
+    data: &'a [u8],
+    done: bool,
+}
+
+impl<'a> TlsHandshakeMessageIterator<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        TlsHandshakeMessageIterator { data, done: false }
+    }
+}
+
+impl<'a> Iterator for TlsHandshakeMessageIterator<'a> {
+    type Item = Result<TlsMessageHandshake<'a>, Err<nom::error::Error<&'a [u8]>>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.data.is_empty() {
+            return None;
+        }
+        match complete(parse_handshake_msg)(self.data) {
+            Ok((rem, msg)) => {
+                self.data = rem;
+                Some(Ok(msg))
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<'a> FusedIterator for TlsHandshakeMessageIterator<'a> {}
+
+/// Iterate over all handshake messages of a buffer, until it is exhausted
+pub fn parse_tls_handshake_messages(i: &[u8]) -> TlsHandshakeMessageIterator<'_> {
+    TlsHandshakeMessageIterator::new(i)
 }
 
 /// Parse a TLS changecipherspec message
-// XXX add extra verification hdr.len == 1
-pub fn parse_tls_message_changecipherspec(i: &[u8]) -> IResult<&[u8], TlsMessage> {
+///
+/// The record length is not checked: use `parse_tls_message_change_cipher_spec` to validate
+/// the payload of a record.
+pub fn parse_tls_message_changecipherspec(i: &[u8]) -> IResult<&[u8], TlsMessage<'_>> {
     let (i, _) = verify(be_u8, |&tag| tag == 0x01)(i)?;
     Ok((i, TlsMessage::ChangeCipherSpec))
 }
 
+/// Parse the payload of a record of type `ChangeCipherSpec`, with strict validation
+///
+/// The payload must be exactly one byte, with value 1. In TLS 1.3, this is the "dummy"
+/// ChangeCipherSpec sent for middlebox compatibility ([RFC8446](https://tools.ietf.org/html/rfc8446)
+/// section 5).
+pub fn parse_tls_message_change_cipher_spec(i: &[u8]) -> IResult<&[u8], TlsMessage<'_>> {
+    all_consuming(complete(parse_tls_message_changecipherspec))(i)
+}
+
 /// Parse a TLS alert message
 // XXX add extra verification hdr.len == 2
-pub fn parse_tls_message_alert(i: &[u8]) -> IResult<&[u8], TlsMessage> {
+pub fn parse_tls_message_alert(i: &[u8]) -> IResult<&[u8], TlsMessage<'_>> {
     let (i, alert) = TlsMessageAlert::parse(i)?;
     Ok((i, TlsMessage::Alert(alert)))
 }
 
+/// Parse all alerts of the payload of a record of type `Alert`
+///
+/// The entire input must be consumed: a trailing incomplete alert is an error.
+pub fn parse_tls_record_alert_all(i: &[u8]) -> IResult<&[u8], Vec<TlsMessageAlert>> {
+    all_consuming(many1(complete(TlsMessageAlert::parse)))(i)
+}
+
 /// Parse a TLS applicationdata message
 ///
-/// Read the entire input as applicationdata
-pub fn parse_tls_message_applicationdata(i: &[u8]) -> IResult<&[u8], TlsMessage> {
+/// Read the entire input as applicationdata. The content is opaque, and is not decrypted.
+pub fn parse_tls_message_applicationdata(i: &[u8]) -> IResult<&[u8], TlsMessage<'_>> {
     let msg = TlsMessage::ApplicationData(TlsMessageApplicationData { blob: i });
     Ok((&[], msg))
 }
 
 /// Parse a TLS heartbeat message
+///
+/// `tls_plaintext_len` is the length of the record. The declared payload length is not trusted:
+/// if it exceeds the record length, the message is still returned, with a truncated payload.
 pub fn parse_tls_message_heartbeat(
     i: &[u8],
     tls_plaintext_len: u16,
-) -> IResult<&[u8], Vec<TlsMessage>> {
-    let (i, heartbeat_type) = TlsHeartbeatMessageType::parse(i)?;
-    let (i, payload_len) = be_u16(i)?;
+) -> IResult<&[u8], Vec<TlsMessage<'_>>> {
     if tls_plaintext_len < 3 {
         return Err(Err::Error(make_error(i, ErrorKind::Verify)));
     }
-    let (i, payload) = take(payload_len as usize)(i)?;
+    let (i, heartbeat_type) = TlsHeartbeatMessageType::parse(i)?;
+    let (i, payload_len) = be_u16(i)?;
+    let (i, data) = take(tls_plaintext_len as usize - 3)(i)?;
+    let (payload, padding) = data.split_at((payload_len as usize).min(data.len()));
     let v = vec![TlsMessage::Heartbeat(TlsMessageHeartbeat {
         heartbeat_type,
         payload_len,
         payload,
+        padding,
     })];
     Ok((i, v))
 }
 
+/// Tell if a heartbeat message declares a payload larger than its record (heartbleed)
+///
+/// `record_len` is the length of the record containing the message (the `len` field of the
+/// record header). As required by [RFC6520](https://tools.ietf.org/html/rfc6520) section 4, the
+/// message must contain the type (1 byte), the payload length (2 bytes), the payload and at least
+/// 16 bytes of padding. A message declaring a payload length that does not fit in the record
+/// should be discarded: when answered by a vulnerable peer, the response leaks memory.
+pub fn is_potential_heartbleed(msg: &TlsMessageHeartbeat, record_len: usize) -> bool {
+    3 + msg.payload_len as usize + 16 > record_len
+}
+
 /// Given data and a TLS record header, parse content.
 ///
 /// A record can contain multiple messages (with the same type).
@@ -927,7 +1643,7 @@ pub fn parse_tls_record_with_header<'i>(i:&'i [u8], hdr:&TlsRecordHeader ) -> IR
         TlsRecordType::ChangeCipherSpec => many1(complete(parse_tls_message_changecipherspec))(i),
         TlsRecordType::Alert            => many1(complete(parse_tls_message_alert))(i),
         TlsRecordType::Handshake        => many1(complete(parse_tls_message_handshake))(i),
-        TlsRecordType::ApplicationData  => many1(complete(parse_tls_message_applicationdata))(i),
+        TlsRecordType::ApplicationData  => map(parse_tls_message_applicationdata, |m| vec![m])(i),
         TlsRecordType::Heartbeat        => parse_tls_message_heartbeat(i, hdr.len),
         _                               => Err(Err::Error(make_error(i, ErrorKind::Switch)))
     }
@@ -935,7 +1651,7 @@ pub fn parse_tls_record_with_header<'i>(i:&'i [u8], hdr:&TlsRecordHeader ) -> IR
 
 /// Parse one packet only, as plaintext
 /// A single record can contain multiple messages, they must share the same record type
-pub fn parse_tls_plaintext(i: &[u8]) -> IResult<&[u8], TlsPlaintext> {
+pub fn parse_tls_plaintext(i: &[u8]) -> IResult<&[u8], TlsPlaintext<'_>> {
     let (i, hdr) = parse_tls_record_header(i)?;
     if hdr.len > MAX_RECORD_LEN {
         return Err(Err::Error(make_error(i, ErrorKind::TooLarge)));
@@ -946,8 +1662,18 @@ pub fn parse_tls_plaintext(i: &[u8]) -> IResult<&[u8], TlsPlaintext> {
     Ok((i, TlsPlaintext { hdr, msg }))
 }
 
+/// Parse one packet only, as plaintext, and return an owned copy of the record
+///
+/// The record messages are parsed (and rejected if invalid) like in `parse_tls_plaintext`, but
+/// only the record data is copied. Use `TlsRecordOwned::parse_messages` to get the messages.
+pub fn parse_tls_plaintext_owned(i: &[u8]) -> IResult<&[u8], TlsRecordOwned> {
+    let (rem, record) = parse_tls_raw_record(i)?;
+    let _ = parse_tls_record_with_header(record.data, &record.hdr)?;
+    Ok((rem, record.to_owned_record()))
+}
+
 /// Parse one packet only, as encrypted content
-pub fn parse_tls_encrypted(i: &[u8]) -> IResult<&[u8], TlsEncrypted> {
+pub fn parse_tls_encrypted(i: &[u8]) -> IResult<&[u8], TlsEncrypted<'_>> {
     let (i, hdr) = parse_tls_record_header(i)?;
     if hdr.len > MAX_RECORD_LEN {
         return Err(Err::Error(make_error(i, ErrorKind::TooLarge)));
@@ -962,7 +1688,7 @@ pub fn parse_tls_encrypted(i: &[u8]) -> IResult<&[u8], TlsEncrypted> {
 /// This function is used to get the record type, and to make sure the record is
 /// complete (not fragmented).
 /// After calling this function, use `parse_tls_record_with_header` to parse content.
-pub fn parse_tls_raw_record(i: &[u8]) -> IResult<&[u8], TlsRawRecord> {
+pub fn parse_tls_raw_record(i: &[u8]) -> IResult<&[u8], TlsRawRecord<'_>> {
     let (i, hdr) = parse_tls_record_header(i)?;
     if hdr.len > MAX_RECORD_LEN {
         return Err(Err::Error(make_error(i, ErrorKind::TooLarge)));
@@ -971,6 +1697,67 @@ pub fn parse_tls_raw_record(i: &[u8]) -> IResult<&[u8], TlsRawRecord> {
     Ok((i, TlsRawRecord { hdr, data }))
 }
 
+/// Iterator over the raw records of a buffer
+///
+/// Records are read using `parse_tls_raw_record`, until the buffer is exhausted or only a partial
+/// record remains. The bytes not consumed (for ex. the start of the next record, if it is split
+/// over several TCP segments) are available using `remaining`.
+///
+/// If a record is invalid, the iterator returns an error and then stops.
+#[derive(Clone, Debug)]
+pub struct TlsRecordIterator<'a> {
+    data: &'a [u8],
+    done: bool,
+}
+
+impl<'a> TlsRecordIterator<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        TlsRecordIterator { data, done: false }
+    }
+
+    /// Return the bytes not consumed yet
+    ///
+    /// After the iterator returned `None`, this is the partial record at the end of the buffer
+    /// (empty if the buffer contained only complete records). After an error, this is the
+    /// invalid record and the following data.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+impl<'a> Iterator for TlsRecordIterator<'a> {
+    type Item = Result<TlsRawRecord<'a>, Err<nom::error::Error<&'a [u8]>>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.data.is_empty() {
+            return None;
+        }
+        match parse_tls_raw_record(self.data) {
+            Ok((rem, record)) => {
+                self.data = rem;
+                Some(Ok(record))
+            }
+            Err(Err::Incomplete(_)) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<'a> FusedIterator for TlsRecordIterator<'a> {}
+
+/// Iterate over the raw records of a buffer, until it is exhausted or a partial record remains
+///
+/// See `TlsRecordIterator`.
+pub fn parse_tls_records(i: &[u8]) -> TlsRecordIterator<'_> {
+    TlsRecordIterator::new(i)
+}
+
 /// Parse one packet only, as plaintext
 /// This function is deprecated. Use `parse_tls_plaintext` instead.
 ///
@@ -978,7 +1765,7 @@ pub fn parse_tls_raw_record(i: &[u8]) -> IResult<&[u8], TlsRawRecord> {
 /// not possible to parse TLS packets without knowing the TLS state.
 #[deprecated(since = "0.5.0", note = "Use parse_tls_plaintext")]
 #[inline]
-pub fn tls_parser(i: &[u8]) -> IResult<&[u8], TlsPlaintext> {
+pub fn tls_parser(i: &[u8]) -> IResult<&[u8], TlsPlaintext<'_>> {
     parse_tls_plaintext(i)
 }
 
@@ -988,6 +1775,6 @@ pub fn tls_parser(i: &[u8]) -> IResult<&[u8], TlsPlaintext> {
 ///
 /// This function will be removed from API, as it should be replaced by a more
 /// useful one to handle fragmentation.
-pub fn tls_parser_many(i: &[u8]) -> IResult<&[u8], Vec<TlsPlaintext>> {
+pub fn tls_parser_many(i: &[u8]) -> IResult<&[u8], Vec<TlsPlaintext<'_>>> {
     many1(complete(parse_tls_plaintext))(i)
 }
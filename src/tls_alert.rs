@@ -2,13 +2,16 @@ use nom_derive::*;
 use rusticata_macros::newtype_enum;
 
 /// TLS alert severity
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Nom)]
+///
+/// `Display` uses the lowercase name of the level (for ex. `fatal`), while `Debug` uses the
+/// constant name (for ex. `Fatal`).
+#[derive(Clone, Copy, PartialEq, Eq, Nom)]
 pub struct TlsAlertSeverity(pub u8);
 
-newtype_enum! {
-impl display TlsAlertSeverity {
-    Warning = 0x01,
-    Fatal   = 0x02
+registry_enum! {
+impl names TlsAlertSeverity {
+    Warning = 0x01 => "warning",
+    Fatal   = 0x02 => "fatal",
 }
 }
 
@@ -16,11 +19,11 @@ impl display TlsAlertSeverity {
 ///
 /// Alerts are defined in the [IANA TLS Alert
 /// Registry](https://www.iana.org/assignments/tls-parameters/tls-parameters.xhtml#tls-parameters-6)
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Nom)]
+#[derive(Clone, Copy, PartialEq, Eq, Nom)]
 pub struct TlsAlertDescription(pub u8);
 
-newtype_enum! {
-impl display TlsAlertDescription {
+registry_enum! {
+impl debug TlsAlertDescription: from_u8(u8) {
     CloseNotify            = 0x00,
     UnexpectedMessage      = 0x0A,
     BadRecordMac           = 0x14,
@@ -39,6 +42,7 @@ impl display TlsAlertDescription {
     AccessDenied           = 0x31,
     DecodeError            = 0x32,
     DecryptError           = 0x33,
+    TooManyCidsRequested   = 0x34, // [RFC9146]
     ExportRestriction      = 0x3C,
     ProtocolVersion        = 0x46,
     InsufficientSecurity   = 0x47,
@@ -54,7 +58,8 @@ impl display TlsAlertDescription {
     BadCertHashValue       = 0x72,
     UnknownPskIdentity     = 0x73,
     CertificateRequired    = 0x74,
-    NoApplicationProtocol  = 0x78 // [RFC7301]
+    NoApplicationProtocol  = 0x78, // [RFC7301]
+    EchRequired            = 0x79, // draft-ietf-tls-esni
 }
 }
 
@@ -67,6 +72,13 @@ pub struct TlsMessageAlert {
     pub code: TlsAlertDescription,
 }
 
+impl TlsMessageAlert {
+    /// Return the alert description, if the code is registered
+    pub const fn description(&self) -> Option<TlsAlertDescription> {
+        TlsAlertDescription::from_u8(self.code.0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::tls_alert::*;
@@ -82,7 +94,10 @@ mod tests {
         assert_eq!(b, TlsAlertSeverity::Warning);
 
         let s = format!("{}", b);
+        assert_eq!(s, "warning");
+        let s = format!("{:?}", b);
         assert_eq!(s, "Warning");
+        assert_eq!(format!("{}", TlsAlertSeverity::Fatal), "fatal");
 
         let s = format!("{}", TlsAlertSeverity(129));
         assert_eq!(s, "TlsAlertSeverity(129 / 0x81)");
@@ -98,4 +113,51 @@ mod tests {
         let b = TlsAlertDescription(a_u8);
         assert_eq!(b, TlsAlertDescription::HandshakeFailure);
     }
+
+    #[test]
+    fn test_tlsalert_description() {
+        let alert = |code| TlsMessageAlert {
+            severity: TlsAlertSeverity::Fatal,
+            code: TlsAlertDescription(code),
+        };
+        assert_eq!(
+            alert(0).description(),
+            Some(TlsAlertDescription::CloseNotify)
+        );
+        assert_eq!(
+            alert(40).description(),
+            Some(TlsAlertDescription::HandshakeFailure)
+        );
+        assert_eq!(
+            alert(42).description(),
+            Some(TlsAlertDescription::BadCertificate)
+        );
+        assert_eq!(
+            alert(70).description(),
+            Some(TlsAlertDescription::ProtocolVersion)
+        );
+        assert_eq!(alert(200).description(), None);
+        assert_eq!(
+            TlsAlertDescription::from_u8(0x78),
+            Some(TlsAlertDescription::NoApplicationProtocol)
+        );
+        assert_eq!(
+            TlsAlertDescription::from_u8(52),
+            Some(TlsAlertDescription::TooManyCidsRequested)
+        );
+        assert_eq!(
+            TlsAlertDescription::from_u8(121),
+            Some(TlsAlertDescription::EchRequired)
+        );
+        assert_eq!(TlsAlertDescription::from_u8(0x7a), None);
+        assert_eq!(format!("{:?}", alert(121).code), "EchRequired");
+
+        let s = format!("{:?}", alert(40));
+        assert_eq!(
+            s,
+            "TlsMessageAlert { severity: Fatal, code: HandshakeFailure }"
+        );
+        let s = format!("{:?}", alert(200));
+        assert!(s.contains("TlsAlertDescription(200 / 0xc8)"));
+    }
 } // mod tests
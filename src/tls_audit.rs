@@ -0,0 +1,64 @@
+//! Classification of weak or deprecated extensions
+//!
+//! `audit_extensions` checks a list of parsed extensions (usually from a ClientHello) against a
+//! small set of rules, and returns a finding for each extension known to be weak or deprecated.
+
+use crate::tls_extensions::{TlsExtension, TlsExtensionType};
+use alloc::vec::Vec;
+
+/// Severity of an `ExtensionFinding`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FindingSeverity {
+    /// Deprecated, but not known to be exploitable
+    Low,
+    /// Known weaknesses, or large attack surface
+    Medium,
+    /// Known to be exploitable
+    High,
+}
+
+/// Weak or deprecated extension, as reported by `audit_extensions`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtensionFinding {
+    pub ext_type: TlsExtensionType,
+    pub severity: FindingSeverity,
+    pub description: &'static str,
+}
+
+struct AuditRule {
+    ext_type: TlsExtensionType,
+    severity: FindingSeverity,
+    description: &'static str,
+}
+
+#[rustfmt::skip]
+static AUDIT_RULES: &[AuditRule] = &[
+    AuditRule { ext_type: TlsExtensionType::Heartbeat, severity: FindingSeverity::Medium,
+                description: "heartbeat offered (attack surface, see CVE-2014-0160)" },
+    AuditRule { ext_type: TlsExtensionType::TruncatedHMac, severity: FindingSeverity::Medium,
+                description: "truncated_hmac offered (weakens record integrity)" },
+    AuditRule { ext_type: TlsExtensionType::NextProtocolNegotiation, severity: FindingSeverity::Low,
+                description: "NPN offered (deprecated, replaced by ALPN)" },
+    AuditRule { ext_type: TlsExtensionType::EncryptedServerName, severity: FindingSeverity::Low,
+                description: "ESNI offered (deprecated draft, replaced by ECH)" },
+];
+
+/// Check extensions for weak or deprecated features
+///
+/// Return one finding per matching extension, in the order of `exts`. Extensions are matched
+/// on their type, so unknown extensions (`TlsExtension::Unknown`) are also checked.
+pub fn audit_extensions(exts: &[TlsExtension]) -> Vec<ExtensionFinding> {
+    exts.iter()
+        .filter_map(|ext| {
+            let ext_type = TlsExtensionType::from(ext);
+            AUDIT_RULES
+                .iter()
+                .find(|rule| rule.ext_type == ext_type)
+                .map(|rule| ExtensionFinding {
+                    ext_type,
+                    severity: rule.severity,
+                    description: rule.description,
+                })
+        })
+        .collect()
+}
@@ -9,6 +9,8 @@
 #![allow(clippy::unreadable_literal)]
 
 use core::convert::TryFrom;
+use core::fmt;
+use core::ops::Deref;
 use num_enum::TryFromPrimitive;
 
 use crate::TlsCipherSuiteID;
@@ -108,6 +110,20 @@ pub enum TlsPRF {
     Sm3,
 }
 
+/// Coarse strength classification of a ciphersuite
+///
+/// See [`TlsCipherSuite::strength`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TlsCipherStrength {
+    /// Broken or unauthenticated: NULL or export-grade encryption, anonymous key exchange, or
+    /// weak ciphers (DES, 3DES, RC2, RC4) or MAC (MD5)
+    Weak,
+    /// No known practical attack, but no forward secrecy or no AEAD
+    Medium,
+    /// Forward secrecy and AEAD encryption
+    Strong,
+}
+
 /// TLS Ciphersuite
 ///
 /// A CipherSuite is a set of algorithm and parameters used to secure
@@ -172,6 +188,54 @@ impl TlsCipherSuite {
         }
     }
 
+    /// Tell if this ciphersuite provides forward secrecy (ephemeral key exchange)
+    pub const fn is_forward_secret(&self) -> bool {
+        matches!(
+            (self.kx, self.au),
+            (TlsCipherKx::Dhe, _)
+                | (TlsCipherKx::Ecdhe, _)
+                | (TlsCipherKx::Aecdh, _)
+                | (TlsCipherKx::Eccpwd, _)
+                | (TlsCipherKx::Tls13, _)
+                // anonymous DH and ECDH use ephemeral keys
+                | (TlsCipherKx::Dh, TlsCipherAu::Null)
+                | (TlsCipherKx::Ecdh, TlsCipherAu::Null)
+                | (TlsCipherKx::Psk, TlsCipherAu::Dhe)
+        )
+    }
+
+    /// Tell if this ciphersuite uses Authenticated Encryption with Associated Data (AEAD)
+    pub const fn is_aead(&self) -> bool {
+        matches!(self.mac, TlsCipherMac::Aead)
+    }
+
+    /// Get a coarse strength classification of this ciphersuite
+    ///
+    /// The classification is based only on the key exchange, authentication, encryption and MAC
+    /// algorithms of the ciphersuite, not on the negotiated parameters (key sizes, etc.).
+    pub const fn strength(&self) -> TlsCipherStrength {
+        let weak_enc = matches!(
+            self.enc,
+            TlsCipherEnc::Null
+                | TlsCipherEnc::Des
+                | TlsCipherEnc::TripleDes
+                | TlsCipherEnc::Rc2
+                | TlsCipherEnc::Rc4
+        );
+        if weak_enc
+            || self.enc_size < 128
+            || matches!(self.kx, TlsCipherKx::Null)
+            || matches!(self.au, TlsCipherAu::Null)
+            || matches!(self.mac, TlsCipherMac::Null | TlsCipherMac::HmacMd5)
+        {
+            TlsCipherStrength::Weak
+        } else if self.is_forward_secret() && self.is_aead() {
+            TlsCipherStrength::Strong
+        } else {
+            TlsCipherStrength::Medium
+        }
+    }
+
     /// Get the length of this ciphersuite MAC algorithm, in bytes
     pub const fn mac_length(&self) -> usize {
         match self.mac {
@@ -186,6 +250,13 @@ impl TlsCipherSuite {
     }
 }
 
+impl fmt::Display for TlsCipherSuite {
+    /// Display the IANA name of this ciphersuite
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
 impl TryFrom<u16> for &'static TlsCipherSuite {
     type Error = CipherSuiteNotFound;
 
@@ -213,9 +284,74 @@ impl<'a> TryFrom<&'a str> for &'static TlsCipherSuite {
     }
 }
 
+/// A list of ciphersuite identifiers, for ex. the ciphers offered in a ClientHello
+///
+/// This is a borrowed view on the raw list, with helpers to look up the ciphersuites. Unknown
+/// identifiers (including GREASE values) are kept in the list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CipherSuiteList<'a>(pub &'a [TlsCipherSuiteID]);
+
+impl<'a> CipherSuiteList<'a> {
+    pub const fn new(ids: &'a [TlsCipherSuiteID]) -> Self {
+        CipherSuiteList(ids)
+    }
+
+    /// Return the raw list of identifiers
+    pub const fn ids(&self) -> &'a [TlsCipherSuiteID] {
+        self.0
+    }
+
+    /// Return an iterator over the ciphersuites, or `None` for unknown identifiers
+    pub fn iter_named(&self) -> impl Iterator<Item = Option<&'static TlsCipherSuite>> + 'a {
+        self.0.iter().map(|id| TlsCipherSuite::from_id(id.0))
+    }
+
+    /// Tell if the list contains the identifier `id`
+    pub fn contains_id(&self, id: u16) -> bool {
+        self.0.iter().any(|c| c.0 == id)
+    }
+
+    /// Tell if at least one known ciphersuite of the list matches the predicate
+    pub fn offers<F>(&self, f: F) -> bool
+    where
+        F: Fn(&TlsCipherSuite) -> bool,
+    {
+        self.iter_named().flatten().any(f)
+    }
+
+    /// Tell if the list contains an AES-GCM ciphersuite
+    pub fn offers_aes_gcm(&self) -> bool {
+        self.offers(|c| c.enc == TlsCipherEnc::Aes && c.enc_mode == TlsCipherEncMode::Gcm)
+    }
+
+    /// Tell if the list contains a ChaCha20-Poly1305 ciphersuite
+    pub fn offers_chacha20_poly1305(&self) -> bool {
+        self.offers(|c| c.enc == TlsCipherEnc::Chacha20_Poly1305)
+    }
+
+    /// Tell if the list contains a ciphersuite providing forward secrecy
+    pub fn offers_forward_secrecy(&self) -> bool {
+        self.offers(TlsCipherSuite::is_forward_secret)
+    }
+
+    /// Tell if the list contains a ciphersuite classified as `TlsCipherStrength::Weak`
+    pub fn offers_weak(&self) -> bool {
+        self.offers(|c| c.strength() == TlsCipherStrength::Weak)
+    }
+}
+
+impl<'a> Deref for CipherSuiteList<'a> {
+    type Target = [TlsCipherSuiteID];
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::tls_ciphers::{TlsCipherKx, TlsCipherSuite, CIPHERS};
+    use crate::tls_ciphers::*;
+    use alloc::{vec, vec::Vec};
     use core::convert::TryFrom;
 
     #[test]
@@ -237,6 +373,36 @@ mod tests {
         println!("Found cipher: {:?}", cipher);
     }
 
+    #[test]
+    fn test_cipher_suite_list() {
+        let ids = [
+            TlsCipherSuiteID(0x0a0a), // GREASE
+            TlsCipherSuiteID(0x1301),
+            TlsCipherSuiteID(0x002f),
+        ];
+        let list = CipherSuiteList::new(&ids);
+        let names: Vec<_> = list.iter_named().map(|c| c.map(|c| c.name)).collect();
+        assert_eq!(
+            names,
+            vec![
+                None,
+                Some("TLS_AES_128_GCM_SHA256"),
+                Some("TLS_RSA_WITH_AES_128_CBC_SHA")
+            ]
+        );
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.ids(), &ids);
+        assert!(list.contains_id(0x002f));
+        assert!(!list.contains_id(0xc02f));
+        assert!(list.offers_aes_gcm());
+        assert!(!list.offers_chacha20_poly1305());
+        assert!(list.offers_forward_secrecy());
+        assert!(!list.offers_weak());
+        let list = CipherSuiteList::new(&ids[2..]);
+        assert!(!list.offers_aes_gcm());
+        assert!(!list.offers_forward_secrecy());
+    }
+
     #[test]
     fn test_cipher_filter() {
         let ecdhe_ciphers_count = CIPHERS
@@ -245,4 +411,71 @@ mod tests {
             .count();
         assert!(ecdhe_ciphers_count > 20);
     }
+
+    #[test]
+    fn test_cipher_parameters() {
+        let cipher = TlsCipherSuite::from_id(0x002f).expect("could not get cipher");
+        assert_eq!(cipher.name, "TLS_RSA_WITH_AES_128_CBC_SHA");
+        assert_eq!(cipher.kx, TlsCipherKx::Rsa);
+        assert_eq!(cipher.au, TlsCipherAu::Rsa);
+        assert_eq!(cipher.enc, TlsCipherEnc::Aes);
+        assert_eq!(cipher.enc_mode, TlsCipherEncMode::Cbc);
+        assert_eq!(cipher.enc_size, 128);
+        assert_eq!(cipher.mac, TlsCipherMac::HmacSha1);
+
+        let cipher = TlsCipherSuite::from_id(0xc02c).expect("could not get cipher");
+        assert_eq!(cipher.kx, TlsCipherKx::Ecdhe);
+        assert_eq!(cipher.au, TlsCipherAu::Ecdsa);
+        assert_eq!(cipher.enc, TlsCipherEnc::Aes);
+        assert_eq!(cipher.enc_mode, TlsCipherEncMode::Gcm);
+        assert_eq!(cipher.enc_size, 256);
+        assert_eq!(cipher.mac, TlsCipherMac::Aead);
+        assert_eq!(cipher.prf, TlsPRF::Sha384);
+
+        let cipher = TlsCipherSuite::from_id(0x1303).expect("could not get cipher");
+        assert_eq!(cipher.kx, TlsCipherKx::Tls13);
+        assert_eq!(cipher.au, TlsCipherAu::Tls13);
+        assert_eq!(cipher.enc, TlsCipherEnc::Chacha20_Poly1305);
+        assert_eq!(cipher.mac, TlsCipherMac::Aead);
+    }
+
+    #[test]
+    fn test_cipher_display() {
+        let cipher = TlsCipherSuite::from_id(0xc02f).expect("could not get cipher");
+        assert_eq!(
+            format!("{}", cipher),
+            "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256"
+        );
+    }
+
+    #[test]
+    fn test_cipher_strength() {
+        // TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256
+        let cipher = TlsCipherSuite::from_id(0xc02f).expect("could not get cipher");
+        assert!(cipher.is_forward_secret());
+        assert!(cipher.is_aead());
+        assert_eq!(cipher.strength(), TlsCipherStrength::Strong);
+        // TLS_AES_128_GCM_SHA256
+        let cipher = TlsCipherSuite::from_id(0x1301).expect("could not get cipher");
+        assert_eq!(cipher.strength(), TlsCipherStrength::Strong);
+        // TLS_RSA_WITH_AES_256_CBC_SHA256
+        let cipher = TlsCipherSuite::from_id(0x003d).expect("could not get cipher");
+        assert!(!cipher.is_forward_secret());
+        assert!(!cipher.is_aead());
+        assert_eq!(cipher.strength(), TlsCipherStrength::Medium);
+        // TLS_DHE_RSA_WITH_AES_128_CBC_SHA
+        let cipher = TlsCipherSuite::from_id(0x0033).expect("could not get cipher");
+        assert!(cipher.is_forward_secret());
+        assert_eq!(cipher.strength(), TlsCipherStrength::Medium);
+        // TLS_RSA_WITH_NULL_SHA256
+        let cipher = TlsCipherSuite::from_id(0x003b).expect("could not get cipher");
+        assert_eq!(cipher.strength(), TlsCipherStrength::Weak);
+        // TLS_DH_anon_WITH_AES_128_GCM_SHA256
+        let cipher = TlsCipherSuite::from_id(0x00a6).expect("could not get cipher");
+        assert!(cipher.is_forward_secret());
+        assert_eq!(cipher.strength(), TlsCipherStrength::Weak);
+        // TLS_RSA_WITH_RC4_128_SHA
+        let cipher = TlsCipherSuite::from_id(0x0005).expect("could not get cipher");
+        assert_eq!(cipher.strength(), TlsCipherStrength::Weak);
+    }
 }
@@ -57,6 +57,27 @@ impl<'a> fmt::Debug for TlsHelloRetryRequestContents<'a> {
     }
 }
 
+impl<'a> fmt::Debug for TlsEncryptedExtensionsContents<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("TlsEncryptedExtensionsContents")
+            .field("ext", &HexSlice(self.ext))
+            .finish()
+    }
+}
+
+impl<'a> fmt::Debug for TlsCompressedCertificateContents<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("TlsCompressedCertificateContents")
+            .field("algorithm", &self.algorithm)
+            .field("uncompressed_length", &self.uncompressed_length)
+            .field(
+                "compressed_certificate_message",
+                &HexSlice(self.compressed_certificate_message),
+            )
+            .finish()
+    }
+}
+
 impl<'a> fmt::Debug for RawCertificate<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         fmt.debug_struct("RawCertificate")
@@ -65,6 +86,46 @@ impl<'a> fmt::Debug for RawCertificate<'a> {
     }
 }
 
+impl<'a> fmt::Debug for CertificateEntry<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("CertificateEntry")
+            .field("cert_data", &HexSlice(self.cert_data))
+            .field("ext", &HexSlice(self.ext))
+            .finish()
+    }
+}
+
+impl<'a> fmt::Debug for PskIdentity<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("PskIdentity")
+            .field("identity", &HexSlice(self.identity))
+            .field("obfuscated_ticket_age", &self.obfuscated_ticket_age)
+            .finish()
+    }
+}
+
+impl<'a> fmt::Debug for OfferedPsks<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let binders: Vec<_> = self.binders.iter().map(|b| HexSlice(b)).collect();
+        fmt.debug_struct("OfferedPsks")
+            .field("identities", &self.identities)
+            .field("binders", &binders)
+            .finish()
+    }
+}
+
+impl<'a> fmt::Debug for TlsCertificateV13Contents<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("TlsCertificateV13Contents")
+            .field(
+                "certificate_request_context",
+                &HexSlice(self.certificate_request_context),
+            )
+            .field("cert_list", &self.cert_list)
+            .finish()
+    }
+}
+
 impl<'a> fmt::Debug for TlsServerKeyExchangeContents<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         fmt.debug_struct("TlsServerKeyExchangeContents")
@@ -73,6 +134,23 @@ impl<'a> fmt::Debug for TlsServerKeyExchangeContents<'a> {
     }
 }
 
+impl<'a> fmt::Debug for TlsFinishedContents<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("TlsFinishedContents")
+            .field("verify_data", &HexSlice(self.verify_data))
+            .finish()
+    }
+}
+
+impl<'a> fmt::Debug for TlsCertificateVerifyContents<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("TlsCertificateVerifyContents")
+            .field("scheme", &self.scheme)
+            .field("signature", &HexSlice(self.signature))
+            .finish()
+    }
+}
+
 impl<'a> fmt::Debug for TlsClientKeyExchangeContents<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -128,7 +206,7 @@ impl<'a> fmt::Debug for ECParametersContent<'a> {
             // ECParametersContent::ExplicitChar2(ref p) => {
             //     fmt.write_fmt(format_args!("ExplicitChar2({:?})", HexSlice(p)))
             // }
-            ECParametersContent::NamedGroup(p) => write!(fmt, "{}", p),
+            ECParametersContent::NamedGroup(p) => write!(fmt, "{:?}", p),
         }
     }
 }
@@ -143,53 +221,87 @@ impl<'a> fmt::Debug for ECParameters<'a> {
 }
 
 // ------------------------- tls_extensions.rs ------------------------------
+/// Display signature algorithms as a SignatureScheme if known, or as a (hash, signature) pair
+fn signature_algorithms_names(v: &[u16]) -> Vec<alloc::string::String> {
+    v.iter()
+        .map(|&alg| match SignatureScheme::from_u16(alg) {
+            Some(scheme) => format!("{}", scheme),
+            None => format!(
+                "{}",
+                SignatureAndHashAlgorithm {
+                    hash: HashAlgorithm((alg >> 8) as u8),
+                    sign: SignAlgorithm((alg & 0xff) as u8)
+                }
+            ),
+        })
+        .collect()
+}
+
 impl<'a> fmt::Debug for TlsExtension<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             TlsExtension::SNI(ref v) => {
                 let v: Vec<_> = v
                     .iter()
-                    .map(|&(ty, n)| {
-                        let s = from_utf8(n).unwrap_or("<error decoding utf8 string>");
-                        format!("type={},name={}", ty, s)
+                    .map(|sn| {
+                        let s = from_utf8(sn.name).unwrap_or("<error decoding utf8 string>");
+                        format!("type={},name={}", sn.name_type, s)
                     })
                     .collect();
                 write!(fmt, "TlsExtension::SNI({:?})", v)
             }
             TlsExtension::MaxFragmentLength(l) => {
-                write!(fmt, "TlsExtension::MaxFragmentLength({})", l)
+                write!(fmt, "TlsExtension::MaxFragmentLength({:?})", l)
             }
             TlsExtension::StatusRequest(data) => {
                 write!(fmt, "TlsExtension::StatusRequest({:?})", data)
             }
+            TlsExtension::CertificateStatus(status_type, response) => write!(
+                fmt,
+                "TlsExtension::CertificateStatus({:?},data={:?})",
+                status_type,
+                HexSlice(response)
+            ),
+            TlsExtension::UserMapping(ref v) => {
+                write!(fmt, "TlsExtension::UserMapping({:?})", v)
+            }
+            TlsExtension::ClientAuthz(ref v) => {
+                write!(fmt, "TlsExtension::ClientAuthz({:?})", v)
+            }
+            TlsExtension::ServerAuthz(ref v) => {
+                write!(fmt, "TlsExtension::ServerAuthz({:?})", v)
+            }
             TlsExtension::EllipticCurves(ref v) => {
                 let v2: Vec<_> = v.iter().map(|&curve| format!("{}", curve)).collect();
                 write!(fmt, "TlsExtension::EllipticCurves({:?})", v2)
             }
-            TlsExtension::EcPointFormats(v) => write!(fmt, "TlsExtension::EcPointFormats({:?})", v),
+            TlsExtension::EcPointFormats(ref v) => {
+                write!(fmt, "TlsExtension::EcPointFormats({:?})", v)
+            }
             TlsExtension::SignatureAlgorithms(ref v) => {
-                let v2: Vec<_> = v
-                    .iter()
-                    .map(|&alg| {
-                        let s = format!("{}", SignatureScheme(alg));
-                        if s.starts_with("SignatureScheme") {
-                            format!(
-                                "{}",
-                                SignatureAndHashAlgorithm {
-                                    hash: HashAlgorithm((alg >> 8) as u8),
-                                    sign: SignAlgorithm((alg & 0xff) as u8)
-                                }
-                            )
-                        } else {
-                            s
-                        }
-                    })
-                    .collect();
+                let v2 = signature_algorithms_names(v);
                 write!(fmt, "TlsExtension::SignatureAlgorithms({:?})", v2)
             }
+            TlsExtension::DelegatedCredentials(ref v) => {
+                let v2 = signature_algorithms_names(v);
+                write!(fmt, "TlsExtension::DelegatedCredentials({:?})", v2)
+            }
+            TlsExtension::SignatureAlgorithmsCert(ref v) => {
+                let v2 = signature_algorithms_names(v);
+                write!(fmt, "TlsExtension::SignatureAlgorithmsCert({:?})", v2)
+            }
             TlsExtension::SessionTicket(data) => {
                 write!(fmt, "TlsExtension::SessionTicket(data={:?})", data)
             }
+            TlsExtension::TokenBinding {
+                major,
+                minor,
+                ref key_params,
+            } => write!(
+                fmt,
+                "TlsExtension::TokenBinding(version={}.{}, key_params={:?})",
+                major, minor, key_params
+            ),
             TlsExtension::RecordSizeLimit(data) => {
                 write!(fmt, "TlsExtension::RecordSizeLimit(data={})", data)
             }
@@ -202,6 +314,12 @@ impl<'a> fmt::Debug for TlsExtension<'a> {
             TlsExtension::PreSharedKey(data) => {
                 write!(fmt, "TlsExtension::PreSharedKey(data={:?})", HexSlice(data))
             }
+            TlsExtension::PreSharedKeyOffered(ref psks) => {
+                write!(fmt, "TlsExtension::PreSharedKeyOffered({:?})", psks)
+            }
+            TlsExtension::PreSharedKeySelected(idx) => {
+                write!(fmt, "TlsExtension::PreSharedKeySelected({})", idx)
+            }
             TlsExtension::EarlyData(o) => write!(fmt, "TlsExtension::EarlyData({:?})", o),
             TlsExtension::SupportedVersions(ref v) => {
                 let v2: Vec<_> = v.iter().map(|c| format!("{}", c)).collect();
@@ -212,32 +330,73 @@ impl<'a> fmt::Debug for TlsExtension<'a> {
                 write!(fmt, "TlsExtension::PskExchangeModes({:?})", v)
             }
             TlsExtension::Heartbeat(mode) => write!(fmt, "TlsExtension::Heartbeat(mode={})", mode),
-            TlsExtension::ALPN(ref v) => {
+            TlsExtension::ALPN(_) => {
+                write!(fmt, "TlsExtension::ALPN({:?})", self.protocol_strings())
+            }
+            TlsExtension::ApplicationSettings(ref v) => {
+                let v: Vec<_> = v
+                    .iter()
+                    .map(|c| from_utf8(c).unwrap_or("<error decoding utf8 string>"))
+                    .collect();
+                write!(fmt, "TlsExtension::ApplicationSettings({:?})", v)
+            }
+            TlsExtension::ApplicationSettingsOld(ref v) => {
                 let v: Vec<_> = v
                     .iter()
                     .map(|c| from_utf8(c).unwrap_or("<error decoding utf8 string>"))
                     .collect();
-                write!(fmt, "TlsExtension::ALPN({:?})", v)
+                write!(fmt, "TlsExtension::ApplicationSettingsOld({:?})", v)
             }
-            TlsExtension::SignedCertificateTimestamp(data) => write!(
+            TlsExtension::SignedCertificateTimestamp(ref data) => write!(
                 fmt,
                 "TlsExtension::SignedCertificateTimestamp(data={:?})",
                 data
             ),
             TlsExtension::Padding(data) => write!(fmt, "TlsExtension::Padding(data={:?})", data),
+            TlsExtension::TruncatedHmac => write!(fmt, "TlsExtension::TruncatedHmac"),
             TlsExtension::EncryptThenMac => write!(fmt, "TlsExtension::EncryptThenMac"),
             TlsExtension::ExtendedMasterSecret => write!(fmt, "TlsExtension::ExtendedMasterSecret"),
+            TlsExtension::CertificateAuthorities(ref v) => {
+                let v: Vec<_> = v.iter().map(|&dn| HexSlice(dn)).collect();
+                write!(fmt, "TlsExtension::CertificateAuthorities({:?})", v)
+            }
             TlsExtension::OidFilters(ref v) => {
                 let v: Vec<_> = v.iter().map(|c| format!("{:?}", c)).collect();
                 write!(fmt, "TlsExtension::OidFilters({:?})", v)
             }
             TlsExtension::PostHandshakeAuth => write!(fmt, "TlsExtension::PostHandshakeAuth"),
+            TlsExtension::QuicTransportParameters(ref v) => {
+                let v: Vec<_> = v
+                    .iter()
+                    .map(|&(id, value)| format!("0x{:x}={:?}", id, HexSlice(value)))
+                    .collect();
+                write!(fmt, "TlsExtension::QuicTransportParameters({:?})", v)
+            }
             TlsExtension::NextProtocolNegotiation => {
                 write!(fmt, "TlsExtension::NextProtocolNegotiation")
             }
+            TlsExtension::NextProtocolNegotiationServer(ref v) => {
+                let v: Vec<_> = v
+                    .iter()
+                    .map(|c| from_utf8(c).unwrap_or("<error decoding utf8 string>"))
+                    .collect();
+                write!(fmt, "TlsExtension::NextProtocolNegotiationServer({:?})", v)
+            }
             TlsExtension::RenegotiationInfo(data) => {
                 write!(fmt, "TlsExtension::RenegotiationInfo(data={:?})", data)
             }
+            TlsExtension::StatusRequestV2(ref v) => {
+                write!(fmt, "TlsExtension::StatusRequestV2({:?})", v)
+            }
+            TlsExtension::ClientCertificateType(ref v) => {
+                write!(fmt, "TlsExtension::ClientCertificateType({:?})", v)
+            }
+            TlsExtension::ServerCertificateType(ref v) => {
+                write!(fmt, "TlsExtension::ServerCertificateType({:?})", v)
+            }
+            TlsExtension::ConnectionId(cid) => {
+                write!(fmt, "TlsExtension::ConnectionId(cid={:?})", HexSlice(cid))
+            }
             TlsExtension::EncryptedServerName {
                 ciphersuite, group, ..
             } => write!(
@@ -245,6 +404,9 @@ impl<'a> fmt::Debug for TlsExtension<'a> {
                 "TlsExtension::EncryptedServerName{{cipher: {:?}, group: {:?} ..}}",
                 ciphersuite, group
             ),
+            TlsExtension::EncryptedClientHello(ref ech) => {
+                write!(fmt, "TlsExtension::EncryptedClientHello({:?})", ech)
+            }
             TlsExtension::Grease(t, data) => write!(
                 fmt,
                 "TlsExtension::Grease(0x{:x},data={:?})",
@@ -260,10 +422,32 @@ impl<'a> fmt::Debug for TlsExtension<'a> {
     }
 }
 
+impl<'a> fmt::Debug for ECHClientHello<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ECHClientHello::Outer {
+                kdf_id,
+                aead_id,
+                config_id,
+                enc,
+                payload,
+            } => fmt
+                .debug_struct("ECHClientHello::Outer")
+                .field("kdf_id", &kdf_id)
+                .field("aead_id", &aead_id)
+                .field("config_id", &config_id)
+                .field("enc", &HexSlice(enc))
+                .field("payload_len", &payload.len())
+                .finish(),
+            ECHClientHello::Inner => fmt.write_str("ECHClientHello::Inner"),
+        }
+    }
+}
+
 // ------------------------- tls_sign_hash.rs ------------------------------
 impl fmt::Display for SignatureAndHashAlgorithm {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmt, "HashSign({},{})", self.hash, self.sign)
+        write!(fmt, "HashSign({:?},{:?})", self.hash, self.sign)
     }
 }
 
@@ -271,7 +455,7 @@ impl fmt::Debug for SignatureAndHashAlgorithm {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         write!(
             fmt,
-            "SignatureAndHashAlgorithm({},{})",
+            "SignatureAndHashAlgorithm({:?},{:?})",
             self.hash, self.sign
         )
     }
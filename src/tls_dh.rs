@@ -1,3 +1,4 @@
+use crate::tls_sign_hash::{parse_digitally_signed, DigitallySigned};
 use nom::multi::length_data;
 use nom::number::streaming::be_u16;
 use nom::IResult;
@@ -17,7 +18,33 @@ pub struct ServerDHParams<'a> {
     pub dh_ys: &'a [u8],
 }
 
+/// Diffie-Hellman parameters of a ServerKeyExchange message, with the optional signature
+///
+/// The signature is present for DHE key exchanges, and absent for anonymous (DH_anon) key
+/// exchanges.
+#[derive(Debug, PartialEq)]
+pub struct ServerDHEParams<'a> {
+    pub params: ServerDHParams<'a>,
+    pub signature: Option<DigitallySigned<'a>>,
+}
+
 #[inline]
-pub fn parse_dh_params(i: &[u8]) -> IResult<&[u8], ServerDHParams> {
+pub fn parse_dh_params(i: &[u8]) -> IResult<&[u8], ServerDHParams<'_>> {
     ServerDHParams::parse(i)
 }
+
+/// Parse the content of a DHE ServerKeyExchange message
+///
+/// If `signed` is true, the parameters are followed by a signature using the TLS 1.2
+/// `DigitallySigned` format (with a `SignatureAndHashAlgorithm` prefix). Otherwise, the key
+/// exchange is anonymous and only the parameters are parsed.
+pub fn parse_server_dhe_params(i: &[u8], signed: bool) -> IResult<&[u8], ServerDHEParams<'_>> {
+    let (i, params) = parse_dh_params(i)?;
+    let (i, signature) = if signed {
+        let (i, signature) = parse_digitally_signed(i)?;
+        (i, Some(signature))
+    } else {
+        (i, None)
+    };
+    Ok((i, ServerDHEParams { params, signature }))
+}
@@ -1,3 +1,5 @@
+use crate::tls_ciphers::TlsCipherKx;
+use crate::tls_sign_hash::{parse_digitally_signed, DigitallySigned};
 use alloc::vec::Vec;
 use nom::error::{make_error, ErrorKind};
 use nom::multi::length_data;
@@ -10,55 +12,85 @@ use rusticata_macros::newtype_enum;
 /// [IANA Supported Groups
 /// Registry](https://www.iana.org/assignments/tls-parameters/tls-parameters.xhtml#tls-parameters-8)
 #[derive(Clone, Copy, PartialEq, Eq, NomBE)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct NamedGroup(pub u16);
 
-newtype_enum! {
-impl debug NamedGroup {
-    Sect163k1 = 1,
-    Sect163r1 = 2,
-    Sect163r2 = 3,
-    Sect193r1 = 4,
-    Sect193r2 = 5,
-    Sect233k1 = 6,
-    Sect233r1 = 7,
-    Sect239k1 = 8,
-    Sect283k1 = 9,
-    Sect283r1 = 10,
-    Sect409k1 = 11,
-    Sect409r1 = 12,
-    Sect571k1 = 13,
-    Sect571r1 = 14,
-    Secp160k1 = 15,
-    Secp160r1 = 16,
-    Secp160r2 = 17,
-    Secp192k1 = 18,
-    Secp192r1 = 19,
-    Secp224k1 = 20,
-    Secp224r1 = 21,
-    Secp256k1 = 22,
-    Secp256r1 = 23,
-    Secp384r1 = 24,
-    Secp521r1 = 25,
-    BrainpoolP256r1 = 26,
-    BrainpoolP384r1 = 27,
-    BrainpoolP512r1 = 28,
-    EcdhX25519 = 29,
-    EcdhX448 = 30,
-    BrainpoolP256r1tls13 = 31,
-    BrainpoolP384r1tls13 = 32,
-    BrainpoolP512r1tls13 = 33,
-    Sm2 = 41,
-    Ffdhe2048 = 0x100,
-    Ffdhe3072 = 0x101,
-    Ffdhe4096 = 0x102,
-    Ffdhe6144 = 0x103,
-    Ffdhe8192 = 0x104,
-    ArbitraryExplicitPrimeCurves = 0xFF01,
-    ArbitraryExplicitChar2Curves = 0xFF02,
+registry_enum! {
+impl names NamedGroup {
+    Sect163k1 = 1                         => "sect163k1",
+    Sect163r1 = 2                         => "sect163r1",
+    Sect163r2 = 3                         => "sect163r2",
+    Sect193r1 = 4                         => "sect193r1",
+    Sect193r2 = 5                         => "sect193r2",
+    Sect233k1 = 6                         => "sect233k1",
+    Sect233r1 = 7                         => "sect233r1",
+    Sect239k1 = 8                         => "sect239k1",
+    Sect283k1 = 9                         => "sect283k1",
+    Sect283r1 = 10                        => "sect283r1",
+    Sect409k1 = 11                        => "sect409k1",
+    Sect409r1 = 12                        => "sect409r1",
+    Sect571k1 = 13                        => "sect571k1",
+    Sect571r1 = 14                        => "sect571r1",
+    Secp160k1 = 15                        => "secp160k1",
+    Secp160r1 = 16                        => "secp160r1",
+    Secp160r2 = 17                        => "secp160r2",
+    Secp192k1 = 18                        => "secp192k1",
+    Secp192r1 = 19                        => "secp192r1",
+    Secp224k1 = 20                        => "secp224k1",
+    Secp224r1 = 21                        => "secp224r1",
+    Secp256k1 = 22                        => "secp256k1",
+    Secp256r1 = 23                        => "secp256r1",
+    Secp384r1 = 24                        => "secp384r1",
+    Secp521r1 = 25                        => "secp521r1",
+    BrainpoolP256r1 = 26                  => "brainpoolP256r1",
+    BrainpoolP384r1 = 27                  => "brainpoolP384r1",
+    BrainpoolP512r1 = 28                  => "brainpoolP512r1",
+    EcdhX25519 = 29                       => "x25519",
+    EcdhX448 = 30                         => "x448",
+    BrainpoolP256r1tls13 = 31             => "brainpoolP256r1tls13",
+    BrainpoolP384r1tls13 = 32             => "brainpoolP384r1tls13",
+    BrainpoolP512r1tls13 = 33             => "brainpoolP512r1tls13",
+    Sm2 = 41                              => "curveSM2",
+    Ffdhe2048 = 0x100                     => "ffdhe2048",
+    Ffdhe3072 = 0x101                     => "ffdhe3072",
+    Ffdhe4096 = 0x102                     => "ffdhe4096",
+    Ffdhe6144 = 0x103                     => "ffdhe6144",
+    Ffdhe8192 = 0x104                     => "ffdhe8192",
+    ArbitraryExplicitPrimeCurves = 0xFF01 => "arbitrary_explicit_prime_curves",
+    ArbitraryExplicitChar2Curves = 0xFF02 => "arbitrary_explicit_char2_curves",
 }
 }
 
 impl NamedGroup {
+    /// Return the name of the group in the IANA registry (for ex. `x25519`), or None if unknown
+    pub fn iana_name(self) -> Option<&'static str> {
+        self.names().map(|(_, iana)| iana)
+    }
+
+    /// Return the expected length of a key share (TLS 1.3) for this group, in bytes
+    ///
+    /// For elliptic curves, this is the length of an uncompressed point. Return None if the
+    /// group is unknown or cannot be used in TLS 1.3.
+    pub fn key_share_length(self) -> Option<usize> {
+        match self {
+            NamedGroup::Secp256r1 => Some(65),
+            NamedGroup::Secp384r1 => Some(97),
+            NamedGroup::Secp521r1 => Some(133),
+            NamedGroup::EcdhX25519 => Some(32),
+            NamedGroup::EcdhX448 => Some(56),
+            NamedGroup::BrainpoolP256r1tls13 => Some(65),
+            NamedGroup::BrainpoolP384r1tls13 => Some(97),
+            NamedGroup::BrainpoolP512r1tls13 => Some(129),
+            NamedGroup::Sm2 => Some(65),
+            NamedGroup::Ffdhe2048 => Some(256),
+            NamedGroup::Ffdhe3072 => Some(384),
+            NamedGroup::Ffdhe4096 => Some(512),
+            NamedGroup::Ffdhe6144 => Some(768),
+            NamedGroup::Ffdhe8192 => Some(1024),
+            _ => None,
+        }
+    }
+
     /// Return key size of curve in bits, or None if unknown
     pub fn key_bits(self: NamedGroup) -> Option<u16> {
         match self {
@@ -121,6 +153,21 @@ impl display ECCurveType {
 }
 }
 
+/// EC point formats, as defined in the
+/// [IANA EC Point Format
+/// Registry](https://www.iana.org/assignments/tls-parameters/tls-parameters.xhtml#tls-parameters-9)
+#[derive(Clone, Copy, PartialEq, Eq, NomBE)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EcPointFormat(pub u8);
+
+newtype_enum! {
+impl debug EcPointFormat {
+    Uncompressed            = 0,
+    AnsiX962CompressedPrime = 1,
+    AnsiX962CompressedChar2 = 2,
+}
+}
+
 /// EC Point
 #[derive(Clone, Debug, PartialEq, NomBE)]
 pub struct ECPoint<'a> {
@@ -173,6 +220,16 @@ pub struct ServerECDHParams<'a> {
     pub public: ECPoint<'a>,
 }
 
+/// ECDH parameters of a ServerKeyExchange message, with the optional signature
+///
+/// The signature is present for ECDHE key exchanges, and absent for anonymous (ECDH_anon) key
+/// exchanges.
+#[derive(Debug, PartialEq)]
+pub struct ServerECDHEParams<'a> {
+    pub params: ServerECDHParams<'a>,
+    pub signature: Option<DigitallySigned<'a>>,
+}
+
 /// Parse the entire input as a list of named groups (curves)
 pub fn parse_named_groups(i: &[u8]) -> IResult<&[u8], Vec<NamedGroup>> {
     let len = i.len();
@@ -190,11 +247,36 @@ pub fn parse_named_groups(i: &[u8]) -> IResult<&[u8], Vec<NamedGroup>> {
 }
 
 #[inline]
-pub fn parse_ec_parameters(i: &[u8]) -> IResult<&[u8], ECParameters> {
+pub fn parse_ec_parameters(i: &[u8]) -> IResult<&[u8], ECParameters<'_>> {
     ECParameters::parse(i)
 }
 
 #[inline]
-pub fn parse_ecdh_params(i: &[u8]) -> IResult<&[u8], ServerECDHParams> {
+pub fn parse_ecdh_params(i: &[u8]) -> IResult<&[u8], ServerECDHParams<'_>> {
     ServerECDHParams::parse(i)
 }
+
+/// Parse the content of a ServerKeyExchange message, for the negotiated key exchange `kx`
+///
+/// `kx` must be `Ecdhe` (signed parameters, using the TLS 1.2 `DigitallySigned` format), or
+/// `Aecdh` (anonymous key exchange, no signature). Other key exchange methods are rejected,
+/// including `Ecdh`: with static ECDH, the server parameters are in the server certificate, and
+/// no ServerKeyExchange message is sent.
+pub fn parse_server_ecdh_params(
+    i: &[u8],
+    kx: TlsCipherKx,
+) -> IResult<&[u8], ServerECDHEParams<'_>> {
+    let signed = match kx {
+        TlsCipherKx::Ecdhe => true,
+        TlsCipherKx::Aecdh => false,
+        _ => return Err(Err::Error(make_error(i, ErrorKind::Switch))),
+    };
+    let (i, params) = parse_ecdh_params(i)?;
+    let (i, signature) = if signed {
+        let (i, signature) = parse_digitally_signed(i)?;
+        (i, Some(signature))
+    } else {
+        (i, None)
+    };
+    Ok((i, ServerECDHEParams { params, signature }))
+}
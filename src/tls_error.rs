@@ -0,0 +1,214 @@
+//! Typed parsing errors
+//!
+//! The parsing functions return nom errors, which only contain the nom `ErrorKind` and the
+//! position of the error. The `_ctx` functions of this module wrap the main parsers, and return a
+//! `TlsParseError` describing which part of the input is invalid.
+
+use crate::tls::*;
+use crate::tls_extensions::{parse_tls_extension, TlsExtension};
+use alloc::vec::Vec;
+use core::fmt;
+use nom::error::ErrorKind;
+use nom::{Err, Needed};
+
+/// Error type for the `_ctx` parsing functions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TlsParseError {
+    /// More data is needed to parse the input
+    Incomplete(Needed),
+    /// The record type is not a known `TlsRecordType`
+    InvalidRecordType(u8),
+    /// The version (of the record, or of a message) is not supported
+    UnsupportedVersion(u16),
+    /// The record version is not consistent with the negotiated version
+    UnexpectedRecordVersion(u16),
+    /// The record length exceeds `MAX_RECORD_LEN`
+    RecordTooLarge(u16),
+    /// The handshake message type is unknown, or not supported
+    UnknownHandshakeType(u8),
+    /// The handshake message is shorter than its declared length
+    TruncatedHandshake,
+    /// The declared length of an extension does not match the extensions block
+    ExtensionLengthMismatch,
+    /// Invalid content, with the nom error kind
+    InvalidMessage(ErrorKind),
+}
+
+impl fmt::Display for TlsParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TlsParseError::Incomplete(Needed::Size(n)) => {
+                write!(f, "incomplete data ({} more bytes needed)", n)
+            }
+            TlsParseError::Incomplete(Needed::Unknown) => write!(f, "incomplete data"),
+            TlsParseError::InvalidRecordType(t) => write!(f, "invalid record type 0x{:02x}", t),
+            TlsParseError::UnsupportedVersion(v) => write!(f, "unsupported version 0x{:04x}", v),
+            TlsParseError::UnexpectedRecordVersion(v) => {
+                write!(f, "unexpected record version 0x{:04x}", v)
+            }
+            TlsParseError::RecordTooLarge(l) => write!(f, "record too large ({} bytes)", l),
+            TlsParseError::UnknownHandshakeType(t) => {
+                write!(f, "unknown handshake type 0x{:02x}", t)
+            }
+            TlsParseError::TruncatedHandshake => write!(f, "truncated handshake message"),
+            TlsParseError::ExtensionLengthMismatch => write!(f, "extension length mismatch"),
+            TlsParseError::InvalidMessage(e) => write!(f, "invalid message ({:?})", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TlsParseError {}
+
+impl<I> From<nom::error::Error<I>> for TlsParseError {
+    fn from(e: nom::error::Error<I>) -> Self {
+        TlsParseError::InvalidMessage(e.code)
+    }
+}
+
+impl<E> From<Err<E>> for TlsParseError
+where
+    TlsParseError: From<E>,
+{
+    fn from(e: Err<E>) -> Self {
+        match e {
+            Err::Incomplete(n) => TlsParseError::Incomplete(n),
+            Err::Error(e) | Err::Failure(e) => TlsParseError::from(e),
+        }
+    }
+}
+
+fn check_record_header(hdr: &TlsRecordHeader) -> Result<(), TlsParseError> {
+    if TlsRecordType::from_u8(hdr.record_type.0).is_none() {
+        return Err(TlsParseError::InvalidRecordType(hdr.record_type.0));
+    }
+    if !(0x0300..=0x0304).contains(&hdr.version.0) {
+        return Err(TlsParseError::UnsupportedVersion(hdr.version.0));
+    }
+    if hdr.len > MAX_RECORD_LEN {
+        return Err(TlsParseError::RecordTooLarge(hdr.len));
+    }
+    Ok(())
+}
+
+/// Check that the version of a record is consistent with the negotiated version
+///
+/// This is meant for records sent after the version was negotiated (after the ServerHello).
+/// If TLS 1.3 (or a TLS 1.3 draft, starting from draft 22) was negotiated, the record version
+/// must be TLS 1.2 ([RFC8446](https://tools.ietf.org/html/rfc8446) section 5.1). Earlier TLS 1.3
+/// drafts required TLS 1.0. For previous versions, the record version must be the negotiated
+/// version.
+///
+/// Return `TlsParseError::UnexpectedRecordVersion` if the version is not consistent.
+pub fn check_record_version(
+    header: &TlsRecordHeader,
+    negotiated: TlsVersion,
+) -> Result<(), TlsParseError> {
+    let expected = if negotiated == TlsVersion::Tls13 {
+        TlsVersion::Tls12
+    } else if negotiated.0 & 0xff00 == 0x7f00 {
+        if negotiated.0 >= TlsVersion::Tls13Draft22.0 {
+            TlsVersion::Tls12
+        } else {
+            TlsVersion::Tls10
+        }
+    } else {
+        negotiated
+    };
+    if header.version != expected {
+        return Err(TlsParseError::UnexpectedRecordVersion(header.version.0));
+    }
+    Ok(())
+}
+
+/// Read TLS record envelope, returning a typed error
+///
+/// See `parse_tls_raw_record`. The record type must be known, and the record version must be
+/// between SSL 3.0 and TLS 1.3.
+pub fn parse_tls_raw_record_ctx(i: &[u8]) -> Result<(&[u8], TlsRawRecord<'_>), TlsParseError> {
+    let (_, hdr) = parse_tls_record_header(i)?;
+    check_record_header(&hdr)?;
+    Ok(parse_tls_raw_record(i)?)
+}
+
+/// Parse one packet only, as plaintext, returning a typed error
+///
+/// See `parse_tls_plaintext`. The record header is checked like in `parse_tls_raw_record_ctx`.
+pub fn parse_tls_plaintext_ctx(i: &[u8]) -> Result<(&[u8], TlsPlaintext<'_>), TlsParseError> {
+    let (rem, record) = parse_tls_raw_record_ctx(i)?;
+    match parse_tls_record_with_header(record.data, &record.hdr) {
+        Ok((_, msg)) => Ok((
+            rem,
+            TlsPlaintext {
+                hdr: record.hdr,
+                msg,
+            },
+        )),
+        // the first message is invalid, get a more precise error
+        Err(e) if record.hdr.record_type == TlsRecordType::Handshake => {
+            parse_tls_message_handshake_ctx(record.data)?;
+            Err(e.into())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Parse a TLS handshake message, returning a typed error
+///
+/// See `parse_tls_message_handshake`. Since the input should be the content of a complete record,
+/// a message shorter than its declared length is reported as `TruncatedHandshake`.
+pub fn parse_tls_message_handshake_ctx(i: &[u8]) -> Result<(&[u8], TlsMessage<'_>), TlsParseError> {
+    let (ht, hl, raw_msg) = match i {
+        [ht, l0, l1, l2, raw_msg @ ..] => (
+            *ht,
+            u32::from_be_bytes([0, *l0, *l1, *l2]) as usize,
+            raw_msg,
+        ),
+        _ => return Err(TlsParseError::TruncatedHandshake),
+    };
+    if raw_msg.len() < hl {
+        return Err(TlsParseError::TruncatedHandshake);
+    }
+    match parse_tls_message_handshake(i) {
+        Ok(r) => Ok(r),
+        Err(Err::Error(e)) if e.code == ErrorKind::Switch => {
+            Err(TlsParseError::UnknownHandshakeType(ht))
+        }
+        Err(e) => {
+            if TlsHandshakeType(ht) == TlsHandshakeType::ServerHello {
+                if let [v0, v1, ..] = raw_msg {
+                    let version = u16::from_be_bytes([*v0, *v1]);
+                    if !matches!(version, 0x0300..=0x0303 | 0x7f12) {
+                        return Err(TlsParseError::UnsupportedVersion(version));
+                    }
+                }
+            }
+            match e {
+                Err::Incomplete(_) => Err(TlsParseError::TruncatedHandshake),
+                e => Err(e.into()),
+            }
+        }
+    }
+}
+
+/// Parse zero or more TLS extensions (of any type), returning a typed error
+///
+/// Unlike `parse_tls_extensions`, the entire input must be consumed: an extension header or
+/// content exceeding the extensions block is reported as `ExtensionLengthMismatch`.
+pub fn parse_tls_extensions_ctx(i: &[u8]) -> Result<Vec<TlsExtension<'_>>, TlsParseError> {
+    let mut v = Vec::new();
+    let mut i = i;
+    while !i.is_empty() {
+        let ext_len = match i {
+            [_, _, l0, l1, ..] => u16::from_be_bytes([*l0, *l1]) as usize,
+            _ => return Err(TlsParseError::ExtensionLengthMismatch),
+        };
+        if i.len() - 4 < ext_len {
+            return Err(TlsParseError::ExtensionLengthMismatch);
+        }
+        let (rem, ext) = parse_tls_extension(i)?;
+        v.push(ext);
+        i = rem;
+    }
+    Ok(v)
+}
@@ -5,15 +5,28 @@
 //! - [RFC6066](https://tools.ietf.org/html/rfc6066)
 //! - [RFC7366](https://tools.ietf.org/html/rfc7366)
 //! - [RFC7627](https://tools.ietf.org/html/rfc7627)
+//! - [RFC8449](https://tools.ietf.org/html/rfc8449)
+//! - [RFC9001](https://tools.ietf.org/html/rfc9001)
+//! - [draft-ietf-tls-esni](https://datatracker.ietf.org/doc/draft-ietf-tls-esni/)
 
+#[cfg(feature = "serde")]
+use crate::serde_hex::*;
 use crate::tls::{parse_tls_versions, TlsCipherSuiteID, TlsVersion};
-use crate::tls_ec::{parse_named_groups, NamedGroup};
+use crate::tls_ec::{parse_named_groups, EcPointFormat, NamedGroup};
+use crate::tls_grease::is_grease_u16;
+use crate::tls_sign_hash::SignatureScheme;
+use alloc::borrow::Cow;
+use alloc::format;
 use alloc::{vec, vec::Vec};
+use core::convert::TryInto;
+use core::iter::FusedIterator;
+use core::str::from_utf8;
 use nom::bytes::streaming::{tag, take};
-use nom::combinator::{complete, cond, map, map_parser, opt, verify};
+use nom::combinator::{all_consuming, complete, cond, map, map_parser, verify};
 use nom::error::{make_error, ErrorKind};
-use nom::multi::{length_data, many0};
-use nom::number::streaming::{be_u16, be_u32, be_u8};
+use nom::multi::{length_data, many0, many1};
+use nom::number::streaming::{be_u16, be_u24, be_u32, be_u8};
+use nom::sequence::pair;
 use nom::{Err, IResult};
 use nom_derive::{NomBE, Parse};
 use rusticata_macros::newtype_enum;
@@ -23,6 +36,7 @@ use rusticata_macros::newtype_enum;
 /// Extensions](http://www.iana.org/assignments/tls-extensiontype-values/tls-extensiontype-values.xhtml)
 /// registry
 #[derive(Clone, Copy, Debug, PartialEq, Eq, NomBE)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TlsExtensionType(pub u16);
 
 newtype_enum! {
@@ -51,11 +65,13 @@ impl display TlsExtensionType {
     Padding                             = 21, // [RFC7685]
     EncryptThenMac                      = 22, // [RFC7366]
     ExtendedMasterSecret                = 23, // [RFC7627]
-    TokenBinding                        = 24,
+    TokenBinding                        = 24, // [RFC8472]
     CachedInfo                          = 25,
 
     RecordSizeLimit                     = 28, // [RFC8449]
 
+    DelegatedCredentials                = 34, // [RFC9345]
+
     SessionTicketTLS                    = 35,
 
     KeyShareOld                         = 40, // moved to 51 in TLS 1.3 draft 23
@@ -71,10 +87,19 @@ impl display TlsExtensionType {
     SigAlgorithmsCert                   = 50, // TLS 1.3 draft 23
     KeyShare                            = 51, // TLS 1.3 draft 23
 
+    ConnectionId                        = 54, // [RFC9146]
+
+    QuicTransportParameters             = 57, // [RFC9001]
+
     NextProtocolNegotiation             = 13172,
 
+    ApplicationSettingsOld              = 17513, // draft-vvv-tls-alps, moved to 17613
+    ApplicationSettings                 = 17613, // draft-vvv-tls-alps
+
     Grease                              = 0xfafa,
 
+    EncryptedClientHello                = 0xfe0d, // draft-ietf-tls-esni
+
     RenegotiationInfo                   = 0xff01, // [RFC5746]
     EncryptedServerName                 = 0xffce, // draft-ietf-tls-esni
 }
@@ -95,47 +120,135 @@ impl From<TlsExtensionType> for u16 {
 /// TLS extensions
 ///
 #[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum TlsExtension<'a> {
-    SNI(Vec<(SNIType, &'a [u8])>),
-    MaxFragmentLength(u8),
-    StatusRequest(Option<(CertificateStatusType, &'a [u8])>),
+    SNI(Vec<ServerName<'a>>),
+    MaxFragmentLength(MaxFragmentLength),
+    /// Truncated HMAC [RFC6066] (deprecated)
+    TruncatedHmac,
+    StatusRequest(
+        #[cfg_attr(feature = "serde", serde(serialize_with = "hex_tagged_opt"))]
+        Option<(CertificateStatusType, &'a [u8])>,
+    ),
+    /// Status of a certificate, as sent in the `status_request` extension of a TLS 1.3
+    /// CertificateEntry ([RFC8446] section 4.4.2.1): the status type and the response (for ex. a
+    /// DER-encoded OCSPResponse)
+    CertificateStatus(
+        CertificateStatusType,
+        #[cfg_attr(feature = "serde", serde(serialize_with = "hex"))] &'a [u8],
+    ),
+    /// User Mapping [RFC4681]: the list of `UserMappingType` values (ClientHello), or empty
+    /// (ServerHello)
+    UserMapping(Vec<u8>),
+    /// Client Authorization extension [RFC5878]: list of supported formats
+    ClientAuthz(Vec<AuthzDataFormat>),
+    /// Server Authorization extension [RFC5878]: list of supported formats
+    ServerAuthz(Vec<AuthzDataFormat>),
     EllipticCurves(Vec<NamedGroup>),
-    EcPointFormats(&'a [u8]),
+    EcPointFormats(Vec<EcPointFormat>),
     SignatureAlgorithms(Vec<u16>),
+    SignatureAlgorithmsCert(Vec<u16>),
+    DelegatedCredentials(Vec<u16>),
     RecordSizeLimit(u16),
-    SessionTicket(&'a [u8]),
-    KeyShareOld(&'a [u8]),
-    KeyShare(&'a [u8]),
-    PreSharedKey(&'a [u8]),
+    TokenBinding {
+        major: u8,
+        minor: u8,
+        key_params: Vec<u8>,
+    },
+    SessionTicket(#[cfg_attr(feature = "serde", serde(serialize_with = "hex"))] &'a [u8]),
+    KeyShareOld(#[cfg_attr(feature = "serde", serde(serialize_with = "hex"))] &'a [u8]),
+    /// Key Share extension (TLS 1.3)
+    ///
+    /// The content depends on the message type, see `parse_key_share_client_hello`,
+    /// `parse_key_share_server_hello` and `parse_key_share_hello_retry_request`.
+    KeyShare(#[cfg_attr(feature = "serde", serde(serialize_with = "hex"))] &'a [u8]),
+    /// Pre-Shared Key extension (TLS 1.3), when the message type is not known
+    PreSharedKey(#[cfg_attr(feature = "serde", serde(serialize_with = "hex"))] &'a [u8]),
+    /// Pre-Shared Key extension (TLS 1.3), sent by clients
+    PreSharedKeyOffered(OfferedPsks<'a>),
+    /// Pre-Shared Key extension (TLS 1.3), sent by servers: index of the selected identity
+    PreSharedKeySelected(u16),
     EarlyData(Option<u32>),
     SupportedVersions(Vec<TlsVersion>),
-    Cookie(&'a [u8]),
-    PskExchangeModes(Vec<u8>),
-    Heartbeat(u8),
-    ALPN(Vec<&'a [u8]>),
-
-    SignedCertificateTimestamp(Option<&'a [u8]>),
-    Padding(&'a [u8]),
+    Cookie(#[cfg_attr(feature = "serde", serde(serialize_with = "hex"))] &'a [u8]),
+    PskExchangeModes(Vec<PskKeyExchangeMode>),
+    Heartbeat(HeartbeatMode),
+    ALPN(#[cfg_attr(feature = "serde", serde(serialize_with = "hex_vec"))] Vec<&'a [u8]>),
+
+    /// Signed Certificate Timestamp extension: `None` if empty (as sent by clients), or the list
+    /// of serialized SCTs
+    SignedCertificateTimestamp(
+        #[cfg_attr(feature = "serde", serde(serialize_with = "hex_vec_opt"))] Option<Vec<&'a [u8]>>,
+    ),
+    Padding(#[cfg_attr(feature = "serde", serde(serialize_with = "hex"))] &'a [u8]),
     EncryptThenMac,
     ExtendedMasterSecret,
 
+    CertificateAuthorities(
+        #[cfg_attr(feature = "serde", serde(serialize_with = "hex_vec"))] Vec<&'a [u8]>,
+    ),
     OidFilters(Vec<OidFilter<'a>>),
     PostHandshakeAuth,
 
-    NextProtocolNegotiation,
+    QuicTransportParameters(
+        #[cfg_attr(feature = "serde", serde(serialize_with = "hex_tagged_vec"))]
+        Vec<(u64, &'a [u8])>,
+    ),
 
-    RenegotiationInfo(&'a [u8]),
+    /// Next Protocol Negotiation extension, empty (as sent by clients)
+    NextProtocolNegotiation,
+    /// Next Protocol Negotiation extension, with the list of protocols advertised by the server
+    NextProtocolNegotiationServer(
+        #[cfg_attr(feature = "serde", serde(serialize_with = "hex_vec"))] Vec<&'a [u8]>,
+    ),
+
+    /// Application-Layer Protocol Settings (ALPS), as sent by clients (code point 17613)
+    ApplicationSettings(
+        #[cfg_attr(feature = "serde", serde(serialize_with = "hex_vec"))] Vec<&'a [u8]>,
+    ),
+    /// Application-Layer Protocol Settings (ALPS), as sent by clients, using the previous code
+    /// point (17513)
+    ApplicationSettingsOld(
+        #[cfg_attr(feature = "serde", serde(serialize_with = "hex_vec"))] Vec<&'a [u8]>,
+    ),
+
+    /// The `renegotiated_connection` field: empty for the initial handshake, or the
+    /// `verify_data` of the previous handshake(s) when renegotiating
+    RenegotiationInfo(#[cfg_attr(feature = "serde", serde(serialize_with = "hex"))] &'a [u8]),
+    /// Multiple Certificate Status Request extension, defined in [RFC6961]: `None` if empty (as
+    /// sent by servers), or the list of requests
+    StatusRequestV2(Option<Vec<CertificateStatusRequestItemV2<'a>>>),
+    /// Client Certificate Type extension [RFC7250]: the list of supported types (ClientHello), or
+    /// the selected type (ServerHello, EncryptedExtensions)
+    ClientCertificateType(Vec<CertificateType>),
+    /// Server Certificate Type extension [RFC7250]: the list of supported types (ClientHello), or
+    /// the selected type (ServerHello, EncryptedExtensions)
+    ServerCertificateType(Vec<CertificateType>),
+    /// Connection ID (DTLS), defined in [RFC9146]
+    ///
+    /// An empty CID means that the sender does not need the peer to use a connection ID.
+    ConnectionId(#[cfg_attr(feature = "serde", serde(serialize_with = "hex"))] &'a [u8]),
     EncryptedServerName {
         ciphersuite: TlsCipherSuiteID,
         group: NamedGroup,
+        #[cfg_attr(feature = "serde", serde(serialize_with = "hex"))]
         key_share: &'a [u8],
+        #[cfg_attr(feature = "serde", serde(serialize_with = "hex"))]
         record_digest: &'a [u8],
+        #[cfg_attr(feature = "serde", serde(serialize_with = "hex"))]
         encrypted_sni: &'a [u8],
     },
+    EncryptedClientHello(ECHClientHello<'a>),
 
-    Grease(u16, &'a [u8]),
+    Grease(
+        u16,
+        #[cfg_attr(feature = "serde", serde(serialize_with = "hex"))] &'a [u8],
+    ),
 
-    Unknown(TlsExtensionType, &'a [u8]),
+    Unknown(
+        TlsExtensionType,
+        #[cfg_attr(feature = "serde", serde(serialize_with = "hex"))] &'a [u8],
+    ),
 }
 
 impl<'a> From<&'a TlsExtension<'a>> for TlsExtensionType {
@@ -144,15 +257,25 @@ impl<'a> From<&'a TlsExtension<'a>> for TlsExtensionType {
         match *ext {
             TlsExtension::SNI(_)                        => TlsExtensionType::ServerName,
             TlsExtension::MaxFragmentLength(_)          => TlsExtensionType::MaxFragmentLength,
+            TlsExtension::TruncatedHmac                 => TlsExtensionType::TruncatedHMac,
             TlsExtension::StatusRequest(_)              => TlsExtensionType::StatusRequest,
+            TlsExtension::CertificateStatus(..)         => TlsExtensionType::StatusRequest,
+            TlsExtension::UserMapping(_)                => TlsExtensionType::UserMapping,
+            TlsExtension::ClientAuthz(_)                => TlsExtensionType::ClientAuthz,
+            TlsExtension::ServerAuthz(_)                => TlsExtensionType::ServerAuthz,
             TlsExtension::EllipticCurves(_)             => TlsExtensionType::SupportedGroups,
             TlsExtension::EcPointFormats(_)             => TlsExtensionType::EcPointFormats,
             TlsExtension::SignatureAlgorithms(_)        => TlsExtensionType::SignatureAlgorithms,
+            TlsExtension::SignatureAlgorithmsCert(_)    => TlsExtensionType::SigAlgorithmsCert,
+            TlsExtension::DelegatedCredentials(_)       => TlsExtensionType::DelegatedCredentials,
             TlsExtension::SessionTicket(_)              => TlsExtensionType::SessionTicketTLS,
             TlsExtension::RecordSizeLimit(_)            => TlsExtensionType::RecordSizeLimit,
+            TlsExtension::TokenBinding{..}              => TlsExtensionType::TokenBinding,
             TlsExtension::KeyShareOld(_)                => TlsExtensionType::KeyShareOld,
             TlsExtension::KeyShare(_)                   => TlsExtensionType::KeyShare,
             TlsExtension::PreSharedKey(_)               => TlsExtensionType::PreSharedKey,
+            TlsExtension::PreSharedKeyOffered(_)        => TlsExtensionType::PreSharedKey,
+            TlsExtension::PreSharedKeySelected(_)       => TlsExtensionType::PreSharedKey,
             TlsExtension::EarlyData(_)                  => TlsExtensionType::EarlyData,
             TlsExtension::SupportedVersions(_)          => TlsExtensionType::SupportedVersions,
             TlsExtension::Cookie(_)                     => TlsExtensionType::Cookie,
@@ -163,34 +286,237 @@ impl<'a> From<&'a TlsExtension<'a>> for TlsExtensionType {
             TlsExtension::Padding(_)                    => TlsExtensionType::Padding,
             TlsExtension::EncryptThenMac                => TlsExtensionType::EncryptThenMac,
             TlsExtension::ExtendedMasterSecret          => TlsExtensionType::ExtendedMasterSecret,
+            TlsExtension::CertificateAuthorities(_)     => TlsExtensionType::CertificateAuthorities,
             TlsExtension::OidFilters(_)                 => TlsExtensionType::OidFilters,
             TlsExtension::PostHandshakeAuth             => TlsExtensionType::PostHandshakeAuth,
+            TlsExtension::QuicTransportParameters(_)    => TlsExtensionType::QuicTransportParameters,
             TlsExtension::NextProtocolNegotiation       => TlsExtensionType::NextProtocolNegotiation,
+            TlsExtension::NextProtocolNegotiationServer(_) => TlsExtensionType::NextProtocolNegotiation,
+            TlsExtension::ApplicationSettings(_)        => TlsExtensionType::ApplicationSettings,
+            TlsExtension::ApplicationSettingsOld(_)     => TlsExtensionType::ApplicationSettingsOld,
             TlsExtension::RenegotiationInfo(_)          => TlsExtensionType::RenegotiationInfo,
+            TlsExtension::ConnectionId(_)               => TlsExtensionType::ConnectionId,
+            TlsExtension::StatusRequestV2(_)            => TlsExtensionType::StatusRequestv2,
+            TlsExtension::ClientCertificateType(_)      => TlsExtensionType::ClientCertificateType,
+            TlsExtension::ServerCertificateType(_)      => TlsExtensionType::ServerCertificateType,
             TlsExtension::EncryptedServerName{..}       => TlsExtensionType::EncryptedServerName,
+            TlsExtension::EncryptedClientHello(_)       => TlsExtensionType::EncryptedClientHello,
             TlsExtension::Grease(_,_)                   => TlsExtensionType::Grease,
             TlsExtension::Unknown(x,_)                  => x
         }
     }
 }
 
+impl<'a> TlsExtension<'a> {
+    /// Return the extension type, as sent on the wire
+    ///
+    /// Unlike `TlsExtensionType::from`, the actual value of GREASE extensions is returned.
+    /// For unknown extensions, this is the stored type.
+    pub fn extension_type(&self) -> u16 {
+        match self {
+            TlsExtension::Grease(ext_type, _) => *ext_type,
+            _ => TlsExtensionType::from(self).0,
+        }
+    }
+
+    /// Return the first `host_name` of a Server Name Indication extension
+    ///
+    /// Return `None` if this is not a SNI extension, or if it contains no host name.
+    pub fn first_hostname(&self) -> Option<&'a [u8]> {
+        match self {
+            TlsExtension::SNI(v) => v.iter().find(|sn| sn.is_host_name()).map(|sn| sn.name),
+            _ => None,
+        }
+    }
+
+    /// Tell if this is an ALPN extension offering the protocol `proto`
+    ///
+    /// For ex. `ext.alpn_contains(b"h2")`.
+    pub fn alpn_contains(&self, proto: &[u8]) -> bool {
+        match self {
+            TlsExtension::ALPN(v) => v.contains(&proto),
+            _ => false,
+        }
+    }
+
+    /// Return the protocols of an ALPN extension, as strings
+    ///
+    /// Names are decoded as UTF-8. Names that are not valid UTF-8 are escaped (non-printable or
+    /// non-ASCII bytes are written as `\xNN`). Return an empty list if this is not an ALPN
+    /// extension.
+    pub fn protocol_strings(&self) -> Vec<Cow<'a, str>> {
+        match self {
+            TlsExtension::ALPN(v) => v
+                .iter()
+                .map(|&name| match from_utf8(name) {
+                    Ok(s) => Cow::Borrowed(s),
+                    Err(_) => Cow::Owned(format!("{}", name.escape_ascii())),
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Return the groups of a Supported Groups (Elliptic Curves) extension
+    ///
+    /// Return `None` if this is not a Supported Groups extension. Unknown groups are kept, and
+    /// their raw value is available as `NamedGroup.0`.
+    pub fn named_groups(&self) -> Option<&[NamedGroup]> {
+        match self {
+            TlsExtension::EllipticCurves(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Return the signature schemes of a Signature Algorithms (or Signature Algorithms Cert)
+    /// extension
+    ///
+    /// Return `None` if this is not a Signature Algorithms extension. Each value is interpreted as
+    /// a TLS 1.3 `SignatureScheme`, which is compatible with the TLS 1.2 (hash, signature) pairs
+    /// for legacy algorithms. Unknown values are kept, use `SignatureScheme::from_u16` to check
+    /// if a scheme is registered.
+    pub fn signature_schemes(&self) -> Option<Vec<SignatureScheme>> {
+        match self {
+            TlsExtension::SignatureAlgorithms(v) | TlsExtension::SignatureAlgorithmsCert(v) => {
+                Some(v.iter().map(|&s| SignatureScheme(s)).collect())
+            }
+            _ => None,
+        }
+    }
+
+    /// Return the ticket of a Session Ticket extension, if not empty
+    ///
+    /// In a ClientHello, this is the ticket used to resume a session.
+    pub fn session_ticket(&self) -> Option<&'a [u8]> {
+        match self {
+            TlsExtension::SessionTicket(ticket) if !ticket.is_empty() => Some(ticket),
+            _ => None,
+        }
+    }
+
+    /// Tell if this is an empty Session Ticket extension
+    ///
+    /// In a ServerHello, this is the acknowledgment that the server will send a
+    /// NewSessionTicket message. In a ClientHello, it indicates that the client supports session
+    /// tickets, but has no ticket to resume a session with.
+    pub fn is_empty_ack(&self) -> bool {
+        matches!(self, TlsExtension::SessionTicket(ticket) if ticket.is_empty())
+    }
+
+    /// Return the raw content of a Padding extension
+    ///
+    /// The content is not checked, see `zero_padding_len` for a strict version.
+    pub fn padding(&self) -> Option<&'a [u8]> {
+        match self {
+            TlsExtension::Padding(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Return the length of a Padding extension, if the padding is valid
+    ///
+    /// [RFC7685] requires the padding to be filled with zero bytes. Return `None` if this is not
+    /// a Padding extension, or if any byte is not zero (which could be used to hide data, or to
+    /// fingerprint clients).
+    pub fn zero_padding_len(&self) -> Option<usize> {
+        self.padding()
+            .filter(|data| data.iter().all(|&b| b == 0))
+            .map(|data| data.len())
+    }
+
+    /// Parse the OCSP request of a Status Request extension (sent by clients)
+    ///
+    /// Return `None` if this is not a Status Request extension, if the status type is not OCSP,
+    /// or if the request is invalid. The raw request is still available in the extension.
+    pub fn ocsp_status_request(&self) -> Option<OCSPStatusRequest<'a>> {
+        match self {
+            TlsExtension::StatusRequest(Some((CertificateStatusType::OCSP, request))) => {
+                parse_ocsp_status_request(request).ok().map(|(_, req)| req)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// An entry of the Key Share extension, as defined in [RFC8446] section 4.2.8
 #[derive(Clone, Debug, PartialEq)]
 pub struct KeyShareEntry<'a> {
     pub group: NamedGroup, // NamedGroup
     pub kx: &'a [u8],      // Key Exchange Data
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, NomBE)]
+/// A PSK identity, offered by the client in the Pre-Shared Key extension [RFC8446]
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PskIdentity<'a> {
+    /// Label of the key (for ex. a session ticket)
+    #[cfg_attr(feature = "serde", serde(serialize_with = "hex"))]
+    pub identity: &'a [u8],
+    /// Age of the ticket, added to the `ticket_age_add` value of the NewSessionTicket message
+    pub obfuscated_ticket_age: u32,
+}
+
+/// Content of the Pre-Shared Key extension, sent by clients [RFC8446]
+///
+/// There is one binder for each identity, in the same order.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct OfferedPsks<'a> {
+    pub identities: Vec<PskIdentity<'a>>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "hex_vec"))]
+    pub binders: Vec<&'a [u8]>,
+}
+
+/// Heartbeat mode, as defined in [RFC6520] section 2
+#[derive(Clone, Copy, PartialEq, Eq, NomBE)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HeartbeatMode(pub u8);
+
+newtype_enum! {
+impl debug HeartbeatMode {
+    PeerAllowedToSend    = 1,
+    PeerNotAllowedToSend = 2,
+}
+}
+
+/// Maximum fragment length, as defined in [RFC6066] section 4
+#[derive(Clone, Copy, PartialEq, Eq, NomBE)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MaxFragmentLength(pub u8);
+
+newtype_enum! {
+impl debug MaxFragmentLength {
+    Len512  = 1,
+    Len1024 = 2,
+    Len2048 = 3,
+    Len4096 = 4,
+}
+}
+
+impl MaxFragmentLength {
+    /// Return the maximum fragment length in bytes, or `None` if the value is unknown
+    pub const fn bytes(&self) -> Option<u16> {
+        match self.0 {
+            1..=4 => Some(1 << (8 + self.0)),
+            _ => None,
+        }
+    }
+}
+
+/// PSK key exchange mode, as defined in [RFC8446] section 4.2.9
+#[derive(Clone, Copy, PartialEq, Eq, NomBE)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct PskKeyExchangeMode(pub u8);
 
 newtype_enum! {
-impl PskKeyExchangeMode {
+impl debug PskKeyExchangeMode {
     Psk    = 0,
     PskDhe = 1,
 }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, NomBE)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SNIType(pub u8);
 
 newtype_enum! {
@@ -199,18 +525,155 @@ impl display SNIType {
 }
 }
 
+/// An entry of the Server Name Indication extension
+///
+/// The name is kept as raw bytes, since it is not guaranteed to be valid UTF-8.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ServerName<'a> {
+    pub name_type: SNIType,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "hex"))]
+    pub name: &'a [u8],
+}
+
+impl<'a> ServerName<'a> {
+    pub const fn new(name_type: SNIType, name: &'a [u8]) -> Self {
+        ServerName { name_type, name }
+    }
+
+    /// Build a `host_name` entry
+    pub const fn host_name(name: &'a [u8]) -> Self {
+        ServerName::new(SNIType::HostName, name)
+    }
+
+    pub fn is_host_name(&self) -> bool {
+        self.name_type == SNIType::HostName
+    }
+
+    /// Return the name as a string, if it is valid UTF-8
+    pub fn as_str(&self) -> Option<&'a str> {
+        from_utf8(self.name).ok()
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, NomBE)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct CertificateStatusType(pub u8);
 
 newtype_enum! {
 impl debug CertificateStatusType {
     OCSP = 1,
+    OCSPMulti = 2, // [RFC6961]
+}
+}
+
+/// Certificate types, as defined in [RFC7250] and the IANA TLS Certificate Types registry
+#[derive(Clone, Copy, PartialEq, Eq, NomBE)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CertificateType(pub u8);
+
+newtype_enum! {
+impl debug CertificateType {
+    X509 = 0,
+    OpenPgp = 1, // [RFC6091]
+    RawPublicKey = 2,
+}
+}
+
+/// Authorization data formats, as defined in [RFC5878] section 3
+#[derive(Clone, Copy, PartialEq, Eq, NomBE)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AuthzDataFormat(pub u8);
+
+newtype_enum! {
+impl debug AuthzDataFormat {
+    X509AttrCert = 0,
+    SamlAssertion = 1,
+    X509AttrCertUrl = 2,
+    SamlAssertionUrl = 3,
+}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, NomBE)]
+pub struct ECHClientHelloType(pub u8);
+
+newtype_enum! {
+impl display ECHClientHelloType {
+    Outer = 0,
+    Inner = 1,
+}
+}
+
+/// Content of the Encrypted Client Hello extension, sent in ClientHello messages
+///
+/// Defined in [draft-ietf-tls-esni]
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ECHClientHello<'a> {
+    /// ClientHelloOuter, carrying the encrypted ClientHelloInner
+    Outer {
+        /// HPKE KDF identifier
+        kdf_id: u16,
+        /// HPKE AEAD identifier
+        aead_id: u16,
+        config_id: u8,
+        /// HPKE encapsulated key
+        #[cfg_attr(feature = "serde", serde(serialize_with = "hex"))]
+        enc: &'a [u8],
+        /// Encrypted ClientHelloInner
+        #[cfg_attr(feature = "serde", serde(serialize_with = "hex"))]
+        payload: &'a [u8],
+    },
+    /// ClientHelloInner (empty body)
+    Inner,
+}
+
+/// OCSP status request, sent in the Status Request extension if the status type is
+/// `CertificateStatusType::OCSP`
+///
+/// Defined in [RFC6066] section 8
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OCSPStatusRequest<'a> {
+    /// List of DER-encoded OCSP responder IDs
+    pub responder_id_list: Vec<&'a [u8]>,
+    /// DER-encoded OCSP request extensions
+    pub request_extensions: &'a [u8],
 }
+
+/// An entry of the Multiple Certificate Status Request extension
+///
+/// Defined in [RFC6961] section 2.2
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CertificateStatusRequestItemV2<'a> {
+    pub status_type: CertificateStatusType,
+    /// The request, depending on the status type
+    #[cfg_attr(feature = "serde", serde(serialize_with = "hex"))]
+    pub request: &'a [u8],
+}
+
+impl<'a> CertificateStatusRequestItemV2<'a> {
+    /// Parse the request, if the status type is `OCSP` or `OCSPMulti`
+    ///
+    /// Return `None` for other status types, or if the request is invalid.
+    pub fn ocsp_status_request(&self) -> Option<OCSPStatusRequest<'a>> {
+        match self.status_type {
+            CertificateStatusType::OCSP | CertificateStatusType::OCSPMulti => {
+                parse_ocsp_status_request(self.request)
+                    .ok()
+                    .map(|(_, req)| req)
+            }
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct OidFilter<'a> {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "hex"))]
     pub cert_ext_oid: &'a [u8],
+    #[cfg_attr(feature = "serde", serde(serialize_with = "hex"))]
     pub cert_ext_val: &'a [u8],
 }
 
@@ -226,16 +689,16 @@ pub struct OidFilter<'a> {
 // } NameType;
 //
 // opaque HostName<1..2^16-1>;
-pub fn parse_tls_extension_sni_hostname(i: &[u8]) -> IResult<&[u8], (SNIType, &[u8])> {
+pub fn parse_tls_extension_sni_hostname(i: &[u8]) -> IResult<&[u8], ServerName<'_>> {
     let (i, t) = SNIType::parse(i)?;
     let (i, v) = length_data(be_u16)(i)?;
-    Ok((i, (t, v)))
+    Ok((i, ServerName::new(t, v)))
 }
 
 // struct {
 //     ServerName server_name_list<1..2^16-1>
 // } ServerNameList;
-pub fn parse_tls_extension_sni_content(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+pub fn parse_tls_extension_sni_content(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     if i.is_empty() {
         // special case: SNI extension in server can be empty
         return Ok((i, TlsExtension::SNI(Vec::new())));
@@ -248,18 +711,25 @@ pub fn parse_tls_extension_sni_content(i: &[u8]) -> IResult<&[u8], TlsExtension>
     Ok((i, TlsExtension::SNI(v)))
 }
 
-pub fn parse_tls_extension_sni(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+pub fn parse_tls_extension_sni(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, _) = tag([0x00, 0x00])(i)?;
     map_parser(length_data(be_u16), parse_tls_extension_sni_content)(i)
 }
 
 /// Max fragment length [RFC6066]
-pub fn parse_tls_extension_max_fragment_length_content(i: &[u8]) -> IResult<&[u8], TlsExtension> {
-    map(be_u8, TlsExtension::MaxFragmentLength)(i)
+///
+/// The extension data must be exactly 1 byte.
+pub fn parse_tls_extension_max_fragment_length_content(
+    i: &[u8],
+) -> IResult<&[u8], TlsExtension<'_>> {
+    if i.len() != 1 {
+        return Err(Err::Error(make_error(i, ErrorKind::Verify)));
+    }
+    map(MaxFragmentLength::parse, TlsExtension::MaxFragmentLength)(i)
 }
 
 /// Max fragment length [RFC6066]
-pub fn parse_tls_extension_max_fragment_length(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+pub fn parse_tls_extension_max_fragment_length(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, _) = tag([0x00, 0x01])(i)?;
     map_parser(
         length_data(be_u16),
@@ -267,11 +737,62 @@ pub fn parse_tls_extension_max_fragment_length(i: &[u8]) -> IResult<&[u8], TlsEx
     )(i)
 }
 
+/// Truncated HMAC [RFC6066]
+///
+/// The extension data must be empty.
+fn parse_tls_extension_truncated_hmac_content(
+    i: &[u8],
+    ext_len: u16,
+) -> IResult<&[u8], TlsExtension<'_>> {
+    if ext_len != 0 {
+        return Err(Err::Error(make_error(i, ErrorKind::Verify)));
+    }
+    Ok((i, TlsExtension::TruncatedHmac))
+}
+
+/// User Mapping, defined in [RFC4681]
+///
+/// In a ClientHello, the content is a non-empty list of 1-byte `UserMappingType` values, whose
+/// length must match the extension length. The server acknowledges the extension with an empty
+/// content, returned as an empty list.
+pub fn parse_tls_extension_user_mapping_content(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
+    if i.is_empty() {
+        return Ok((i, TlsExtension::UserMapping(Vec::new())));
+    }
+    all_consuming(map(
+        verify(length_data(be_u8), |d: &[u8]| !d.is_empty()),
+        |d: &[u8]| TlsExtension::UserMapping(d.to_vec()),
+    ))(i)
+}
+
+// AuthzDataFormat authz_format_list<1..2^8-1>;
+fn parse_authz_formats(i: &[u8]) -> IResult<&[u8], Vec<AuthzDataFormat>> {
+    all_consuming(map_parser(
+        verify(length_data(be_u8), |d: &[u8]| !d.is_empty()),
+        many1(complete(AuthzDataFormat::parse)),
+    ))(i)
+}
+
+/// Client Authorization, defined in [RFC5878] section 3
+///
+/// The content is the same for ClientHello and ServerHello: a non-empty list of formats, whose
+/// length must match the extension length.
+pub fn parse_tls_extension_client_authz_content(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
+    map(parse_authz_formats, TlsExtension::ClientAuthz)(i)
+}
+
+/// Server Authorization, defined in [RFC5878] section 3
+///
+/// See `parse_tls_extension_client_authz_content`.
+pub fn parse_tls_extension_server_authz_content(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
+    map(parse_authz_formats, TlsExtension::ServerAuthz)(i)
+}
+
 /// Status Request [RFC6066]
 fn parse_tls_extension_status_request_content(
     i: &[u8],
     ext_len: u16,
-) -> IResult<&[u8], TlsExtension> {
+) -> IResult<&[u8], TlsExtension<'_>> {
     match ext_len {
         0 => Ok((i, TlsExtension::StatusRequest(None))),
         _ => {
@@ -285,7 +806,143 @@ fn parse_tls_extension_status_request_content(
     }
 }
 
-pub fn parse_tls_extension_status_request(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+// struct {
+//     select(ClientOrServerExtension) {
+//         case client:
+//           CertificateType client_certificate_types<1..2^8-1>;
+//         case server:
+//           CertificateType client_certificate_type;
+//     }
+// } ClientCertTypeExtension;
+//
+// (same structure for ServerCertTypeExtension)
+fn parse_certificate_types(i: &[u8]) -> IResult<&[u8], Vec<CertificateType>> {
+    all_consuming(map_parser(
+        verify(length_data(be_u8), |d: &[u8]| !d.is_empty()),
+        many1(complete(CertificateType::parse)),
+    ))(i)
+}
+
+fn parse_certificate_type_selected(i: &[u8]) -> IResult<&[u8], Vec<CertificateType>> {
+    all_consuming(map(CertificateType::parse, |t| vec![t]))(i)
+}
+
+/// Client Certificate Type [RFC7250], as sent by clients (list of supported types)
+pub fn parse_tls_extension_client_certificate_type_client_content(
+    i: &[u8],
+) -> IResult<&[u8], TlsExtension<'_>> {
+    map(parse_certificate_types, TlsExtension::ClientCertificateType)(i)
+}
+
+/// Client Certificate Type [RFC7250], as sent by servers (selected type)
+pub fn parse_tls_extension_client_certificate_type_server_content(
+    i: &[u8],
+) -> IResult<&[u8], TlsExtension<'_>> {
+    map(
+        parse_certificate_type_selected,
+        TlsExtension::ClientCertificateType,
+    )(i)
+}
+
+/// Server Certificate Type [RFC7250], as sent by clients (list of supported types)
+pub fn parse_tls_extension_server_certificate_type_client_content(
+    i: &[u8],
+) -> IResult<&[u8], TlsExtension<'_>> {
+    map(parse_certificate_types, TlsExtension::ServerCertificateType)(i)
+}
+
+/// Server Certificate Type [RFC7250], as sent by servers (selected type)
+pub fn parse_tls_extension_server_certificate_type_server_content(
+    i: &[u8],
+) -> IResult<&[u8], TlsExtension<'_>> {
+    map(
+        parse_certificate_type_selected,
+        TlsExtension::ServerCertificateType,
+    )(i)
+}
+
+// The content depends on the message type. When it is not known, the length is used to
+// distinguish the cases: a list has at least 2 bytes (length and one type), while a selected
+// type has exactly 1 byte.
+fn parse_tls_extension_client_certificate_type_content(
+    i: &[u8],
+) -> IResult<&[u8], TlsExtension<'_>> {
+    if i.len() == 1 {
+        parse_tls_extension_client_certificate_type_server_content(i)
+    } else {
+        parse_tls_extension_client_certificate_type_client_content(i)
+    }
+}
+
+fn parse_tls_extension_server_certificate_type_content(
+    i: &[u8],
+) -> IResult<&[u8], TlsExtension<'_>> {
+    if i.len() == 1 {
+        parse_tls_extension_server_certificate_type_server_content(i)
+    } else {
+        parse_tls_extension_server_certificate_type_client_content(i)
+    }
+}
+
+// struct {
+//     CertificateStatusType status_type;
+//     uint16 request_length; /* Length of request field in bytes */
+//     select (status_type) {
+//         case ocsp: OCSPStatusRequest;
+//         case ocsp_multi: OCSPStatusRequest;
+//     } request;
+// } CertificateStatusRequestItemV2;
+fn parse_certificate_status_request_item_v2(
+    i: &[u8],
+) -> IResult<&[u8], CertificateStatusRequestItemV2<'_>> {
+    let (i, status_type) = CertificateStatusType::parse(i)?;
+    let (i, request) = length_data(be_u16)(i)?;
+    let item = CertificateStatusRequestItemV2 {
+        status_type,
+        request,
+    };
+    Ok((i, item))
+}
+
+// struct {
+//     CertificateStatusRequestItemV2
+//                      certificate_status_req_list<1..2^16-1>;
+// } CertificateStatusRequestListV2;
+/// Multiple Certificate Status Request [RFC6961]
+///
+/// The extension is empty in ServerHello messages. Otherwise, the list must not be empty, and
+/// must fill the extension data.
+pub fn parse_tls_extension_status_request_v2_content(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
+    if i.is_empty() {
+        return Ok((i, TlsExtension::StatusRequestV2(None)));
+    }
+    let (i, v) = all_consuming(map_parser(
+        length_data(be_u16),
+        all_consuming(many1(complete(parse_certificate_status_request_item_v2))),
+    ))(i)?;
+    Ok((i, TlsExtension::StatusRequestV2(Some(v))))
+}
+
+// struct {
+//     ResponderID responder_id_list<0..2^16-1>;
+//     Extensions  request_extensions;
+// } OCSPStatusRequest;
+//
+// opaque ResponderID<1..2^16-1>;
+// opaque Extensions<0..2^16-1>;
+/// Parse the request of a Status Request extension, for the OCSP status type [RFC6066]
+pub fn parse_ocsp_status_request(i: &[u8]) -> IResult<&[u8], OCSPStatusRequest<'_>> {
+    let (i, responder_id_list) =
+        map_parser(length_data(be_u16), many0(complete(length_data(be_u16))))(i)?;
+    let (i, request_extensions) = length_data(be_u16)(i)?;
+    let request = OCSPStatusRequest {
+        responder_id_list,
+        request_extensions,
+    };
+    Ok((i, request))
+}
+
+pub fn parse_tls_extension_status_request(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, _) = tag([0x00, 0x05])(i)?;
     let (i, ext_len) = be_u16(i)?;
     map_parser(take(ext_len), move |d| {
@@ -294,14 +951,14 @@ pub fn parse_tls_extension_status_request(i: &[u8]) -> IResult<&[u8], TlsExtensi
 }
 
 // defined in rfc8422
-pub fn parse_tls_extension_elliptic_curves_content(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+pub fn parse_tls_extension_elliptic_curves_content(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     map_parser(
         length_data(be_u16),
         map(parse_named_groups, TlsExtension::EllipticCurves),
     )(i)
 }
 
-pub fn parse_tls_extension_elliptic_curves(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+pub fn parse_tls_extension_elliptic_curves(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, _) = tag([0x00, 0x0a])(i)?;
     map_parser(
         length_data(be_u16),
@@ -309,12 +966,19 @@ pub fn parse_tls_extension_elliptic_curves(i: &[u8]) -> IResult<&[u8], TlsExtens
     )(i)
 }
 
-pub fn parse_tls_extension_ec_point_formats_content(i: &[u8]) -> IResult<&[u8], TlsExtension> {
-    map(length_data(be_u8), TlsExtension::EcPointFormats)(i)
+/// Parse 'EC Point Formats' extension content
+///
+/// The list length must match the extension length.
+pub fn parse_tls_extension_ec_point_formats_content(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
+    let (i, v) = all_consuming(map_parser(
+        verify(length_data(be_u8), |d: &[u8]| !d.is_empty()),
+        many0(complete(EcPointFormat::parse)),
+    ))(i)?;
+    Ok((i, TlsExtension::EcPointFormats(v)))
 }
 
-pub fn parse_tls_extension_ec_point_formats(i: &[u8]) -> IResult<&[u8], TlsExtension> {
-    let (i, _) = tag([0x00, 0x0a])(i)?;
+pub fn parse_tls_extension_ec_point_formats(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
+    let (i, _) = tag([0x00, 0x0b])(i)?;
     map_parser(
         length_data(be_u16),
         parse_tls_extension_ec_point_formats_content,
@@ -322,12 +986,47 @@ pub fn parse_tls_extension_ec_point_formats(i: &[u8]) -> IResult<&[u8], TlsExten
 }
 
 /// Parse 'Signature Algorithms' extension (rfc8446, TLS 1.3 only)
-pub fn parse_tls_extension_signature_algorithms_content(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+pub fn parse_tls_extension_signature_algorithms_content(
+    i: &[u8],
+) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, l) = map_parser(length_data(be_u16), many0(complete(be_u16)))(i)?;
     Ok((i, TlsExtension::SignatureAlgorithms(l))) // XXX SignatureAlgorithms or SignatureScheme
 }
 
-pub fn parse_tls_extension_signature_algorithms(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+/// Parse 'Signature Algorithms Cert' extension (rfc8446, TLS 1.3 only)
+///
+/// The content is the same as the 'Signature Algorithms' extension, but applies to signatures
+/// in certificates. The list must be non-empty, and its length must match the extension length.
+pub fn parse_tls_extension_signature_algorithms_cert_content(
+    i: &[u8],
+) -> IResult<&[u8], TlsExtension<'_>> {
+    let (i, l) = all_consuming(map_parser(
+        verify(length_data(be_u16), |d: &[u8]| {
+            d.len() >= 2 && d.len() % 2 == 0
+        }),
+        all_consuming(many0(complete(be_u16))),
+    ))(i)?;
+    Ok((i, TlsExtension::SignatureAlgorithmsCert(l)))
+}
+
+/// Parse 'Delegated Credentials' extension, as sent by clients ([RFC9345])
+///
+/// The client sends the list of signature schemes it accepts for delegated credentials. In a
+/// CertificateEntry, the extension contains the credential itself, and is not parsed. The list
+/// length must match the extension length.
+pub fn parse_tls_extension_delegated_credentials_content(
+    i: &[u8],
+) -> IResult<&[u8], TlsExtension<'_>> {
+    let (i, l) = all_consuming(map_parser(
+        verify(length_data(be_u16), |d: &[u8]| {
+            d.len() >= 2 && d.len() % 2 == 0
+        }),
+        many0(complete(be_u16)),
+    ))(i)?;
+    Ok((i, TlsExtension::DelegatedCredentials(l)))
+}
+
+pub fn parse_tls_extension_signature_algorithms(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, _) = tag([0x00, 13])(i)?;
     map_parser(
         length_data(be_u16),
@@ -336,12 +1035,15 @@ pub fn parse_tls_extension_signature_algorithms(i: &[u8]) -> IResult<&[u8], TlsE
 }
 
 // rfc6520
-pub fn parse_tls_extension_heartbeat_content(i: &[u8]) -> IResult<&[u8], TlsExtension> {
-    map(be_u8, TlsExtension::Heartbeat)(i)
+pub fn parse_tls_extension_heartbeat_content(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
+    if i.len() != 1 {
+        return Err(Err::Error(make_error(i, ErrorKind::Verify)));
+    }
+    map(HeartbeatMode::parse, TlsExtension::Heartbeat)(i)
 }
 
-pub fn parse_tls_extension_heartbeat(i: &[u8]) -> IResult<&[u8], TlsExtension> {
-    let (i, _) = tag([0x00, 0x0d])(i)?;
+pub fn parse_tls_extension_heartbeat(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
+    let (i, _) = tag([0x00, 0x0f])(i)?;
     let (i, ext_len) = verify(be_u16, |&n| n == 1)(i)?;
     map_parser(take(ext_len), parse_tls_extension_heartbeat_content)(i)
 }
@@ -351,31 +1053,85 @@ fn parse_protocol_name(i: &[u8]) -> IResult<&[u8], &[u8]> {
 }
 
 /// Defined in [RFC7301]
-pub fn parse_tls_extension_alpn_content(i: &[u8]) -> IResult<&[u8], TlsExtension> {
-    let (i, v) = map_parser(length_data(be_u16), many0(complete(parse_protocol_name)))(i)?;
+///
+/// Protocol names are kept in the advertised order, as raw bytes (they are not required to be
+/// valid UTF-8). Each name must be non-empty and fit in the list, and the list length must match
+/// the extension length.
+pub fn parse_tls_extension_alpn_content(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
+    let (i, v) = all_consuming(map_parser(
+        length_data(be_u16),
+        all_consuming(many0(complete(verify(
+            parse_protocol_name,
+            |p: &[u8]| !p.is_empty(),
+        )))),
+    ))(i)?;
     Ok((i, TlsExtension::ALPN(v)))
 }
 
+fn parse_alps_protocols(i: &[u8]) -> IResult<&[u8], Vec<&[u8]>> {
+    all_consuming(map_parser(
+        verify(length_data(be_u16), |d: &[u8]| !d.is_empty()),
+        all_consuming(many0(complete(verify(
+            parse_protocol_name,
+            |p: &[u8]| !p.is_empty(),
+        )))),
+    ))(i)
+}
+
+/// Parse 'Application-Layer Protocol Settings' extension, as sent by clients
+///
+/// Defined in [draft-vvv-tls-alps](https://datatracker.ietf.org/doc/html/draft-vvv-tls-alps).
+/// The client sends the list of ALPN protocols for which it supports settings. The list length
+/// must match the extension length.
+pub fn parse_tls_extension_application_settings_content(
+    i: &[u8],
+) -> IResult<&[u8], TlsExtension<'_>> {
+    map(parse_alps_protocols, TlsExtension::ApplicationSettings)(i)
+}
+
+/// Parse 'Application-Layer Protocol Settings' extension, using the previous code point (17513)
+///
+/// See `parse_tls_extension_application_settings_content`.
+pub fn parse_tls_extension_application_settings_old_content(
+    i: &[u8],
+) -> IResult<&[u8], TlsExtension<'_>> {
+    map(parse_alps_protocols, TlsExtension::ApplicationSettingsOld)(i)
+}
+
 /// Defined in [RFC7685]
-fn parse_tls_extension_padding_content(i: &[u8], ext_len: u16) -> IResult<&[u8], TlsExtension> {
+fn parse_tls_extension_padding_content(i: &[u8], ext_len: u16) -> IResult<&[u8], TlsExtension<'_>> {
     map(take(ext_len), TlsExtension::Padding)(i)
 }
 
-/// Defined in [RFC6962]
+/// Defined in [RFC6962] section 3.3
+///
+/// The extension is empty when sent by clients. Otherwise, it contains a non-empty list of
+/// serialized SCTs, each one prefixed by its length. The list length must match the extension
+/// length.
+///
+/// SCTs are not decoded: use `parse_ct_signed_certificate_timestamp_list` on the extension data
+/// to decode them.
 pub fn parse_tls_extension_signed_certificate_timestamp_content(
     i: &[u8],
-) -> IResult<&[u8], TlsExtension> {
-    map(
-        opt(complete(length_data(be_u16))),
-        TlsExtension::SignedCertificateTimestamp,
-    )(i)
+) -> IResult<&[u8], TlsExtension<'_>> {
+    if i.is_empty() {
+        return Ok((i, TlsExtension::SignedCertificateTimestamp(None)));
+    }
+    let (i, v) = all_consuming(map_parser(
+        complete(length_data(be_u16)),
+        all_consuming(many1(complete(verify(
+            length_data(be_u16),
+            |d: &[u8]| !d.is_empty(),
+        )))),
+    ))(i)?;
+    Ok((i, TlsExtension::SignedCertificateTimestamp(Some(v))))
 }
 
 /// Encrypt-then-MAC is defined in [RFC7366]
 fn parse_tls_extension_encrypt_then_mac_content(
     i: &[u8],
     ext_len: u16,
-) -> IResult<&[u8], TlsExtension> {
+) -> IResult<&[u8], TlsExtension<'_>> {
     if ext_len != 0 {
         return Err(Err::Error(make_error(i, ErrorKind::Verify)));
     }
@@ -383,7 +1139,7 @@ fn parse_tls_extension_encrypt_then_mac_content(
 }
 
 /// Encrypt-then-MAC is defined in [RFC7366]
-pub fn parse_tls_extension_encrypt_then_mac(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+pub fn parse_tls_extension_encrypt_then_mac(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, _) = tag([0x00, 0x16])(i)?;
     let (i, ext_len) = be_u16(i)?;
     map_parser(take(ext_len), move |d| {
@@ -395,7 +1151,7 @@ pub fn parse_tls_extension_encrypt_then_mac(i: &[u8]) -> IResult<&[u8], TlsExten
 fn parse_tls_extension_extended_master_secret_content(
     i: &[u8],
     ext_len: u16,
-) -> IResult<&[u8], TlsExtension> {
+) -> IResult<&[u8], TlsExtension<'_>> {
     if ext_len != 0 {
         return Err(Err::Error(make_error(i, ErrorKind::Verify)));
     }
@@ -403,7 +1159,7 @@ fn parse_tls_extension_extended_master_secret_content(
 }
 
 /// Extended Master Secret is defined in [RFC7627]
-pub fn parse_tls_extension_extended_master_secret(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+pub fn parse_tls_extension_extended_master_secret(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, _) = tag([0x00, 0x17])(i)?;
     let (i, ext_len) = be_u16(i)?;
     map_parser(take(ext_len), move |d| {
@@ -411,19 +1167,68 @@ pub fn parse_tls_extension_extended_master_secret(i: &[u8]) -> IResult<&[u8], Tl
     })(i)
 }
 
-/// Extended Record Size Limit is defined in [RFC7627]
-fn parse_tls_extension_record_size_limit(i: &[u8]) -> IResult<&[u8], TlsExtension> {
-    map(be_u16, TlsExtension::RecordSizeLimit)(i)
+/// Record Size Limit is defined in [RFC8449]
+///
+/// The extension data must be exactly 2 bytes, and the limit must be at least 64.
+fn parse_tls_extension_record_size_limit_content(
+    i: &[u8],
+    ext_len: u16,
+) -> IResult<&[u8], TlsExtension<'_>> {
+    if ext_len != 2 {
+        return Err(Err::Error(make_error(i, ErrorKind::Verify)));
+    }
+    map(verify(be_u16, |&v| v >= 64), TlsExtension::RecordSizeLimit)(i)
+}
+
+/// Record Size Limit is defined in [RFC8449]
+pub fn parse_tls_extension_record_size_limit(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
+    let (i, _) = tag([0x00, 0x1c])(i)?;
+    let (i, ext_len) = be_u16(i)?;
+    map_parser(take(ext_len), move |d| {
+        parse_tls_extension_record_size_limit_content(d, ext_len)
+    })(i)
+}
+
+/// Token Binding is defined in [RFC8472]
+///
+/// The client sends the list of supported key parameters, the server selects one. The length of
+/// the key parameters list must match the extension length.
+pub fn parse_tls_extension_token_binding_content(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
+    let (i, major) = be_u8(i)?;
+    let (i, minor) = be_u8(i)?;
+    let (i, key_params) = all_consuming(map(
+        verify(length_data(be_u8), |d: &[u8]| !d.is_empty()),
+        |d: &[u8]| d.to_vec(),
+    ))(i)?;
+    let ext = TlsExtension::TokenBinding {
+        major,
+        minor,
+        key_params,
+    };
+    Ok((i, ext))
 }
 
 fn parse_tls_extension_session_ticket_content(
     i: &[u8],
     ext_len: u16,
-) -> IResult<&[u8], TlsExtension> {
+) -> IResult<&[u8], TlsExtension<'_>> {
     map(take(ext_len), TlsExtension::SessionTicket)(i)
 }
 
-pub fn parse_tls_extension_session_ticket(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+/// Session Ticket, as sent by servers [RFC5077]
+///
+/// The extension data must be empty.
+fn parse_tls_extension_session_ticket_server_content(
+    i: &[u8],
+    ext_len: u16,
+) -> IResult<&[u8], TlsExtension<'_>> {
+    if ext_len != 0 {
+        return Err(Err::Error(make_error(i, ErrorKind::Verify)));
+    }
+    Ok((i, TlsExtension::SessionTicket(i)))
+}
+
+pub fn parse_tls_extension_session_ticket(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, _) = tag([0x00, 0x23])(i)?;
     let (i, ext_len) = be_u16(i)?;
     map_parser(take(ext_len), move |d| {
@@ -434,15 +1239,18 @@ pub fn parse_tls_extension_session_ticket(i: &[u8]) -> IResult<&[u8], TlsExtensi
 fn parse_tls_extension_key_share_old_content(
     i: &[u8],
     ext_len: u16,
-) -> IResult<&[u8], TlsExtension> {
+) -> IResult<&[u8], TlsExtension<'_>> {
     map(take(ext_len), TlsExtension::KeyShareOld)(i)
 }
 
-fn parse_tls_extension_key_share_content(i: &[u8], ext_len: u16) -> IResult<&[u8], TlsExtension> {
+fn parse_tls_extension_key_share_content(
+    i: &[u8],
+    ext_len: u16,
+) -> IResult<&[u8], TlsExtension<'_>> {
     map(take(ext_len), TlsExtension::KeyShare)(i)
 }
 
-pub fn parse_tls_extension_key_share(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+pub fn parse_tls_extension_key_share(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, _) = tag([0x00, 0x33])(i)?;
     let (i, ext_len) = be_u16(i)?;
     map_parser(take(ext_len), move |d| {
@@ -450,14 +1258,108 @@ pub fn parse_tls_extension_key_share(i: &[u8]) -> IResult<&[u8], TlsExtension> {
     })(i)
 }
 
+// struct {
+//     NamedGroup group;
+//     opaque key_exchange<1..2^16-1>;
+// } KeyShareEntry;
+/// Parse a single Key Share entry [RFC8446]
+pub fn parse_key_share_entry(i: &[u8]) -> IResult<&[u8], KeyShareEntry<'_>> {
+    let (i, group) = NamedGroup::parse_be(i)?;
+    let (i, kx) = verify(length_data(be_u16), |d: &[u8]| !d.is_empty())(i)?;
+    Ok((i, KeyShareEntry { group, kx }))
+}
+
+/// Parse the content of a Key Share extension, in a ClientHello message [RFC8446]
+///
+/// The content is a list of entries, possibly empty.
+pub fn parse_key_share_client_hello(i: &[u8]) -> IResult<&[u8], Vec<KeyShareEntry<'_>>> {
+    map_parser(
+        length_data(be_u16),
+        all_consuming(many0(complete(parse_key_share_entry))),
+    )(i)
+}
+
+/// Parse the content of a Key Share extension, in a ServerHello message [RFC8446]
+///
+/// The content is the entry selected by the server.
+pub fn parse_key_share_server_hello(i: &[u8]) -> IResult<&[u8], KeyShareEntry<'_>> {
+    parse_key_share_entry(i)
+}
+
+/// Parse the content of a Key Share extension, in a HelloRetryRequest message [RFC8446]
+///
+/// The content is the group selected by the server, without key exchange data.
+pub fn parse_key_share_hello_retry_request(i: &[u8]) -> IResult<&[u8], NamedGroup> {
+    NamedGroup::parse_be(i)
+}
+
 fn parse_tls_extension_pre_shared_key_content(
     i: &[u8],
     ext_len: u16,
-) -> IResult<&[u8], TlsExtension> {
+) -> IResult<&[u8], TlsExtension<'_>> {
     map(take(ext_len), TlsExtension::PreSharedKey)(i)
 }
 
-pub fn parse_tls_extension_pre_shared_key(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+// struct {
+//     opaque identity<1..2^16-1>;
+//     uint32 obfuscated_ticket_age;
+// } PskIdentity;
+fn parse_psk_identity(i: &[u8]) -> IResult<&[u8], PskIdentity<'_>> {
+    let (i, identity) = verify(length_data(be_u16), |d: &[u8]| !d.is_empty())(i)?;
+    let (i, obfuscated_ticket_age) = be_u32(i)?;
+    let psk_identity = PskIdentity {
+        identity,
+        obfuscated_ticket_age,
+    };
+    Ok((i, psk_identity))
+}
+
+// opaque PskBinderEntry<32..255>;
+//
+// struct {
+//     PskIdentity identities<7..2^16-1>;
+//     PskBinderEntry binders<33..2^16-1>;
+// } OfferedPsks;
+/// Parse the content of a Pre-Shared Key extension, sent by clients [RFC8446]
+///
+/// There must be exactly one binder per identity (RFC8446 section 4.2.11), and the input must
+/// not contain data after the binders.
+pub fn parse_offered_psks(i: &[u8]) -> IResult<&[u8], OfferedPsks<'_>> {
+    let (i, identities) = map_parser(
+        length_data(be_u16),
+        all_consuming(many1(complete(parse_psk_identity))),
+    )(i)?;
+    let (i, binders) = all_consuming(map_parser(
+        length_data(be_u16),
+        all_consuming(many1(complete(verify(length_data(be_u8), |d: &[u8]| {
+            d.len() >= 32
+        })))),
+    ))(i)?;
+    if identities.len() != binders.len() {
+        return Err(Err::Error(make_error(i, ErrorKind::Verify)));
+    }
+    let offered = OfferedPsks {
+        identities,
+        binders,
+    };
+    Ok((i, offered))
+}
+
+fn parse_tls_extension_pre_shared_key_client_content(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
+    map(parse_offered_psks, TlsExtension::PreSharedKeyOffered)(i)
+}
+
+fn parse_tls_extension_pre_shared_key_server_content(
+    i: &[u8],
+    ext_len: u16,
+) -> IResult<&[u8], TlsExtension<'_>> {
+    if ext_len != 2 {
+        return Err(Err::Error(make_error(i, ErrorKind::Verify)));
+    }
+    map(be_u16, TlsExtension::PreSharedKeySelected)(i)
+}
+
+pub fn parse_tls_extension_pre_shared_key(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, _) = tag([0x00, 0x28])(i)?;
     let (i, ext_len) = be_u16(i)?;
     map_parser(take(ext_len), move |d| {
@@ -465,11 +1367,21 @@ pub fn parse_tls_extension_pre_shared_key(i: &[u8]) -> IResult<&[u8], TlsExtensi
     })(i)
 }
 
-fn parse_tls_extension_early_data_content(i: &[u8], ext_len: u16) -> IResult<&[u8], TlsExtension> {
+/// Early Data extension [RFC8446]
+///
+/// The extension is empty, except in NewSessionTicket messages where it contains the maximum
+/// early data size (4 bytes).
+fn parse_tls_extension_early_data_content(
+    i: &[u8],
+    ext_len: u16,
+) -> IResult<&[u8], TlsExtension<'_>> {
+    if ext_len != 0 && ext_len != 4 {
+        return Err(Err::Error(make_error(i, ErrorKind::Verify)));
+    }
     map(cond(ext_len > 0, be_u32), TlsExtension::EarlyData)(i)
 }
 
-pub fn parse_tls_extension_early_data(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+pub fn parse_tls_extension_early_data(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, _) = tag([0x00, 0x2a])(i)?;
     let (i, ext_len) = be_u16(i)?;
     map_parser(take(ext_len), move |d| {
@@ -487,12 +1399,38 @@ pub fn parse_tls_extension_early_data(i: &[u8]) -> IResult<&[u8], TlsExtension>
 //                    ProtocolVersion selected_version;
 //           };
 //       } SupportedVersions;
-// XXX the content depends on the current message type
-// XXX first case has length 1 + 2*n, while the second case has length 2
+//
+// The content depends on the current message type: the first case has length 1 + 2*n, while
+// the second case has length 2. When the message type is not known, the length is used to
+// guess the layout.
+//
+// In the first case, the list length must match the extension length.
+pub(crate) fn parse_tls_extension_supported_versions_client_content(
+    i: &[u8],
+) -> IResult<&[u8], TlsExtension<'_>> {
+    let (i, l) = all_consuming(map_parser(
+        verify(length_data(be_u8), |d: &[u8]| d.len() >= 2),
+        parse_tls_versions,
+    ))(i)?;
+    Ok((i, TlsExtension::SupportedVersions(l)))
+}
+
+fn parse_tls_extension_supported_versions_server_content(
+    i: &[u8],
+    ext_len: u16,
+) -> IResult<&[u8], TlsExtension<'_>> {
+    if ext_len != 2 {
+        return Err(Err::Error(make_error(i, ErrorKind::Verify)));
+    }
+    map(be_u16, |x| {
+        TlsExtension::SupportedVersions(vec![TlsVersion(x)])
+    })(i)
+}
+
 fn parse_tls_extension_supported_versions_content(
     i: &[u8],
     ext_len: u16,
-) -> IResult<&[u8], TlsExtension> {
+) -> IResult<&[u8], TlsExtension<'_>> {
     if ext_len == 2 {
         map(be_u16, |x| {
             TlsExtension::SupportedVersions(vec![TlsVersion(x)])
@@ -507,7 +1445,7 @@ fn parse_tls_extension_supported_versions_content(
     }
 }
 
-pub fn parse_tls_extension_supported_versions(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+pub fn parse_tls_extension_supported_versions(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, _) = tag([0x00, 0x2b])(i)?;
     let (i, ext_len) = be_u16(i)?;
     map_parser(take(ext_len), move |d| {
@@ -515,26 +1453,34 @@ pub fn parse_tls_extension_supported_versions(i: &[u8]) -> IResult<&[u8], TlsExt
     })(i)
 }
 
-fn parse_tls_extension_cookie_content(i: &[u8], ext_len: u16) -> IResult<&[u8], TlsExtension> {
-    map(take(ext_len), TlsExtension::Cookie)(i)
+/// Cookie is defined in [RFC8446] section 4.2.2
+///
+/// The extension data is the length-prefixed cookie, which must not be empty. The cookie length
+/// must match the extension length.
+fn parse_tls_extension_cookie_content(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
+    map(
+        all_consuming(verify(complete(length_data(be_u16)), |c: &[u8]| {
+            !c.is_empty()
+        })),
+        TlsExtension::Cookie,
+    )(i)
 }
 
-pub fn parse_tls_extension_cookie(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+/// Cookie is defined in [RFC8446] section 4.2.2
+pub fn parse_tls_extension_cookie(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, _) = tag([0x00, 0x2c])(i)?;
-    let (i, ext_len) = be_u16(i)?;
-    map_parser(take(ext_len), move |d| {
-        parse_tls_extension_cookie_content(d, ext_len)
-    })(i)
+    map_parser(length_data(be_u16), parse_tls_extension_cookie_content)(i)
 }
 
 pub fn parse_tls_extension_psk_key_exchange_modes_content(
     i: &[u8],
-) -> IResult<&[u8], TlsExtension> {
+) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, v) = length_data(be_u8)(i)?;
-    Ok((i, TlsExtension::PskExchangeModes(v.to_vec())))
+    let v = v.iter().map(|&m| PskKeyExchangeMode(m)).collect();
+    Ok((i, TlsExtension::PskExchangeModes(v)))
 }
 
-pub fn parse_tls_extension_psk_key_exchange_modes(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+pub fn parse_tls_extension_psk_key_exchange_modes(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, _) = tag([0x00, 0x2d])(i)?;
     let (i, ext_len) = be_u16(i)?;
     map_parser(
@@ -544,20 +1490,46 @@ pub fn parse_tls_extension_psk_key_exchange_modes(i: &[u8]) -> IResult<&[u8], Tl
 }
 
 /// Defined in RFC-draft-agl-tls-nextprotoneg-03. Deprecated in favour of ALPN.
-fn parse_tls_extension_npn_content(i: &[u8], ext_len: u16) -> IResult<&[u8], TlsExtension> {
+fn parse_tls_extension_npn_content(i: &[u8], ext_len: u16) -> IResult<&[u8], TlsExtension<'_>> {
     if ext_len != 0 {
         return Err(Err::Error(make_error(i, ErrorKind::Verify)));
     }
     Ok((i, TlsExtension::NextProtocolNegotiation))
 }
 
+/// Next Protocol Negotiation, as sent by servers
+///
+/// The extension contains an optional list of protocols, each protocol being a non-empty,
+/// 8-bit length-prefixed string. If the extension is empty, return `NextProtocolNegotiation`.
+pub fn parse_tls_extension_npn_server_content(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
+    if i.is_empty() {
+        return Ok((i, TlsExtension::NextProtocolNegotiation));
+    }
+    let (i, v) = all_consuming(many1(complete(verify(
+        parse_protocol_name,
+        |p: &[u8]| !p.is_empty(),
+    ))))(i)?;
+    Ok((i, TlsExtension::NextProtocolNegotiationServer(v)))
+}
+
+/// Connection ID, defined in [RFC9146] section 3
+///
+/// The length of the `cid` field must match the extension length.
+pub fn parse_tls_extension_connection_id_content(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
+    all_consuming(map(length_data(be_u8), TlsExtension::ConnectionId))(i)
+}
+
 /// Renegotiation Info, defined in [RFC5746]
-pub fn parse_tls_extension_renegotiation_info_content(i: &[u8]) -> IResult<&[u8], TlsExtension> {
-    map(length_data(be_u8), TlsExtension::RenegotiationInfo)(i)
+///
+/// The length of the `renegotiated_connection` field must match the extension length.
+pub fn parse_tls_extension_renegotiation_info_content(
+    i: &[u8],
+) -> IResult<&[u8], TlsExtension<'_>> {
+    all_consuming(map(length_data(be_u8), TlsExtension::RenegotiationInfo))(i)
 }
 
 /// Encrypted Server Name, defined in [draft-ietf-tls-esni]
-pub fn parse_tls_extension_encrypted_server_name(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+pub fn parse_tls_extension_encrypted_server_name(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, ciphersuite) = map(be_u16, TlsCipherSuiteID)(i)?;
     let (i, group) = NamedGroup::parse(i)?;
     let (i, key_share) = length_data(be_u16)(i)?;
@@ -573,8 +1545,74 @@ pub fn parse_tls_extension_encrypted_server_name(i: &[u8]) -> IResult<&[u8], Tls
     Ok((i, esn))
 }
 
-fn parse_tls_oid_filter(i: &[u8]) -> IResult<&[u8], OidFilter> {
-    let (i, cert_ext_oid) = length_data(be_u8)(i)?;
+// enum { outer(0), inner(1) } ECHClientHelloType;
+//
+// struct {
+//    ECHClientHelloType type;
+//    select (ECHClientHello.type) {
+//        case outer:
+//            HpkeSymmetricCipherSuite cipher_suite;
+//            uint8 config_id;
+//            opaque enc<0..2^16-1>;
+//            opaque payload<1..2^16-1>;
+//        case inner:
+//            Empty;
+//    };
+// } ECHClientHello;
+/// Encrypted Client Hello, defined in [draft-ietf-tls-esni]
+///
+/// The content must not contain data after the `payload` (outer form) or the type (inner form).
+pub fn parse_tls_extension_encrypted_client_hello_content(
+    i: &[u8],
+) -> IResult<&[u8], TlsExtension<'_>> {
+    let (i, ech_type) = ECHClientHelloType::parse(i)?;
+    let (i, ech) = match ech_type {
+        ECHClientHelloType::Outer => {
+            let (i, kdf_id) = be_u16(i)?;
+            let (i, aead_id) = be_u16(i)?;
+            let (i, config_id) = be_u8(i)?;
+            let (i, enc) = length_data(be_u16)(i)?;
+            let (i, payload) =
+                all_consuming(verify(length_data(be_u16), |p: &[u8]| !p.is_empty()))(i)?;
+            let ech = ECHClientHello::Outer {
+                kdf_id,
+                aead_id,
+                config_id,
+                enc,
+                payload,
+            };
+            (i, ech)
+        }
+        ECHClientHelloType::Inner if i.is_empty() => (i, ECHClientHello::Inner),
+        _ => return Err(Err::Error(make_error(i, ErrorKind::Verify))),
+    };
+    Ok((i, TlsExtension::EncryptedClientHello(ech)))
+}
+
+// opaque DistinguishedName<1..2^16-1>;
+//
+// struct {
+//     DistinguishedName authorities<3..2^16-1>;
+// } CertificateAuthoritiesExtension;
+/// Certificate Authorities, defined in [RFC8446] section 4.2.4
+///
+/// Each entry is a DER-encoded distinguished name. The list length must match the extension
+/// length.
+pub fn parse_tls_extension_certificate_authorities_content(
+    i: &[u8],
+) -> IResult<&[u8], TlsExtension<'_>> {
+    let (i, v) = all_consuming(map_parser(
+        verify(length_data(be_u16), |d: &[u8]| d.len() >= 3),
+        all_consuming(many0(complete(verify(
+            length_data(be_u16),
+            |dn: &[u8]| !dn.is_empty(),
+        )))),
+    ))(i)?;
+    Ok((i, TlsExtension::CertificateAuthorities(v)))
+}
+
+fn parse_tls_oid_filter(i: &[u8]) -> IResult<&[u8], OidFilter<'_>> {
+    let (i, cert_ext_oid) = verify(length_data(be_u8), |d: &[u8]| !d.is_empty())(i)?;
     let (i, cert_ext_val) = length_data(be_u16)(i)?;
     let filter = OidFilter {
         cert_ext_oid,
@@ -583,9 +1621,14 @@ fn parse_tls_oid_filter(i: &[u8]) -> IResult<&[u8], OidFilter> {
     Ok((i, filter))
 }
 
-/// Defined in TLS 1.3 draft 19
-fn parse_tls_extension_oid_filters(i: &[u8]) -> IResult<&[u8], TlsExtension> {
-    let (i, v) = map_parser(length_data(be_u16), many0(complete(parse_tls_oid_filter)))(i)?;
+/// Defined in TLS 1.3 draft 19, and [RFC8446] section 4.2.5
+///
+/// The OIDs must not be empty, and the filters must fill the extension data.
+fn parse_tls_extension_oid_filters(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
+    let (i, v) = all_consuming(map_parser(
+        length_data(be_u16),
+        all_consuming(many0(complete(parse_tls_oid_filter))),
+    ))(i)?;
     Ok((i, TlsExtension::OidFilters(v)))
 }
 
@@ -593,14 +1636,51 @@ fn parse_tls_extension_oid_filters(i: &[u8]) -> IResult<&[u8], TlsExtension> {
 fn parse_tls_extension_post_handshake_auth_content(
     i: &[u8],
     ext_len: u16,
-) -> IResult<&[u8], TlsExtension> {
+) -> IResult<&[u8], TlsExtension<'_>> {
     if ext_len != 0 {
         return Err(Err::Error(make_error(i, ErrorKind::Verify)));
     }
     Ok((i, TlsExtension::PostHandshakeAuth))
 }
 
-pub fn parse_tls_extension_unknown(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+/// Parse a QUIC variable-length integer, defined in [RFC9000] section 16
+///
+/// The 2 most significant bits of the first byte give the length of the encoding (1, 2, 4 or 8
+/// bytes).
+pub fn parse_quic_varint(i: &[u8]) -> IResult<&[u8], u64> {
+    let (_, first) = be_u8(i)?;
+    let len = 1usize << (first >> 6);
+    let (i, bytes) = take(len)(i)?;
+    let v = bytes[1..]
+        .iter()
+        .fold(u64::from(first & 0x3f), |acc, &b| (acc << 8) | u64::from(b));
+    Ok((i, v))
+}
+
+fn parse_quic_transport_parameter(i: &[u8]) -> IResult<&[u8], (u64, &[u8])> {
+    let (i, id) = parse_quic_varint(i)?;
+    let (i, len) = parse_quic_varint(i)?;
+    let len: usize = len
+        .try_into()
+        .map_err(|_| Err::Error(make_error(i, ErrorKind::TooLarge)))?;
+    let (i, value) = take(len)(i)?;
+    Ok((i, (id, value)))
+}
+
+/// QUIC Transport Parameters, defined in [RFC9001] section 8.2
+///
+/// Parameters IDs and lengths are encoded as QUIC variable-length integers. The format of
+/// the values depends on the parameter, see [RFC9000] section 18.
+pub fn parse_tls_extension_quic_transport_parameters_content(
+    i: &[u8],
+) -> IResult<&[u8], TlsExtension<'_>> {
+    map(
+        all_consuming(many0(complete(parse_quic_transport_parameter))),
+        TlsExtension::QuicTransportParameters,
+    )(i)
+}
+
+pub fn parse_tls_extension_unknown(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, ext_type) = be_u16(i)?;
     let (i, ext_data) = length_data(be_u16)(i)?;
     Ok((
@@ -610,37 +1690,53 @@ pub fn parse_tls_extension_unknown(i: &[u8]) -> IResult<&[u8], TlsExtension> {
 }
 
 /// Parse a single TLS Client Hello extension
-pub fn parse_tls_client_hello_extension(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+pub fn parse_tls_client_hello_extension(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, ext_type) = be_u16(i)?;
     let (i, ext_data) = length_data(be_u16)(i)?;
-    if ext_type & 0x0f0f == 0x0a0a {
+    if is_grease_u16(ext_type) {
         return Ok((i, TlsExtension::Grease(ext_type, ext_data)));
     }
     let ext_len = ext_data.len() as u16;
     let (_, ext) = match ext_type {
         0 => parse_tls_extension_sni_content(ext_data),
         1 => parse_tls_extension_max_fragment_length_content(ext_data),
+        4 => parse_tls_extension_truncated_hmac_content(ext_data, ext_len),
         5 => parse_tls_extension_status_request_content(ext_data, ext_len),
+        6 => parse_tls_extension_user_mapping_content(ext_data),
+        7 => parse_tls_extension_client_authz_content(ext_data),
+        8 => parse_tls_extension_server_authz_content(ext_data),
         10 => parse_tls_extension_elliptic_curves_content(ext_data),
         11 => parse_tls_extension_ec_point_formats_content(ext_data),
         13 => parse_tls_extension_signature_algorithms_content(ext_data),
         15 => parse_tls_extension_heartbeat_content(ext_data),
         16 => parse_tls_extension_alpn_content(ext_data),
+        17 => parse_tls_extension_status_request_v2_content(ext_data),
         18 => parse_tls_extension_signed_certificate_timestamp_content(ext_data), // ok XXX should be empty
+        19 => parse_tls_extension_client_certificate_type_client_content(ext_data),
+        20 => parse_tls_extension_server_certificate_type_client_content(ext_data),
         21 => parse_tls_extension_padding_content(ext_data, ext_len),
         22 => parse_tls_extension_encrypt_then_mac_content(ext_data, ext_len),
         23 => parse_tls_extension_extended_master_secret_content(ext_data, ext_len),
-        28 => parse_tls_extension_record_size_limit(ext_data),
+        24 => parse_tls_extension_token_binding_content(ext_data),
+        28 => parse_tls_extension_record_size_limit_content(ext_data, ext_len),
+        34 => parse_tls_extension_delegated_credentials_content(ext_data),
         35 => parse_tls_extension_session_ticket_content(ext_data, ext_len),
-        41 => parse_tls_extension_pre_shared_key_content(ext_data, ext_len),
+        41 => parse_tls_extension_pre_shared_key_client_content(ext_data),
         42 => parse_tls_extension_early_data_content(ext_data, ext_len),
-        43 => parse_tls_extension_supported_versions_content(ext_data, ext_len),
-        44 => parse_tls_extension_cookie_content(ext_data, ext_len),
+        43 => parse_tls_extension_supported_versions_client_content(ext_data),
+        44 => parse_tls_extension_cookie_content(ext_data),
         45 => parse_tls_extension_psk_key_exchange_modes_content(ext_data),
+        47 => parse_tls_extension_certificate_authorities_content(ext_data),
         48 => parse_tls_extension_oid_filters(ext_data),
         49 => parse_tls_extension_post_handshake_auth_content(ext_data, ext_len),
+        50 => parse_tls_extension_signature_algorithms_cert_content(ext_data),
         51 => parse_tls_extension_key_share_content(ext_data, ext_len), // XXX request
-        13172 => parse_tls_extension_npn_content(ext_data, ext_len),    // XXX must be empty
+        54 => parse_tls_extension_connection_id_content(ext_data),
+        57 => parse_tls_extension_quic_transport_parameters_content(ext_data),
+        13172 => parse_tls_extension_npn_content(ext_data, ext_len),
+        17513 => parse_tls_extension_application_settings_old_content(ext_data),
+        17613 => parse_tls_extension_application_settings_content(ext_data),
+        0xfe0d => parse_tls_extension_encrypted_client_hello_content(ext_data),
         0xff01 => parse_tls_extension_renegotiation_info_content(ext_data),
         0xffce => parse_tls_extension_encrypted_server_name(ext_data),
         _ => Ok((
@@ -652,32 +1748,41 @@ pub fn parse_tls_client_hello_extension(i: &[u8]) -> IResult<&[u8], TlsExtension
 }
 
 /// Parse a single TLS Server Hello extension
-pub fn parse_tls_server_hello_extension(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+pub fn parse_tls_server_hello_extension(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, ext_type) = be_u16(i)?;
     let (i, ext_data) = length_data(be_u16)(i)?;
-    if ext_type & 0x0f0f == 0x0a0a {
+    if is_grease_u16(ext_type) {
         return Ok((i, TlsExtension::Grease(ext_type, ext_data)));
     }
     let ext_len = ext_data.len() as u16;
     let (_, ext) = match ext_type {
         0 => parse_tls_extension_sni_content(ext_data), // XXX SHALL be empty (RFC6066 section 3)
         1 => parse_tls_extension_max_fragment_length_content(ext_data),
+        4 => parse_tls_extension_truncated_hmac_content(ext_data, ext_len),
         5 => parse_tls_extension_status_request_content(ext_data, ext_len), // SHALL be empty
-        11 => parse_tls_extension_ec_point_formats_content(ext_data),       // ok XXX only one
-        13 => parse_tls_extension_signature_algorithms_content(ext_data),   // XXX allowed?
+        6 => parse_tls_extension_user_mapping_content(ext_data),
+        7 => parse_tls_extension_client_authz_content(ext_data),
+        8 => parse_tls_extension_server_authz_content(ext_data),
+        11 => parse_tls_extension_ec_point_formats_content(ext_data), // ok XXX only one
+        13 => parse_tls_extension_signature_algorithms_content(ext_data), // XXX allowed?
         15 => parse_tls_extension_heartbeat_content(ext_data),
         16 => parse_tls_extension_alpn_content(ext_data), // ok XXX MUST contain one protocol name
+        17 => parse_tls_extension_status_request_v2_content(ext_data),
         18 => parse_tls_extension_signed_certificate_timestamp_content(ext_data),
-        21 => parse_tls_extension_encrypt_then_mac_content(ext_data, ext_len),
+        19 => parse_tls_extension_client_certificate_type_server_content(ext_data),
+        20 => parse_tls_extension_server_certificate_type_server_content(ext_data),
+        22 => parse_tls_extension_encrypt_then_mac_content(ext_data, ext_len),
         23 => parse_tls_extension_extended_master_secret_content(ext_data, ext_len),
-        28 => parse_tls_extension_record_size_limit(ext_data),
-        35 => parse_tls_extension_session_ticket_content(ext_data, ext_len),
-        41 => parse_tls_extension_pre_shared_key_content(ext_data, ext_len),
+        24 => parse_tls_extension_token_binding_content(ext_data),
+        28 => parse_tls_extension_record_size_limit_content(ext_data, ext_len),
+        35 => parse_tls_extension_session_ticket_server_content(ext_data, ext_len),
+        41 => parse_tls_extension_pre_shared_key_server_content(ext_data, ext_len),
         42 => parse_tls_extension_early_data_content(ext_data, ext_len),
-        43 => parse_tls_extension_supported_versions_content(ext_data, ext_len), // ok XXX only one
-        44 => parse_tls_extension_cookie_content(ext_data, ext_len),
+        43 => parse_tls_extension_supported_versions_server_content(ext_data, ext_len),
+        44 => parse_tls_extension_cookie_content(ext_data),
         51 => parse_tls_extension_key_share_content(ext_data, ext_len), // XXX selected entry
-        13172 => parse_tls_extension_npn_content(ext_data, ext_len),
+        54 => parse_tls_extension_connection_id_content(ext_data),
+        13172 => parse_tls_extension_npn_server_content(ext_data),
         0xff01 => parse_tls_extension_renegotiation_info_content(ext_data),
         _ => Ok((
             i,
@@ -688,38 +1793,51 @@ pub fn parse_tls_server_hello_extension(i: &[u8]) -> IResult<&[u8], TlsExtension
 }
 
 /// Parse a single TLS extension (of any type)
-pub fn parse_tls_extension(i: &[u8]) -> IResult<&[u8], TlsExtension> {
+pub fn parse_tls_extension(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
     let (i, ext_type) = be_u16(i)?;
     let (i, ext_data) = length_data(be_u16)(i)?;
-    if ext_type & 0x0f0f == 0x0a0a {
+    if is_grease_u16(ext_type) {
         return Ok((i, TlsExtension::Grease(ext_type, ext_data)));
     }
     let ext_len = ext_data.len() as u16;
     let (_, ext) = match ext_type {
         0 => parse_tls_extension_sni_content(ext_data),
         1 => parse_tls_extension_max_fragment_length_content(ext_data),
+        4 => parse_tls_extension_truncated_hmac_content(ext_data, ext_len),
         5 => parse_tls_extension_status_request_content(ext_data, ext_len),
+        6 => parse_tls_extension_user_mapping_content(ext_data),
+        7 => parse_tls_extension_client_authz_content(ext_data),
+        8 => parse_tls_extension_server_authz_content(ext_data),
         10 => parse_tls_extension_elliptic_curves_content(ext_data),
         11 => parse_tls_extension_ec_point_formats_content(ext_data),
         13 => parse_tls_extension_signature_algorithms_content(ext_data),
         15 => parse_tls_extension_heartbeat_content(ext_data),
         16 => parse_tls_extension_alpn_content(ext_data),
+        17 => parse_tls_extension_status_request_v2_content(ext_data),
         18 => parse_tls_extension_signed_certificate_timestamp_content(ext_data),
+        19 => parse_tls_extension_client_certificate_type_content(ext_data),
+        20 => parse_tls_extension_server_certificate_type_content(ext_data),
         21 => parse_tls_extension_padding_content(ext_data, ext_len),
         22 => parse_tls_extension_encrypt_then_mac_content(ext_data, ext_len),
         23 => parse_tls_extension_extended_master_secret_content(ext_data, ext_len),
-        28 => parse_tls_extension_record_size_limit(ext_data),
+        24 => parse_tls_extension_token_binding_content(ext_data),
+        28 => parse_tls_extension_record_size_limit_content(ext_data, ext_len),
         35 => parse_tls_extension_session_ticket_content(ext_data, ext_len),
         40 => parse_tls_extension_key_share_old_content(ext_data, ext_len),
         41 => parse_tls_extension_pre_shared_key_content(ext_data, ext_len),
         42 => parse_tls_extension_early_data_content(ext_data, ext_len),
         43 => parse_tls_extension_supported_versions_content(ext_data, ext_len),
-        44 => parse_tls_extension_cookie_content(ext_data, ext_len),
+        44 => parse_tls_extension_cookie_content(ext_data),
         45 => parse_tls_extension_psk_key_exchange_modes_content(ext_data),
+        47 => parse_tls_extension_certificate_authorities_content(ext_data),
         48 => parse_tls_extension_oid_filters(ext_data),
         49 => parse_tls_extension_post_handshake_auth_content(ext_data, ext_len),
+        50 => parse_tls_extension_signature_algorithms_cert_content(ext_data),
         51 => parse_tls_extension_key_share_content(ext_data, ext_len),
-        13172 => parse_tls_extension_npn_content(ext_data, ext_len),
+        54 => parse_tls_extension_connection_id_content(ext_data),
+        57 => parse_tls_extension_quic_transport_parameters_content(ext_data),
+        13172 => parse_tls_extension_npn_server_content(ext_data),
+        0xfe0d => parse_tls_extension_encrypted_client_hello_content(ext_data),
         0xff01 => parse_tls_extension_renegotiation_info_content(ext_data),
         0xffce => parse_tls_extension_encrypted_server_name(ext_data),
         _ => Ok((
@@ -731,16 +1849,140 @@ pub fn parse_tls_extension(i: &[u8]) -> IResult<&[u8], TlsExtension> {
 }
 
 /// Parse zero or more TLS Client Hello extensions
-pub fn parse_tls_client_hello_extensions(i: &[u8]) -> IResult<&[u8], Vec<TlsExtension>> {
+pub fn parse_tls_client_hello_extensions(i: &[u8]) -> IResult<&[u8], Vec<TlsExtension<'_>>> {
     many0(complete(parse_tls_client_hello_extension))(i)
 }
 
 /// Parse zero or more TLS Server Hello extensions
-pub fn parse_tls_server_hello_extensions(i: &[u8]) -> IResult<&[u8], Vec<TlsExtension>> {
+pub fn parse_tls_server_hello_extensions(i: &[u8]) -> IResult<&[u8], Vec<TlsExtension<'_>>> {
     many0(complete(parse_tls_server_hello_extension))(i)
 }
 
 /// Parse zero or more TLS extensions (of any type)
-pub fn parse_tls_extensions(i: &[u8]) -> IResult<&[u8], Vec<TlsExtension>> {
+pub fn parse_tls_extensions(i: &[u8]) -> IResult<&[u8], Vec<TlsExtension<'_>>> {
     many0(complete(parse_tls_extension))(i)
 }
+
+/// Parse zero or more TLS extensions (of any type), with at most `max_count` extensions
+///
+/// This behaves like `parse_tls_extensions`, but returns an error (`ErrorKind::TooLarge`) if
+/// the input contains more than `max_count` extensions. This should be preferred when parsing
+/// untrusted data, to bound the size of the returned vector.
+pub fn parse_tls_extensions_limited(
+    i: &[u8],
+    max_count: usize,
+) -> IResult<&[u8], Vec<TlsExtension<'_>>> {
+    let mut v = Vec::new();
+    let mut i = i;
+    while !i.is_empty() {
+        match complete(parse_tls_extension)(i) {
+            Ok((rem, ext)) => {
+                if v.len() >= max_count {
+                    return Err(Err::Error(make_error(i, ErrorKind::TooLarge)));
+                }
+                v.push(ext);
+                i = rem;
+            }
+            Err(Err::Error(_)) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok((i, v))
+}
+
+// struct {
+//     CertificateStatusType status_type;
+//     select (status_type) {
+//         case ocsp: OCSPResponse;
+//     } response;
+// } CertificateStatus;
+//
+// opaque OCSPResponse<1..2^24-1>;
+fn parse_certificate_entry_status_content(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
+    let (i, status_type) = CertificateStatusType::parse(i)?;
+    let (i, response) = all_consuming(complete(length_data(be_u24)))(i)?;
+    Ok((i, TlsExtension::CertificateStatus(status_type, response)))
+}
+
+fn parse_certificate_entry_extension(i: &[u8]) -> IResult<&[u8], TlsExtension<'_>> {
+    let (i, ext_type) = be_u16(i)?;
+    let (i, ext_data) = length_data(be_u16)(i)?;
+    let (_, ext) = match ext_type {
+        5 => parse_certificate_entry_status_content(ext_data),
+        18 => parse_tls_extension_signed_certificate_timestamp_content(ext_data),
+        _ => Ok((
+            i,
+            TlsExtension::Unknown(TlsExtensionType(ext_type), ext_data),
+        )),
+    }?;
+    Ok((i, ext))
+}
+
+/// Parse the extensions of a TLS 1.3 CertificateEntry ([RFC8446] section 4.4.2)
+///
+/// Only the extensions allowed in a CertificateEntry are recognized, other extensions are
+/// returned as `Unknown`:
+///
+/// - `status_request`: the stapled response, returned as `TlsExtension::CertificateStatus`
+/// - `signed_certificate_timestamp`
+///
+/// The input must contain only extensions: an error is returned if any of them is malformed.
+pub fn parse_certificate_entry_extensions(i: &[u8]) -> IResult<&[u8], Vec<TlsExtension<'_>>> {
+    let mut v = Vec::new();
+    let mut i = i;
+    while !i.is_empty() {
+        let (rem, ext) = complete(parse_certificate_entry_extension)(i)?;
+        v.push(ext);
+        i = rem;
+    }
+    Ok((i, v))
+}
+
+/// Iterator over raw (unparsed) TLS extensions
+///
+/// Each item is the extension type and the extension data, in wire order. No allocation
+/// is done, and extension data is not parsed (use `parse_tls_extension` or the content
+/// parsers for that).
+///
+/// If the input is truncated, the iterator returns an error and then stops.
+#[derive(Clone, Debug)]
+pub struct TlsExtensionIterator<'a> {
+    data: &'a [u8],
+    done: bool,
+}
+
+impl<'a> TlsExtensionIterator<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        TlsExtensionIterator { data, done: false }
+    }
+}
+
+impl<'a> Iterator for TlsExtensionIterator<'a> {
+    type Item = Result<(u16, &'a [u8]), Err<nom::error::Error<&'a [u8]>>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.data.is_empty() {
+            return None;
+        }
+        match complete(pair(be_u16, length_data(be_u16)))(self.data) {
+            Ok((rem, ext)) => {
+                self.data = rem;
+                Some(Ok(ext))
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<'a> FusedIterator for TlsExtensionIterator<'a> {}
+
+/// Iterate over raw TLS extensions, without allocating
+///
+/// The input should be the extensions block (for ex. the `ext` field of a ClientHello),
+/// without the length prefix.
+pub fn parse_tls_extensions_iter(i: &[u8]) -> TlsExtensionIterator<'_> {
+    TlsExtensionIterator::new(i)
+}
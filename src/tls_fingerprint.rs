@@ -0,0 +1,360 @@
+//! # TLS fingerprints
+//!
+//! Functions to compute fingerprints of TLS peers from parsed handshake messages.
+//!
+//! - [JA3](https://github.com/salesforce/ja3) for clients
+//! - [JA3S](https://github.com/salesforce/ja3) for servers
+//! - [JA4](https://github.com/FoxIO-LLC/ja4) for clients
+//! - [JA4S](https://github.com/FoxIO-LLC/ja4) for servers
+//!
+//! This module requires the `fingerprint` feature.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use core::fmt::Write;
+use sha2::{Digest, Sha256};
+
+use crate::tls::{ClientHello, ServerHello, TlsVersion};
+use crate::tls_ec::{EcPointFormat, NamedGroup};
+use crate::tls_extensions::*;
+use crate::tls_grease::is_grease_u16;
+
+/// Read the extension type and raw data of all extensions, in wire order
+///
+/// Parsing stops at the first malformed extension.
+fn raw_extensions(ext: Option<&[u8]>) -> Vec<(u16, &[u8])> {
+    ext.map(|ext| {
+        parse_tls_extensions_iter(ext)
+            .map_while(Result::ok)
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Append values to `s`, separated by `-`
+fn push_values<I, T>(s: &mut String, values: I)
+where
+    I: IntoIterator<Item = T>,
+    T: core::fmt::Display,
+{
+    for (idx, v) in values.into_iter().enumerate() {
+        if idx > 0 {
+            s.push('-');
+        }
+        let _ = write!(s, "{}", v);
+    }
+}
+
+/// Build the JA3 string of a ClientHello message
+///
+/// The JA3 string is composed of the version, ciphers, extension types, elliptic curves and
+/// elliptic curve point formats, all in decimal. Fields are separated by `,` and values inside
+/// a field by `-`. GREASE values are ignored.
+pub fn ja3_string<'a, CH: ClientHello<'a>>(ch: &CH) -> String {
+    let extensions = raw_extensions(ch.ext());
+    let mut curves: Vec<NamedGroup> = Vec::new();
+    let mut point_formats: Vec<EcPointFormat> = Vec::new();
+    for &(ext_type, ext_data) in &extensions {
+        match TlsExtensionType(ext_type) {
+            TlsExtensionType::SupportedGroups => {
+                if let Ok((_, TlsExtension::EllipticCurves(v))) =
+                    parse_tls_extension_elliptic_curves_content(ext_data)
+                {
+                    curves = v;
+                }
+            }
+            TlsExtensionType::EcPointFormats => {
+                if let Ok((_, TlsExtension::EcPointFormats(v))) =
+                    parse_tls_extension_ec_point_formats_content(ext_data)
+                {
+                    point_formats = v;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let mut s = String::new();
+    let _ = write!(s, "{},", ch.version().0);
+    push_values(
+        &mut s,
+        ch.ciphers()
+            .iter()
+            .map(|c| c.0)
+            .filter(|&c| !is_grease_u16(c)),
+    );
+    s.push(',');
+    push_values(
+        &mut s,
+        extensions
+            .iter()
+            .map(|&(t, _)| t)
+            .filter(|&t| !is_grease_u16(t)),
+    );
+    s.push(',');
+    push_values(
+        &mut s,
+        curves.iter().map(|g| g.0).filter(|&g| !is_grease_u16(g)),
+    );
+    s.push(',');
+    push_values(&mut s, point_formats.iter().map(|f| f.0));
+    s
+}
+
+/// Compute the JA3 fingerprint (MD5 digest of the JA3 string) of a ClientHello message
+///
+/// The fingerprint is usually displayed as a lowercase hex string.
+pub fn ja3_hash<'a, CH: ClientHello<'a>>(ch: &CH) -> [u8; 16] {
+    md5::compute(ja3_string(ch).as_bytes()).0
+}
+
+/// Build the JA3S string of a ServerHello message
+///
+/// The JA3S string is composed of the version, the selected cipher and the extension types (in
+/// wire order), all in decimal. Fields are separated by `,` and extension types by `-`. GREASE
+/// values are ignored.
+pub fn ja3s_string<'a, SH: ServerHello<'a>>(sh: &SH) -> String {
+    let extensions = raw_extensions(sh.ext());
+    let mut s = String::new();
+    let _ = write!(s, "{},{},", sh.version().0, sh.cipher().0);
+    push_values(
+        &mut s,
+        extensions
+            .iter()
+            .map(|&(t, _)| t)
+            .filter(|&t| !is_grease_u16(t)),
+    );
+    s
+}
+
+/// Compute the JA3S fingerprint (MD5 digest of the JA3S string) of a ServerHello message
+///
+/// The fingerprint is usually displayed as a lowercase hex string.
+pub fn ja3s_hash<'a, SH: ServerHello<'a>>(sh: &SH) -> [u8; 16] {
+    md5::compute(ja3s_string(sh).as_bytes()).0
+}
+
+/// Return the JA4 code of a TLS version
+fn ja4_version(v: TlsVersion) -> &'static str {
+    match v {
+        TlsVersion::Tls13 => "13",
+        TlsVersion::Tls12 => "12",
+        TlsVersion::Tls11 => "11",
+        TlsVersion::Tls10 => "10",
+        TlsVersion::Ssl30 => "s3",
+        TlsVersion::Ssl20 => "s2",
+        TlsVersion::DTls10 => "d1",
+        TlsVersion::DTls12 => "d2",
+        TlsVersion(0xfefc) => "d3",
+        _ => "00",
+    }
+}
+
+/// Return the JA4 protocol code: `d` for DTLS, `t` otherwise
+///
+/// QUIC cannot be detected from the handshake messages, so `q` is never returned.
+fn ja4_protocol(v: TlsVersion) -> char {
+    if v.0 >> 8 == 0xfe {
+        'd'
+    } else {
+        't'
+    }
+}
+
+/// Return the first and last characters of an ALPN protocol
+///
+/// If one of them is not alphanumeric, the first and last hex digits of the protocol bytes are
+/// used instead. If there is no protocol, `00` is returned.
+fn ja4_alpn(protocol: Option<&[u8]>) -> String {
+    let mut s = String::new();
+    match protocol {
+        Some(&[first, ..]) => {
+            let last = protocol.and_then(|p| p.last().copied()).unwrap_or(first);
+            if first.is_ascii_alphanumeric() && last.is_ascii_alphanumeric() {
+                s.push(first as char);
+                s.push(last as char);
+            } else {
+                let _ = write!(s, "{:x}{:x}", first >> 4, last & 0xf);
+            }
+        }
+        _ => s.push_str("00"),
+    }
+    s
+}
+
+/// Return the first protocol of an ALPN extension
+fn first_alpn_protocol<'a>(extensions: &[(u16, &'a [u8])]) -> Option<&'a [u8]> {
+    extensions
+        .iter()
+        .filter(|&&(t, _)| {
+            TlsExtensionType(t) == TlsExtensionType::ApplicationLayerProtocolNegotiation
+        })
+        .find_map(|&(_, data)| match parse_tls_extension_alpn_content(data) {
+            Ok((_, TlsExtension::ALPN(v))) => v.first().copied(),
+            _ => None,
+        })
+}
+
+/// Append 16-bit values as 4-digits hex, separated by `,`
+fn push_hex_values<I: IntoIterator<Item = u16>>(s: &mut String, values: I) {
+    for (idx, v) in values.into_iter().enumerate() {
+        if idx > 0 {
+            s.push(',');
+        }
+        let _ = write!(s, "{:04x}", v);
+    }
+}
+
+/// Return the first 12 hex digits of the SHA256 digest of `s`, or `000000000000` if `s` is
+/// empty
+fn ja4_hash(s: &str) -> String {
+    if s.is_empty() {
+        return String::from("000000000000");
+    }
+    let digest = Sha256::digest(s.as_bytes());
+    let mut h = String::with_capacity(12);
+    for b in &digest[..6] {
+        let _ = write!(h, "{:02x}", b);
+    }
+    h
+}
+
+/// Build the three parts of a JA4 fingerprint, before hashing
+fn ja4_parts<'a, CH: ClientHello<'a>>(ch: &CH) -> (String, String, String) {
+    let extensions: Vec<_> = raw_extensions(ch.ext())
+        .into_iter()
+        .filter(|&(t, _)| !is_grease_u16(t))
+        .collect();
+    let mut version = ch.version();
+    let mut sig_algs = Vec::new();
+    let mut has_sni = false;
+    for &(ext_type, ext_data) in &extensions {
+        match TlsExtensionType(ext_type) {
+            TlsExtensionType::ServerName => has_sni = true,
+            TlsExtensionType::SupportedVersions => {
+                if let Ok((_, TlsExtension::SupportedVersions(v))) =
+                    parse_tls_extension_supported_versions_client_content(ext_data)
+                {
+                    let v = v.into_iter().filter(|v| !is_grease_u16(v.0));
+                    // DTLS versions are decreasing
+                    let highest = if ja4_protocol(version) == 'd' {
+                        v.min_by_key(|v| v.0)
+                    } else {
+                        v.max_by_key(|v| v.0)
+                    };
+                    version = highest.unwrap_or(version);
+                }
+            }
+            TlsExtensionType::SignatureAlgorithms => {
+                if let Ok((_, TlsExtension::SignatureAlgorithms(v))) =
+                    parse_tls_extension_signature_algorithms_content(ext_data)
+                {
+                    sig_algs = v.into_iter().filter(|&s| !is_grease_u16(s)).collect();
+                }
+            }
+            _ => (),
+        }
+    }
+    let mut ciphers: Vec<u16> = ch
+        .ciphers()
+        .iter()
+        .map(|c| c.0)
+        .filter(|&c| !is_grease_u16(c))
+        .collect();
+    ciphers.sort_unstable();
+
+    let mut a = String::new();
+    a.push(ja4_protocol(ch.version()));
+    a.push_str(ja4_version(version));
+    a.push(if has_sni { 'd' } else { 'i' });
+    let _ = write!(
+        a,
+        "{:02}{:02}",
+        ciphers.len().min(99),
+        extensions.len().min(99)
+    );
+    a.push_str(&ja4_alpn(first_alpn_protocol(&extensions)));
+
+    let mut b = String::new();
+    push_hex_values(&mut b, ciphers);
+
+    // SNI and ALPN are not part of the extensions hash
+    let mut ext_types: Vec<u16> = extensions
+        .iter()
+        .map(|&(t, _)| t)
+        .filter(|&t| {
+            TlsExtensionType(t) != TlsExtensionType::ServerName
+                && TlsExtensionType(t) != TlsExtensionType::ApplicationLayerProtocolNegotiation
+        })
+        .collect();
+    ext_types.sort_unstable();
+    let mut c = String::new();
+    push_hex_values(&mut c, ext_types);
+    if !sig_algs.is_empty() {
+        c.push('_');
+        push_hex_values(&mut c, sig_algs);
+    }
+    (a, b, c)
+}
+
+/// Compute the JA4 fingerprint of a ClientHello message
+///
+/// The fingerprint is composed of the protocol, highest supported version, SNI presence, the
+/// number of ciphers and extensions and the first ALPN protocol, followed by the truncated
+/// SHA256 digests of the sorted ciphers, and of the sorted extension types (excluding SNI and
+/// ALPN) and signature algorithms. GREASE values are ignored.
+///
+/// The protocol is `d` for DTLS, and `t` otherwise (QUIC is not detected).
+pub fn ja4<'a, CH: ClientHello<'a>>(ch: &CH) -> String {
+    let (a, b, c) = ja4_parts(ch);
+    format!("{}_{}_{}", a, ja4_hash(&b), ja4_hash(&c))
+}
+
+/// Build the raw (unhashed) JA4 fingerprint of a ClientHello message, also known as `JA4_r`
+///
+/// Ciphers, extension types and signature algorithms are displayed as 4-digits hex values.
+pub fn ja4_raw<'a, CH: ClientHello<'a>>(ch: &CH) -> String {
+    let (a, b, c) = ja4_parts(ch);
+    format!("{}_{}_{}", a, b, c)
+}
+
+/// Build the three parts of a JA4S fingerprint, before hashing
+fn ja4s_parts<'a, SH: ServerHello<'a>>(sh: &SH) -> (String, String, String) {
+    let extensions = raw_extensions(sh.ext());
+    let version = extensions
+        .iter()
+        .find(|&&(t, _)| TlsExtensionType(t) == TlsExtensionType::SupportedVersions)
+        .and_then(|&(_, data)| data.try_into().ok())
+        .map(|v| TlsVersion(u16::from_be_bytes(v)))
+        .unwrap_or_else(|| sh.version());
+
+    let mut a = String::new();
+    a.push(ja4_protocol(sh.version()));
+    a.push_str(ja4_version(version));
+    let _ = write!(a, "{:02}", extensions.len().min(99));
+    a.push_str(&ja4_alpn(first_alpn_protocol(&extensions)));
+
+    let b = format!("{:04x}", sh.cipher().0);
+
+    let mut c = String::new();
+    push_hex_values(&mut c, extensions.iter().map(|&(t, _)| t));
+    (a, b, c)
+}
+
+/// Compute the JA4S fingerprint of a ServerHello message
+///
+/// The fingerprint is composed of the protocol, selected version, the number of extensions and
+/// the selected ALPN protocol, followed by the selected cipher, and the truncated SHA256 digest
+/// of the extension types (in wire order).
+pub fn ja4s<'a, SH: ServerHello<'a>>(sh: &SH) -> String {
+    let (a, b, c) = ja4s_parts(sh);
+    format!("{}_{}_{}", a, b, ja4_hash(&c))
+}
+
+/// Build the raw (unhashed) JA4S fingerprint of a ServerHello message, also known as `JA4S_r`
+pub fn ja4s_raw<'a, SH: ServerHello<'a>>(sh: &SH) -> String {
+    let (a, b, c) = ja4s_parts(sh);
+    format!("{}_{}_{}", a, b, c)
+}
@@ -0,0 +1,20 @@
+//! GREASE (Generate Random Extensions And Sustain Extensibility) values
+//!
+//! GREASE values are reserved by [RFC8701](https://tools.ietf.org/html/rfc8701), and are sent by
+//! clients to check that peers correctly ignore unknown values. They can appear as cipher
+//! suites, extension types, named groups, signature algorithms, versions and ALPN identifiers.
+
+/// Tell if a 16-bit value (cipher suite, extension type, named group, version, etc.) is a GREASE
+/// value
+///
+/// The 16 GREASE values are `0x0a0a`, `0x1a1a`, ..., `0xfafa`.
+pub const fn is_grease_u16(v: u16) -> bool {
+    v & 0x0f0f == 0x0a0a && (v >> 8) == (v & 0xff)
+}
+
+/// Tell if a 8-bit value (PSK key exchange mode) is a GREASE value
+///
+/// The 8 GREASE values are `0x0b`, `0x2a`, `0x49`, `0x68`, `0x87`, `0xa6`, `0xc5` and `0xe4`.
+pub const fn is_grease_u8(v: u8) -> bool {
+    v % 0x1f == 0x0b
+}
@@ -0,0 +1,102 @@
+//! Parsing of untrusted data with resource limits
+//!
+//! Individual parsers do not limit the number of parsed items (other than by the length of the
+//! input). `safe_parse_tls_records` parses a buffer of TLS records, and checks the number of
+//! records, messages and list items against a set of limits.
+
+use crate::tls::*;
+use alloc::vec::Vec;
+use nom::Err;
+
+/// Limits used by `safe_parse_tls_records`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TlsParseLimits {
+    /// Maximum number of records
+    pub max_records: usize,
+    /// Maximum number of handshake messages, for all records
+    pub max_handshake_messages: usize,
+    /// Maximum number of items in a list of a message (for ex. cipher suites, or certificates)
+    pub max_list_items: usize,
+}
+
+impl Default for TlsParseLimits {
+    /// Default limits: 256 records, 64 handshake messages and 4096 items per list
+    ///
+    /// Since a ClientHello can contain up to 32767 cipher suites, the list limit is only safe
+    /// for regular handshakes, where the number of items is much lower.
+    fn default() -> Self {
+        TlsParseLimits {
+            max_records: 256,
+            max_handshake_messages: 64,
+            max_list_items: 4096,
+        }
+    }
+}
+
+/// Error types for `safe_parse_tls_records`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TlsSafeParseError {
+    /// Input data is not a valid TLS record
+    InvalidRecord,
+    /// Input has more records than `max_records`
+    TooManyRecords,
+    /// Input has more handshake messages than `max_handshake_messages`
+    TooManyHandshakeMessages,
+    /// A message has a list with more items than `max_list_items`
+    TooManyListItems,
+}
+
+fn check_list_items(msg: &TlsMessageHandshake, max: usize) -> bool {
+    match msg {
+        TlsMessageHandshake::ClientHello(ch) => ch.ciphers.len() <= max && ch.comp.len() <= max,
+        TlsMessageHandshake::Certificate(c) => c.cert_chain.len() <= max,
+        TlsMessageHandshake::CertificateV13(c) => c.cert_list.len() <= max,
+        TlsMessageHandshake::CertificateRequest(cr) => {
+            cr.cert_types.len() <= max
+                && cr.sig_hash_algs.as_ref().map_or(0, |v| v.len()) <= max
+                && cr.unparsed_ca.len() <= max
+        }
+        _ => true,
+    }
+}
+
+/// Parse a buffer of TLS plaintext records, checking resource limits
+///
+/// Records are parsed until the end of the input, or until an incomplete record is found. The
+/// parsed records and the remaining (incomplete) data are returned.
+///
+/// This function does not panic, and returns an error if the records are invalid or if the
+/// limits are exceeded. Since the length of a record is limited to `MAX_RECORD_LEN`, memory
+/// allocated while parsing a record is bounded.
+pub fn safe_parse_tls_records<'a>(
+    input: &'a [u8],
+    limits: &TlsParseLimits,
+) -> Result<(&'a [u8], Vec<TlsPlaintext<'a>>), TlsSafeParseError> {
+    let mut records = Vec::new();
+    let mut handshake_messages = 0;
+    let mut i = input;
+    while !i.is_empty() {
+        let (rem, record) = match parse_tls_plaintext(i) {
+            Ok(r) => r,
+            Err(Err::Incomplete(_)) => break,
+            Err(_) => return Err(TlsSafeParseError::InvalidRecord),
+        };
+        if records.len() >= limits.max_records {
+            return Err(TlsSafeParseError::TooManyRecords);
+        }
+        for msg in &record.msg {
+            if let TlsMessage::Handshake(ref m) = msg {
+                handshake_messages += 1;
+                if handshake_messages > limits.max_handshake_messages {
+                    return Err(TlsSafeParseError::TooManyHandshakeMessages);
+                }
+                if !check_list_items(m, limits.max_list_items) {
+                    return Err(TlsSafeParseError::TooManyListItems);
+                }
+            }
+        }
+        records.push(record);
+        i = rem;
+    }
+    Ok((i, records))
+}
@@ -0,0 +1,119 @@
+//! TLS handshake messages reassembly
+//!
+//! A TLS handshake message can be split across several records, and a record can contain
+//! several handshake messages (Section 5.1 of RFC8446). Records do not have to be aligned with
+//! messages.
+
+use alloc::vec::Vec;
+
+/// Default maximum length of a handshake message (body only)
+pub const TLS_DEFAULT_MAX_HANDSHAKE_LEN: u32 = 1 << 20;
+
+/// Error types for TLS handshake messages reassembly
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TlsReassemblyError {
+    /// Message is longer than the configured maximum length
+    MessageTooLarge,
+}
+
+/// Reassembler for TLS handshake messages spanning multiple records
+///
+/// The data of successive records of type `Handshake` (for one direction) is added using
+/// `add_record_data`. Complete messages (header and body) are then returned by `next_message`,
+/// and can be parsed using `parse_tls_message_handshake`.
+///
+/// # Example
+///
+/// ```rust
+/// use tls_parser::*;
+///
+/// fn handle_record(r: &mut TlsHandshakeReassembler, record: &TlsRawRecord) {
+///     if record.hdr.record_type != TlsRecordType::Handshake {
+///         return;
+///     }
+///     if let Err(e) = r.add_record_data(record.data) {
+///         println!("reassembly error: {:?}", e);
+///         return;
+///     }
+///     while let Some(msg) = r.next_message() {
+///         let res = parse_tls_message_handshake(&msg);
+///         // ...
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct TlsHandshakeReassembler {
+    buffer: Vec<u8>,
+    max_message_len: u32,
+}
+
+impl Default for TlsHandshakeReassembler {
+    fn default() -> Self {
+        TlsHandshakeReassembler::new()
+    }
+}
+
+impl TlsHandshakeReassembler {
+    pub fn new() -> Self {
+        TlsHandshakeReassembler {
+            buffer: Vec::new(),
+            max_message_len: TLS_DEFAULT_MAX_HANDSHAKE_LEN,
+        }
+    }
+
+    /// Set the maximum length of a handshake message (body only)
+    ///
+    /// Data containing a message header with a longer length is rejected.
+    pub fn with_max_message_len(mut self, max_message_len: u32) -> Self {
+        self.max_message_len = max_message_len;
+        self
+    }
+
+    /// Add the data (payload) of a record of type `Handshake`
+    ///
+    /// If the data is rejected, the reassembler is not modified.
+    pub fn add_record_data(&mut self, data: &[u8]) -> Result<(), TlsReassemblyError> {
+        let prev_len = self.buffer.len();
+        self.buffer.extend_from_slice(data);
+        // check the length of all messages headers
+        let mut pos = 0;
+        while let Some(len) = self.message_len(pos) {
+            if len > self.max_message_len {
+                self.buffer.truncate(prev_len);
+                return Err(TlsReassemblyError::MessageTooLarge);
+            }
+            pos += 4 + len as usize;
+        }
+        Ok(())
+    }
+
+    /// Return the next complete handshake message (header and body), if any
+    pub fn next_message(&mut self) -> Option<Vec<u8>> {
+        let len = 4 + self.message_len(0)? as usize;
+        if self.buffer.len() < len {
+            return None;
+        }
+        Some(self.buffer.drain(..len).collect())
+    }
+
+    /// Tell if there is no buffered data
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Return the number of buffered bytes
+    pub fn buffered_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Drop all buffered data
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+
+    /// Read the length of the message starting at `pos`, if the header is complete
+    fn message_len(&self, pos: usize) -> Option<u32> {
+        let hdr = self.buffer.get(pos..pos + 4)?;
+        Some(u32::from_be_bytes([0, hdr[1], hdr[2], hdr[3]]))
+    }
+}
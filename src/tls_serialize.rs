@@ -1,6 +1,9 @@
 use crate::tls::*;
 use crate::tls_ec::{ECPoint, NamedGroup};
-use crate::tls_extensions::{SNIType, TlsExtension, TlsExtensionType};
+use crate::tls_extensions::{
+    KeyShareEntry, MaxFragmentLength, ServerName, TlsExtension, TlsExtensionType,
+};
+use crate::tls_sign_hash::SignatureScheme;
 use alloc::vec::Vec;
 use cookie_factory::bytes::{be_u16, be_u24, be_u8};
 use cookie_factory::combinator::slice;
@@ -13,11 +16,36 @@ pub use cookie_factory::GenError;
 pub use rusticata_macros::Serialize;
 
 fn gen_tls_ext_sni_hostname<'a, 'b: 'a, W: Write + 'a>(
-    i: &(SNIType, &'b [u8]),
+    i: &ServerName<'b>,
 ) -> impl SerializeFn<W> + 'a {
-    tuple((be_u8((i.0).0), be_u16(i.1.len() as u16), slice(i.1)))
+    tuple((
+        be_u8(i.name_type.0),
+        be_u16(i.name.len() as u16),
+        slice(i.name),
+    ))
 }
 
+/// Write the length of `f` as a single byte, followed by `f`
+///
+/// Return `GenError::CustomError(0)` if the length does not fit in a byte.
+fn length_be_u8<W, F>(f: F) -> impl SerializeFn<W>
+where
+    W: Write,
+    F: SerializeFn<Vec<u8>>,
+{
+    move |out| {
+        // use a temporary buffer
+        let (buf, len) = gen(&f, Vec::new())?;
+        if len > u64::from(u8::MAX) {
+            return Err(GenError::CustomError(0));
+        }
+        tuple((be_u8(len as u8), slice(buf)))(out)
+    }
+}
+
+/// Write the length of `f` as a 16-bit integer, followed by `f`
+///
+/// Return `GenError::CustomError(0)` if the length does not fit in 16 bits.
 fn length_be_u16<W, F>(f: F) -> impl SerializeFn<W>
 where
     W: Write,
@@ -26,10 +54,16 @@ where
     move |out| {
         // use a temporary buffer
         let (buf, len) = gen(&f, Vec::new())?;
+        if len > u64::from(u16::MAX) {
+            return Err(GenError::CustomError(0));
+        }
         tuple((be_u16(len as u16), slice(buf)))(out)
     }
 }
 
+/// Write the length of `f` as a 24-bit integer, followed by `f`
+///
+/// Return `GenError::CustomError(0)` if the length does not fit in 24 bits.
 fn length_be_u24<W, F>(f: F) -> impl SerializeFn<W>
 where
     W: Write,
@@ -38,6 +72,9 @@ where
     move |out| {
         // use a temporary buffer
         let (buf, len) = gen(&f, Vec::new())?;
+        if len >= 1 << 24 {
+            return Err(GenError::CustomError(0));
+        }
         tuple((be_u24(len as u32), slice(buf)))(out)
     }
 }
@@ -50,7 +87,7 @@ where
     move |out| tuple((be_u16(tag), length_be_u16(&f)))(out)
 }
 
-fn gen_tls_ext_sni<'a, W>(m: &'a [(SNIType, &[u8])]) -> impl SerializeFn<W> + 'a
+fn gen_tls_ext_sni<'a, W>(m: &'a [ServerName]) -> impl SerializeFn<W> + 'a
 where
     W: Write + 'a,
 {
@@ -60,11 +97,11 @@ where
     )
 }
 
-fn gen_tls_ext_max_fragment_length<W>(l: u8) -> impl SerializeFn<W>
+fn gen_tls_ext_max_fragment_length<W>(l: MaxFragmentLength) -> impl SerializeFn<W>
 where
     W: Write,
 {
-    tagged_extension(u16::from(TlsExtensionType::MaxFragmentLength), be_u8(l))
+    tagged_extension(u16::from(TlsExtensionType::MaxFragmentLength), be_u8(l.0))
 }
 
 fn gen_tls_named_group<W>(g: NamedGroup) -> impl SerializeFn<W>
@@ -84,6 +121,79 @@ where
     )
 }
 
+fn gen_tls_ext_token_binding<'a, W>(
+    major: u8,
+    minor: u8,
+    key_params: &'a [u8],
+) -> impl SerializeFn<W> + 'a
+where
+    W: Write + 'a,
+{
+    tagged_extension(
+        u16::from(TlsExtensionType::TokenBinding),
+        tuple((
+            be_u8(major),
+            be_u8(minor),
+            be_u8(key_params.len() as u8),
+            slice(key_params),
+        )),
+    )
+}
+
+fn gen_tls_ext_cookie<'a, W>(cookie: &'a [u8]) -> impl SerializeFn<W> + 'a
+where
+    W: Write + 'a,
+{
+    tagged_extension(
+        u16::from(TlsExtensionType::Cookie),
+        tuple((be_u16(cookie.len() as u16), slice(cookie))),
+    )
+}
+
+fn gen_tls_ext_alpn<'a, W>(v: &'a [&[u8]]) -> impl SerializeFn<W> + 'a
+where
+    W: Write + 'a,
+{
+    tagged_extension(
+        u16::from(TlsExtensionType::ApplicationLayerProtocolNegotiation),
+        length_be_u16(all(v.iter().map(|p| length_be_u8(slice(p))))),
+    )
+}
+
+fn gen_tls_ext_signature_algorithms<'a, W>(v: &'a [u16]) -> impl SerializeFn<W> + 'a
+where
+    W: Write + 'a,
+{
+    tagged_extension(
+        u16::from(TlsExtensionType::SignatureAlgorithms),
+        length_be_u16(all(v.iter().map(|&s| be_u16(s)))),
+    )
+}
+
+/// Supported Versions extension, as sent by clients
+fn gen_tls_ext_supported_versions_client<'a, W>(v: &'a [TlsVersion]) -> impl SerializeFn<W> + 'a
+where
+    W: Write + 'a,
+{
+    tagged_extension(
+        u16::from(TlsExtensionType::SupportedVersions),
+        length_be_u8(all(v.iter().map(|version| be_u16(version.0)))),
+    )
+}
+
+/// Key Share extension, as sent by clients
+fn gen_tls_ext_key_share_client<'a, W>(v: &'a [KeyShareEntry]) -> impl SerializeFn<W> + 'a
+where
+    W: Write + 'a,
+{
+    tagged_extension(
+        u16::from(TlsExtensionType::KeyShare),
+        length_be_u16(all(v.iter().map(|e| {
+            tuple((gen_tls_named_group(e.group), length_be_u16(slice(e.kx))))
+        }))),
+    )
+}
+
 /// Serialize a single TLS extension
 ///
 /// # Example
@@ -109,8 +219,16 @@ where
     move |out| match m {
         TlsExtension::SNI(ref v) => gen_tls_ext_sni(v)(out),
         TlsExtension::MaxFragmentLength(l) => gen_tls_ext_max_fragment_length(*l)(out),
+        TlsExtension::TokenBinding {
+            major,
+            minor,
+            ref key_params,
+        } => gen_tls_ext_token_binding(*major, *minor, key_params)(out),
 
         TlsExtension::EllipticCurves(ref v) => gen_tls_ext_elliptic_curves(v)(out),
+        TlsExtension::Cookie(cookie) => gen_tls_ext_cookie(cookie)(out),
+        TlsExtension::ALPN(ref v) => gen_tls_ext_alpn(v)(out),
+        TlsExtension::SignatureAlgorithms(ref v) => gen_tls_ext_signature_algorithms(v)(out),
         _ => Err(GenError::NotYetImplemented),
     }
 }
@@ -123,6 +241,98 @@ where
     length_be_u16(many_ref(m, gen_tls_extension))
 }
 
+#[derive(Clone, Debug)]
+enum BuilderExtension<'a> {
+    Extension(TlsExtension<'a>),
+    SupportedVersions(Vec<TlsVersion>),
+    KeyShare(Vec<KeyShareEntry<'a>>),
+}
+
+/// Builder for the extensions block of a ClientHello message
+///
+/// Extensions are serialized in the order they were added. The result of `build` does not
+/// contain the length of the block, and can be used as the `ext` field of
+/// `TlsClientHelloContents`.
+///
+/// # Example
+///
+///  ```rust
+///  use tls_parser::{ClientHelloExtensionsBuilder, NamedGroup, TlsVersion};
+///
+///  let ext = ClientHelloExtensionsBuilder::new()
+///      .sni(b"example.com")
+///      .alpn(&[b"h2", b"http/1.1"])
+///      .supported_versions(&[TlsVersion::Tls13, TlsVersion::Tls12])
+///      .supported_groups(&[NamedGroup::EcdhX25519])
+///      .build()
+///      .expect("could not serialize extensions");
+///  ```
+#[derive(Clone, Debug, Default)]
+pub struct ClientHelloExtensionsBuilder<'a> {
+    extensions: Vec<BuilderExtension<'a>>,
+}
+
+impl<'a> ClientHelloExtensionsBuilder<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a Server Name Indication extension, with a single host name
+    pub fn sni(self, host_name: &'a [u8]) -> Self {
+        self.extension(TlsExtension::SNI(vec![ServerName::host_name(host_name)]))
+    }
+
+    /// Add an Application-Layer Protocol Negotiation extension
+    pub fn alpn(self, protocols: &[&'a [u8]]) -> Self {
+        self.extension(TlsExtension::ALPN(protocols.to_vec()))
+    }
+
+    /// Add a Supported Versions extension
+    pub fn supported_versions(mut self, versions: &[TlsVersion]) -> Self {
+        let ext = BuilderExtension::SupportedVersions(versions.to_vec());
+        self.extensions.push(ext);
+        self
+    }
+
+    /// Add a Supported Groups extension
+    pub fn supported_groups(self, groups: &[NamedGroup]) -> Self {
+        self.extension(TlsExtension::EllipticCurves(groups.to_vec()))
+    }
+
+    /// Add a Signature Algorithms extension
+    pub fn signature_algorithms(self, schemes: &[SignatureScheme]) -> Self {
+        let v = schemes.iter().map(|s| s.0).collect();
+        self.extension(TlsExtension::SignatureAlgorithms(v))
+    }
+
+    /// Add a Key Share extension (TLS 1.3)
+    pub fn key_share(mut self, entries: &[KeyShareEntry<'a>]) -> Self {
+        let ext = BuilderExtension::KeyShare(entries.to_vec());
+        self.extensions.push(ext);
+        self
+    }
+
+    /// Add any extension supported by `gen_tls_extension`
+    pub fn extension(mut self, ext: TlsExtension<'a>) -> Self {
+        self.extensions.push(BuilderExtension::Extension(ext));
+        self
+    }
+
+    /// Serialize the extensions block
+    pub fn build(&self) -> Result<Vec<u8>, GenError> {
+        let gen_ext = |e: &BuilderExtension<'a>, out: Vec<u8>| match e {
+            BuilderExtension::Extension(ref ext) => gen(gen_tls_extension(ext), out),
+            BuilderExtension::SupportedVersions(ref v) => {
+                gen(gen_tls_ext_supported_versions_client(v), out)
+            }
+            BuilderExtension::KeyShare(ref v) => gen(gen_tls_ext_key_share_client(v), out),
+        };
+        self.extensions
+            .iter()
+            .try_fold(Vec::new(), |out, e| gen_ext(e, out).map(|(out, _)| out))
+    }
+}
+
 fn gen_tls_sessionid<'a, W>(m: &'a Option<&[u8]>) -> impl SerializeFn<W> + 'a
 where
     W: Write + 'a,
@@ -133,13 +343,17 @@ where
     }
 }
 
+/// Serialize the extensions block, if present
+///
+/// If `m` is `None`, nothing is written (not even the length), so the message can be parsed back
+/// to the same structure. An empty block (`Some(&[])`) is written as a zero length.
 fn maybe_extensions<'a, W>(m: &'a Option<&[u8]>) -> impl SerializeFn<W> + 'a
 where
     W: Write + 'a,
 {
     move |out| match m {
         Some(o) => be_u16(o.len() as u16)(out).and_then(slice(o)),
-        None => be_u16(0)(out),
+        None => Ok(out),
     }
 }
 
@@ -163,7 +377,17 @@ where
     ))
 }
 
+impl<'a> Serialize<Vec<u8>> for TlsClientHelloContents<'a> {
+    type Error = GenError;
+    fn serialize(&self) -> Result<Vec<u8>, Self::Error> {
+        gen_simple(gen_tls_clienthello(self), Vec::new())
+    }
+}
+
 /// Serialize a ServerHello message
+///
+/// If `ext` is `None`, the extensions length is omitted (as done by servers not sending any
+/// extension).
 pub fn gen_tls_serverhello<'a, W>(m: &'a TlsServerHelloContents) -> impl SerializeFn<W> + 'a
 where
     W: Write + 'a,
@@ -181,6 +405,13 @@ where
     ))
 }
 
+impl<'a> Serialize<Vec<u8>> for TlsServerHelloContents<'a> {
+    type Error = GenError;
+    fn serialize(&self) -> Result<Vec<u8>, Self::Error> {
+        gen_simple(gen_tls_serverhello(self), Vec::new())
+    }
+}
+
 /// Serialize a ServerHello (TLS 1.3 draft 18) message
 pub fn gen_tls_serverhellodraft18<'a, W>(
     m: &'a TlsServerHelloV13Draft18Contents,
@@ -280,7 +511,7 @@ where
         TlsMessageHandshake::ServerHello(ref m) => gen_tls_serverhello(m)(out),
         TlsMessageHandshake::ServerHelloV13Draft18(ref m) => gen_tls_serverhellodraft18(m)(out),
         TlsMessageHandshake::ClientKeyExchange(ref m) => gen_tls_clientkeyexchange(m)(out),
-        TlsMessageHandshake::Finished(m) => gen_tls_finished(m)(out),
+        TlsMessageHandshake::Finished(ref m) => gen_tls_finished(m.verify_data)(out),
         _ => Err(GenError::NotYetImplemented),
     }
 }
@@ -365,11 +596,32 @@ impl<'a> Serialize<Vec<u8>> for TlsPlaintext<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tls_extensions::parse_tls_extension;
+    use crate::tls_extensions::{
+        parse_key_share_client_hello, parse_tls_client_hello_extensions, parse_tls_extension,
+    };
     use hex_literal::hex;
 
     const CH_DHE: &[u8] = include_bytes!("../assets/client_hello_dhe.bin");
 
+    #[rustfmt::skip]
+    static SERVER_HELLO_TLS12: &[u8] = &[
+        0x16, 0x03, 0x03, 0x00, 0x3b, 0x02, 0x00, 0x00, 0x37, 0x03, 0x03, 0x57,
+        0xc4, 0x57, 0xda, 0x9c, 0xd3, 0x24, 0x6d, 0x9d, 0x02, 0x26, 0xa2, 0xe5,
+        0x9a, 0xe8, 0xa5, 0x6f, 0x40, 0xad, 0x94, 0x30, 0xba, 0x49, 0x05, 0x3a,
+        0x1e, 0x1b, 0xe1, 0x94, 0xa1, 0xba, 0x41, 0x00, 0xc0, 0x2f, 0x00, 0x00,
+        0x0f, 0xff, 0x01, 0x00, 0x01, 0x00, 0x00, 0x23, 0x00, 0x00, 0x00, 0x0b,
+        0x00, 0x02, 0x01, 0x00
+    ];
+
+    #[rustfmt::skip]
+    static SERVER_HELLO_SSLV3: &[u8] = &[
+        0x16, 0x03, 0x00, 0x00, 0x3a, 0x02, 0x00, 0x00, 0x36, 0x03, 0x00, 0xf9, 0x5d, 0x75,
+        0x87, 0x68, 0x2b, 0x49, 0x8c, 0x72, 0xf8, 0x72, 0x3a, 0xb7, 0xb6, 0x79, 0x02, 0xee,
+        0x3c, 0xa9, 0xfc, 0x12, 0x90, 0xea, 0xab, 0x29, 0x46, 0xcc, 0xc2, 0x0d, 0xee, 0x12,
+        0x93, 0x10, 0xb3, 0xdc, 0xd8, 0x87, 0x0b, 0x14, 0xa0, 0x3a, 0x34, 0x90, 0xeb, 0x26,
+        0xec, 0x92, 0xe2, 0x42, 0x00, 0x10, 0x00,
+    ];
+
     #[test]
     fn serialize_tagged_extension() {
         let expected = &hex!("12 34 00 02 00 01");
@@ -396,10 +648,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn serialize_extension_token_binding() {
+        // version 1.0, rsa2048_pkcs1.5 and ecdsap256
+        let raw = &hex!("00 18 00 05 01 00 02 00 02");
+        let (_, ext) = parse_tls_extension(raw).expect("could not parse token binding extension");
+        let expected = TlsExtension::TokenBinding {
+            major: 1,
+            minor: 0,
+            key_params: vec![0, 2],
+        };
+        assert_eq!(ext, expected);
+        let res = gen_simple(gen_tls_extension(&ext), Vec::new())
+            .expect("could not serialize token binding extension");
+        assert_eq!(&res, raw);
+    }
+
+    #[test]
+    fn roundtrip_extension_cookie() {
+        let raw = &hex!("00 2c 00 06 00 04 de ad be ef");
+        let (_, ext) = parse_tls_extension(raw).expect("could not parse cookie extension");
+        assert_eq!(ext, TlsExtension::Cookie(&hex!("de ad be ef")));
+        let res = gen_simple(gen_tls_extension(&ext), Vec::new())
+            .expect("could not serialize cookie extension");
+        assert_eq!(&res, raw);
+    }
+
     #[test]
     fn serialize_tls_extensions() {
-        let ext = vec![TlsExtension::SNI(vec![(
-            SNIType::HostName,
+        let ext = vec![TlsExtension::SNI(vec![ServerName::host_name(
             b"www.google.com",
         )])];
 
@@ -474,7 +751,7 @@ mod tests {
 
     #[test]
     fn serialize_tls_ext() {
-        let ext = TlsExtension::SNI(vec![(SNIType::HostName, b"www.google.com")]);
+        let ext = TlsExtension::SNI(vec![ServerName::host_name(b"www.google.com")]);
 
         let res =
             gen_simple(gen_tls_extension(&ext), Vec::new()).expect("Could not serialize messages");
@@ -511,14 +788,13 @@ mod tests {
 
         let res = m.serialize().expect("Could not serialize messages");
         let v = [
-            0x01, 0x00, 0x00, 0x2d, 0x03, 0x03, // type, length, version
+            0x01, 0x00, 0x00, 0x2b, 0x03, 0x03, // type, length, version
             0xb2, 0x9d, 0xd7, 0x87, // random time
             0xff, 0x21, 0xeb, 0x04, 0xc8, 0xa5, 0x38, 0x39, // random data
             0x9a, 0xcf, 0xb7, 0xa3, 0x82, 0x1f, 0x82, 0x6c, 0x49, 0xbc, 0x8b, 0xb8, 0xa9, 0x03,
             0x0a, 0x2d, 0xce, 0x38, 0x0b, 0xf4, 0x00, // session ID
             0x00, 0x04, 0xc0, 0x30, 0xc0, 0x2c, // ciphers
             0x01, 0x00, // compression
-            0x00, 0x00, // extensions length
         ];
         assert_eq!(&v[..], &res[..]);
     }
@@ -543,14 +819,13 @@ mod tests {
         let res = gen_simple(gen_tls_messagehandshake(&m), Vec::new())
             .expect("Could not serialize message");
         let v = [
-            0x02, 0x00, 0x00, 0x28, 0x03, 0x03, // type, length, version
+            0x02, 0x00, 0x00, 0x26, 0x03, 0x03, // type, length, version
             0xb2, 0x9d, 0xd7, 0x87, // random time
             0xff, 0x21, 0xeb, 0x04, 0xc8, 0xa5, 0x38, 0x39, // random data
             0x9a, 0xcf, 0xb7, 0xa3, 0x82, 0x1f, 0x82, 0x6c, 0x49, 0xbc, 0x8b, 0xb8, 0xa9, 0x03,
             0x0a, 0x2d, 0xce, 0x38, 0x0b, 0xf4, 0x00, // session ID
             0xc0, 0x30, // cipher
             0x00, // compression
-            0x00, 0x00, // extensions length
         ];
         assert_eq!(&v[..], &res[..]);
     }
@@ -563,4 +838,177 @@ mod tests {
         let (_, record2) = parse_tls_plaintext(&res).expect("re-parsing failed");
         assert_eq!(record, record2);
     }
+
+    #[test]
+    fn roundtrip_clienthello() {
+        let (_, record) = parse_tls_plaintext(CH_DHE).expect("parsing failed");
+        let ch = match record.msg[0] {
+            TlsMessage::Handshake(TlsMessageHandshake::ClientHello(ref ch)) => ch,
+            _ => panic!("expected ClientHello"),
+        };
+        let res = ch.serialize().expect("Could not serialize ClientHello");
+        let (rem, msg) = parse_tls_message_handshake(&res).expect("re-parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(
+            msg,
+            TlsMessage::Handshake(TlsMessageHandshake::ClientHello(ch.clone()))
+        );
+    }
+
+    #[test]
+    fn roundtrip_clienthello_empty_fields() {
+        let random = &[0x55; 32];
+        let ciphers = vec![TlsCipherSuiteID(0x1301)];
+        let comp = vec![TlsCompressionID(0)];
+        // empty session ID, and empty (but present) or absent extension block
+        for ext in [Some(&b""[..]), None] {
+            let ch = TlsClientHelloContents::new(
+                0x0303,
+                random,
+                None,
+                ciphers.clone(),
+                comp.clone(),
+                ext,
+            );
+            let res = ch.serialize().expect("Could not serialize ClientHello");
+            let expected_len = 2 + 32 + 1 + 2 + 2 + 2 + if ext.is_some() { 2 } else { 0 };
+            assert_eq!(res.len(), 4 + expected_len);
+            let (_, msg) = parse_tls_message_handshake(&res).expect("re-parsing failed");
+            assert_eq!(
+                msg,
+                TlsMessage::Handshake(TlsMessageHandshake::ClientHello(ch))
+            );
+        }
+    }
+
+    #[test]
+    fn roundtrip_clienthello_extensions_builder() {
+        let kx = &[0x42; 32];
+        let ext = ClientHelloExtensionsBuilder::new()
+            .sni(b"www.example.com")
+            .alpn(&[b"h2", b"http/1.1"])
+            .supported_versions(&[TlsVersion::Tls13, TlsVersion::Tls12])
+            .supported_groups(&[NamedGroup::EcdhX25519, NamedGroup::Secp256r1])
+            .signature_algorithms(&[
+                SignatureScheme::ecdsa_secp256r1_sha256,
+                SignatureScheme::rsa_pss_rsae_sha256,
+            ])
+            .key_share(&[KeyShareEntry {
+                group: NamedGroup::EcdhX25519,
+                kx,
+            }])
+            .build()
+            .expect("could not build extensions");
+        let (rem, parsed) =
+            parse_tls_client_hello_extensions(&ext).expect("could not parse extensions");
+        assert!(rem.is_empty());
+        let key_share = &ext[ext.len() - 38..];
+        let expected = vec![
+            TlsExtension::SNI(vec![ServerName::host_name(b"www.example.com")]),
+            TlsExtension::ALPN(vec![b"h2", b"http/1.1"]),
+            TlsExtension::SupportedVersions(vec![TlsVersion::Tls13, TlsVersion::Tls12]),
+            TlsExtension::EllipticCurves(vec![NamedGroup::EcdhX25519, NamedGroup::Secp256r1]),
+            TlsExtension::SignatureAlgorithms(vec![0x0403, 0x0804]),
+            TlsExtension::KeyShare(key_share),
+        ];
+        assert_eq!(parsed, expected);
+        let (_, entries) = parse_key_share_client_hello(key_share).expect("invalid key share");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].kx, kx);
+        // use the block in a ClientHello, and re-parse the message
+        let random = &[0x55; 32];
+        let ciphers = vec![TlsCipherSuiteID(0x1301)];
+        let comp = vec![TlsCompressionID(0)];
+        let ch = TlsClientHelloContents::new(0x0303, random, None, ciphers, comp, Some(&ext));
+        let res = ch.serialize().expect("Could not serialize ClientHello");
+        let (_, msg) = parse_tls_message_handshake(&res).expect("re-parsing failed");
+        match msg {
+            TlsMessage::Handshake(TlsMessageHandshake::ClientHello(ref ch2)) => {
+                assert_eq!(ch2.ext, Some(&ext[..]));
+                assert_eq!(ch2.extensions(), Some(expected));
+            }
+            _ => panic!("expected ClientHello"),
+        }
+        assert!(ClientHelloExtensionsBuilder::new()
+            .build()
+            .expect("could not build extensions")
+            .is_empty());
+        // the supported versions list is limited to 127 versions
+        let versions = vec![TlsVersion::Tls12; 128];
+        assert!(ClientHelloExtensionsBuilder::new()
+            .supported_versions(&versions)
+            .build()
+            .is_err());
+        let ext = ClientHelloExtensionsBuilder::new()
+            .supported_versions(&versions[..127])
+            .build()
+            .expect("could not build extensions");
+        assert_eq!(ext.len(), 4 + 1 + 254);
+        // ALPN names are limited to 255 bytes
+        let name = [b'a'; 256];
+        assert!(ClientHelloExtensionsBuilder::new()
+            .alpn(&[&name])
+            .build()
+            .is_err());
+        let ext = ClientHelloExtensionsBuilder::new()
+            .alpn(&[&name[..255]])
+            .build()
+            .expect("could not build extensions");
+        assert_eq!(ext.len(), 4 + 2 + 1 + 255);
+        // key exchange data and lists are limited to 65535 bytes
+        let kx = vec![0x42; 65536];
+        let key_share = [KeyShareEntry {
+            group: NamedGroup::EcdhX25519,
+            kx: &kx,
+        }];
+        assert!(ClientHelloExtensionsBuilder::new()
+            .key_share(&key_share)
+            .build()
+            .is_err());
+        // the entry fits, but not the list of entries
+        let key_share = [KeyShareEntry {
+            group: NamedGroup::EcdhX25519,
+            kx: &kx[..65533],
+        }];
+        assert!(ClientHelloExtensionsBuilder::new()
+            .key_share(&key_share)
+            .build()
+            .is_err());
+        let names = vec![&name[..255]; 300];
+        let ext = TlsExtension::ALPN(names);
+        assert!(gen(gen_tls_extensions(&[ext]), Vec::new()).is_err());
+    }
+
+    #[test]
+    fn roundtrip_serverhello() {
+        for &bytes in &[SERVER_HELLO_TLS12, SERVER_HELLO_SSLV3] {
+            let (_, record) = parse_tls_plaintext(bytes).expect("parsing failed");
+            let sh = match record.msg[0] {
+                TlsMessage::Handshake(TlsMessageHandshake::ServerHello(ref sh)) => sh,
+                _ => panic!("expected ServerHello"),
+            };
+            let res = sh.serialize().expect("Could not serialize ServerHello");
+            assert_eq!(&res[..], &bytes[5..]);
+            let res = record.serialize().expect("Could not serialize record");
+            assert_eq!(&res[..], bytes);
+        }
+    }
+
+    #[test]
+    fn serialize_serverhello_no_extensions() {
+        let random = &[0x55; 32];
+        let mut sh = TlsServerHelloContents::new(0x0303, random, None, 0xc02f, 0, None);
+        let res = sh.serialize().expect("Could not serialize ServerHello");
+        // no extensions length after compression method
+        assert_eq!(res.len(), 4 + 2 + 32 + 1 + 2 + 1);
+        let (_, msg) = parse_tls_message_handshake(&res).expect("re-parsing failed");
+        assert_eq!(
+            msg,
+            TlsMessage::Handshake(TlsMessageHandshake::ServerHello(sh.clone()))
+        );
+        // empty, but present, extensions block
+        sh.ext = Some(&[]);
+        let res = sh.serialize().expect("Could not serialize ServerHello");
+        assert_eq!(&res[res.len() - 2..], &[0x00, 0x00]);
+    }
 }
@@ -7,34 +7,40 @@ use nom_derive::*;
 use rusticata_macros::newtype_enum;
 
 /// Hash algorithms, as defined in [RFC5246]
-#[derive(Clone, Debug, PartialEq, Eq, Nom)]
+///
+/// `Display` uses the name of the IANA registry (for ex. `sha256`), while `Debug` uses the
+/// constant name (for ex. `Sha256`).
+#[derive(Clone, PartialEq, Eq, Nom)]
 pub struct HashAlgorithm(pub u8);
 
-newtype_enum! {
-impl display HashAlgorithm {
-    None = 0,
-    Md5 = 1,
-    Sha1 = 2,
-    Sha224 = 3,
-    Sha256 = 4,
-    Sha384 = 5,
-    Sha512 = 6,
-    Intrinsic = 8, // [RFC8422]
+registry_enum! {
+impl names HashAlgorithm {
+    None = 0      => "none",
+    Md5 = 1       => "md5",
+    Sha1 = 2      => "sha1",
+    Sha224 = 3    => "sha224",
+    Sha256 = 4    => "sha256",
+    Sha384 = 5    => "sha384",
+    Sha512 = 6    => "sha512",
+    Intrinsic = 8 => "intrinsic", // [RFC8422]
 }
 }
 
 /// Signature algorithms, as defined in [RFC5246]
-#[derive(Clone, Debug, PartialEq, Eq, Nom)]
+///
+/// `Display` uses the name of the IANA registry (for ex. `ecdsa`), while `Debug` uses the
+/// constant name (for ex. `Ecdsa`).
+#[derive(Clone, PartialEq, Eq, Nom)]
 pub struct SignAlgorithm(pub u8);
 
-newtype_enum! {
-impl display SignAlgorithm {
-    Anonymous = 0,
-    Rsa = 1,
-    Dsa = 2,
-    Ecdsa = 3,
-    Ed25519 = 7, // [RFC8422]
-    Ed448 = 8, // [RFC8422]
+registry_enum! {
+impl names SignAlgorithm {
+    Anonymous = 0 => "anonymous",
+    Rsa = 1       => "rsa",
+    Dsa = 2       => "dsa",
+    Ecdsa = 3     => "ecdsa",
+    Ed25519 = 7   => "ed25519", // [RFC8422]
+    Ed448 = 8     => "ed448", // [RFC8422]
 }
 }
 
@@ -45,11 +51,11 @@ pub struct SignatureAndHashAlgorithm {
 }
 
 /// Signature algorithms, as defined in [RFC8446] 4.2.3
-#[derive(Debug, PartialEq, Eq, Nom)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Nom)]
 pub struct SignatureScheme(pub u16);
 
-newtype_enum! {
-impl display SignatureScheme {
+registry_enum! {
+impl display SignatureScheme: from_u16(u16) {
     /* RSASSA-PKCS1-v1_5 algorithms */
     rsa_pkcs1_sha256 = 0x0401,
     rsa_pkcs1_sha384 = 0x0501,
@@ -115,14 +121,14 @@ pub struct DigitallySigned<'a> {
     pub data: &'a [u8],
 }
 
-pub fn parse_digitally_signed_old(i: &[u8]) -> IResult<&[u8], DigitallySigned> {
+pub fn parse_digitally_signed_old(i: &[u8]) -> IResult<&[u8], DigitallySigned<'_>> {
     map(length_data(be_u16), |data| DigitallySigned {
         alg: None,
         data,
     })(i)
 }
 
-pub fn parse_digitally_signed(i: &[u8]) -> IResult<&[u8], DigitallySigned> {
+pub fn parse_digitally_signed(i: &[u8]) -> IResult<&[u8], DigitallySigned<'_>> {
     let (i, hash) = HashAlgorithm::parse(i)?;
     let (i, sign) = SignAlgorithm::parse(i)?;
     let (i, data) = length_data(be_u16)(i)?;
@@ -139,7 +145,7 @@ pub fn parse_content_and_signature<'a, F, T: 'a>(
     i: &'a [u8],
     fun: F,
     ext: bool,
-) -> IResult<&'a [u8], (T, DigitallySigned)>
+) -> IResult<&'a [u8], (T, DigitallySigned<'a>)>
 where
     F: Fn(&'a [u8]) -> IResult<&[u8], T>,
 {
@@ -0,0 +1,114 @@
+//! Streaming parser for TLS records
+//!
+//! When reading TLS from a TCP stream (for ex. from a PCAP), data arrives in chunks that are not
+//! aligned with records boundaries.
+
+use crate::tls::{parse_tls_raw_record, TlsRawRecord};
+use alloc::vec::Vec;
+use nom::Err;
+
+/// Error types for the TLS streaming parser
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TlsStreamError {
+    /// Buffered data is not a valid TLS record
+    ///
+    /// The parser state is not modified, and the same error will be returned until `clear` is
+    /// called.
+    InvalidRecord,
+}
+
+/// Stateful parser for TLS records, accepting arbitrary chunks of data
+///
+/// Data is added using `push`, and complete records are returned by `next_record`. Bytes of
+/// incomplete records are kept in the parser until more data is pushed.
+///
+/// # Example
+///
+/// ```rust
+/// use tls_parser::*;
+///
+/// fn handle_chunk(state: &mut TlsParserState, chunk: &[u8]) {
+///     state.push(chunk);
+///     loop {
+///         match state.next_record() {
+///             Ok(Some(record)) => {
+///                 let res = parse_tls_record_with_header(record.data, &record.hdr);
+///                 // ...
+///             }
+///             Ok(None) => break, // wait for more data
+///             Err(e) => {
+///                 println!("stream error: {:?}", e);
+///                 break;
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct TlsParserState {
+    buffer: Vec<u8>,
+    /// Offset of the first byte not yet returned as part of a record
+    pos: usize,
+    /// Number of bytes consumed (returned as records) since creation
+    consumed: usize,
+}
+
+impl TlsParserState {
+    pub fn new() -> Self {
+        TlsParserState::default()
+    }
+
+    /// Add data to the parser
+    ///
+    /// Records returned by previous calls to `next_record` are dropped from the buffer.
+    pub fn push(&mut self, data: &[u8]) {
+        if self.pos > 0 {
+            self.buffer.drain(..self.pos);
+            self.pos = 0;
+        }
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Return the next complete record, or `None` if more data is needed
+    pub fn next_record(&mut self) -> Result<Option<TlsRawRecord<'_>>, TlsStreamError> {
+        let TlsParserState {
+            buffer,
+            pos,
+            consumed,
+        } = self;
+        match parse_tls_raw_record(&buffer[*pos..]) {
+            Ok((rem, record)) => {
+                let len = buffer.len() - *pos - rem.len();
+                *pos += len;
+                *consumed += len;
+                Ok(Some(record))
+            }
+            Err(Err::Incomplete(_)) => Ok(None),
+            Err(_) => Err(TlsStreamError::InvalidRecord),
+        }
+    }
+
+    /// Tell if more data is needed to return the next record
+    pub fn needs_more_data(&self) -> bool {
+        matches!(
+            parse_tls_raw_record(&self.buffer[self.pos..]),
+            Err(Err::Incomplete(_))
+        )
+    }
+
+    /// Return the total number of bytes returned as records
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+
+    /// Return the number of buffered bytes not yet returned as records
+    pub fn buffered_len(&self) -> usize {
+        self.buffer.len() - self.pos
+    }
+
+    /// Drop all buffered data
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.pos = 0;
+    }
+}
@@ -0,0 +1,89 @@
+//! Summary of the parameters negotiated in a handshake
+//!
+//! The negotiated parameters are spread over the ClientHello and ServerHello messages (and their
+//! extensions). `summarize` correlates both messages, and returns the values most commonly used
+//! for reporting.
+
+use crate::tls::*;
+use crate::tls_ciphers::TlsCipherSuite;
+use crate::tls_ec::NamedGroup;
+use crate::tls_extensions::{
+    parse_key_share_server_hello, parse_tls_extension_alpn_content, parse_tls_extensions_iter,
+    TlsExtension, TlsExtensionType,
+};
+use nom::combinator::all_consuming;
+use nom::number::complete::be_u16;
+
+/// Parameters negotiated in a handshake
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TlsHandshakeSummary<'a> {
+    /// Negotiated version: the version of the `supported_versions` extension of the ServerHello
+    /// if present (TLS 1.3), or the version of the ServerHello
+    pub version: TlsVersion,
+    /// Cipher suite selected by the server
+    pub cipher: TlsCipherSuiteID,
+    /// Group of the key share selected by the server (TLS 1.3 only)
+    pub group: Option<NamedGroup>,
+    /// Application protocol selected by the server (ALPN)
+    pub alpn: Option<&'a [u8]>,
+    /// Host name requested by the client (SNI)
+    pub sni: Option<&'a [u8]>,
+}
+
+impl<'a> TlsHandshakeSummary<'a> {
+    /// Get the selected cipher suite, if known
+    pub fn get_cipher(&self) -> Option<&'static TlsCipherSuite> {
+        self.cipher.get_ciphersuite()
+    }
+}
+
+/// Summarize the parameters negotiated by a ClientHello and the matching ServerHello
+///
+/// Invalid or missing extensions are ignored: the corresponding fields are set to `None` (or, for
+/// `version`, to the version of the ServerHello). An invalid extension does not prevent the other
+/// extensions from being used.
+/// For TLS 1.2 and earlier versions, the group is only sent in the ServerKeyExchange message,
+/// so `group` is `None`.
+pub fn summarize<'a>(
+    client: &TlsClientHelloContents<'a>,
+    server: &TlsServerHelloContents<'a>,
+) -> TlsHandshakeSummary<'a> {
+    let mut summary = TlsHandshakeSummary {
+        version: server.version,
+        cipher: server.cipher,
+        group: None,
+        alpn: None,
+        sni: None,
+    };
+    let server_ext = server
+        .ext
+        .map(parse_tls_extensions_iter)
+        .into_iter()
+        .flatten();
+    for (ext_type, data) in server_ext.filter_map(Result::ok) {
+        match TlsExtensionType(ext_type) {
+            TlsExtensionType::SupportedVersions => {
+                let res: IResult<_, _> = all_consuming(be_u16)(data);
+                if let Ok((_, v)) = res {
+                    summary.version = TlsVersion(v);
+                }
+            }
+            TlsExtensionType::KeyShare => {
+                if let Ok((_, entry)) = parse_key_share_server_hello(data) {
+                    summary.group = Some(entry.group);
+                }
+            }
+            TlsExtensionType::ApplicationLayerProtocolNegotiation => {
+                if let Ok((_, TlsExtension::ALPN(v))) = parse_tls_extension_alpn_content(data) {
+                    summary.alpn = v.first().copied();
+                }
+            }
+            _ => (),
+        }
+    }
+    summary.sni = client
+        .find_sni()
+        .and_then(|v| v.into_iter().find(|sn| sn.is_host_name()))
+        .map(|sn| sn.name);
+    summary
+}
@@ -0,0 +1,275 @@
+mod dtls {
+    use tls_parser::*;
+
+    // DTLS 1.0 HelloVerifyRequest, epoch 0, sequence number 1
+    #[rustfmt::skip]
+static DTLS_HELLO_VERIFY_REQUEST: &[u8] = &[
+    0x16, 0xfe, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00,
+    0x23, 0x03, 0x00, 0x00, 0x17, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x17, 0xfe, 0xff, 0x14, 0x2b, 0x1b, 0x09, 0x2c, 0x6c, 0x33, 0x9d, 0x5c,
+    0x86, 0xb3, 0x9a, 0x23, 0x7c, 0x5d, 0x4e, 0x3d, 0x84, 0x28, 0x97, 0x16,
+];
+
+    #[test]
+    fn test_dtls_record_header() {
+        let (rem, hdr) =
+            parse_dtls_record_header(DTLS_HELLO_VERIFY_REQUEST).expect("parsing header failed");
+        assert_eq!(rem.len(), 0x23);
+        let expected = DTLSRecordHeader {
+            content_type: TlsRecordType::Handshake,
+            version: TlsVersion::DTls10,
+            epoch: 0,
+            sequence_number: 1,
+            length: 0x23,
+        };
+        assert_eq!(hdr, expected);
+    }
+
+    #[test]
+    fn test_dtls_record_header_epoch_seq() {
+        // epoch and sequence number share 8 bytes (16 + 48 bits)
+        let bytes = &[
+            0x17, 0xfe, 0xfd, 0x00, 0x02, 0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0x00, 0x00,
+        ];
+        let (_, hdr) = parse_dtls_record_header(bytes).expect("parsing header failed");
+        assert_eq!(hdr.content_type, TlsRecordType::ApplicationData);
+        assert_eq!(hdr.version, TlsVersion::DTls12);
+        assert_eq!(hdr.epoch, 2);
+        assert_eq!(hdr.sequence_number, 0x1234_5678_9abc);
+        assert_eq!(hdr.length, 0);
+        let s = format!("{:?}", hdr);
+        assert!(s.contains("epoch: 2"));
+        assert!(s.contains(&format!("sequence_number: {}", 0x1234_5678_9abc_u64)));
+    }
+
+    #[test]
+    fn test_dtls_record_header_incomplete() {
+        let res = parse_dtls_record_header(&DTLS_HELLO_VERIFY_REQUEST[..12]);
+        assert!(matches!(res, Err(nom::Err::Incomplete(_))));
+    }
+
+    #[test]
+    fn test_dtls_record_hello_verify_request() {
+        let (rem, record) =
+            parse_dtls_plaintext_record(DTLS_HELLO_VERIFY_REQUEST).expect("parsing record failed");
+        assert!(rem.is_empty());
+        assert_eq!(record.messages.len(), 1);
+        match record.messages[0] {
+            DTLSMessage::Handshake(ref msg) => {
+                assert_eq!(msg.msg_type, TlsHandshakeType::HelloVerifyRequest);
+                let expected = DTLSHelloVerifyRequest {
+                    server_version: TlsVersion::DTls10,
+                    cookie: &DTLS_HELLO_VERIFY_REQUEST[28..],
+                };
+                assert_eq!(
+                    msg.body,
+                    DTLSMessageHandshakeBody::HelloVerifyRequest(expected)
+                );
+            }
+            _ => panic!("expected handshake message"),
+        }
+    }
+
+    #[test]
+    fn test_dtls_handshake_header() {
+        let (rem, hdr) = parse_dtls_handshake_header(&DTLS_HELLO_VERIFY_REQUEST[13..])
+            .expect("parsing handshake header failed");
+        assert_eq!(rem.len(), 0x17);
+        let expected = DTLSHandshakeHeader {
+            msg_type: TlsHandshakeType::HelloVerifyRequest,
+            length: 0x17,
+            message_seq: 0,
+            fragment_offset: 0,
+            fragment_length: 0x17,
+        };
+        assert_eq!(hdr, expected);
+        assert!(!hdr.is_fragmented());
+    }
+
+    #[test]
+    fn test_dtls_handshake_fragment() {
+        // first 4 bytes of a 16-byte Finished message (message_seq 5)
+        let bytes = &[
+            0x14, 0x00, 0x00, 0x10, 0x00, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0xde, 0xad,
+            0xbe, 0xef,
+        ];
+        let (_, hdr) = parse_dtls_handshake_header(bytes).expect("parsing handshake header failed");
+        assert_eq!(hdr.message_seq, 5);
+        assert!(hdr.is_fragmented());
+        let (_, msg) = parse_dtls_message_handshake(bytes).expect("parsing fragment failed");
+        assert!(msg.is_fragment());
+        match msg {
+            DTLSMessage::Handshake(ref h) => {
+                assert_eq!(h.header(), hdr);
+                assert_eq!(h.body, DTLSMessageHandshakeBody::Fragment(&bytes[12..]));
+            }
+            _ => panic!("expected handshake message"),
+        }
+    }
+} // mod dtls
+
+mod dtls_fragment {
+    use tls_parser::*;
+
+    static BODY: &[u8] = b"0123456789abcdef";
+
+    fn header(offset: u32, len: u32) -> DTLSHandshakeHeader {
+        DTLSHandshakeHeader {
+            msg_type: TlsHandshakeType::Certificate,
+            length: BODY.len() as u32,
+            message_seq: 2,
+            fragment_offset: offset,
+            fragment_length: len,
+        }
+    }
+
+    fn add(
+        r: &mut DTLSFragmentReassembler,
+        offset: usize,
+        len: usize,
+    ) -> Result<Option<DTLSReassembledMessage>, DTLSReassemblyError> {
+        r.add_fragment(
+            &header(offset as u32, len as u32),
+            &BODY[offset..offset + len],
+        )
+    }
+
+    #[test]
+    fn test_dtls_reassembly_in_order() {
+        let mut r = DTLSFragmentReassembler::new();
+        assert_eq!(add(&mut r, 0, 6), Ok(None));
+        assert_eq!(add(&mut r, 6, 6), Ok(None));
+        assert_eq!(r.pending_messages().collect::<Vec<_>>(), vec![2]);
+        let msg = add(&mut r, 12, 4).expect("reassembly failed");
+        let expected = DTLSReassembledMessage {
+            msg_type: TlsHandshakeType::Certificate,
+            message_seq: 2,
+            body: BODY.to_vec(),
+        };
+        assert_eq!(msg, Some(expected));
+        assert!(r.is_empty());
+    }
+
+    #[test]
+    fn test_dtls_reassembly_out_of_order() {
+        let mut r = DTLSFragmentReassembler::new();
+        assert_eq!(add(&mut r, 12, 4), Ok(None));
+        assert_eq!(add(&mut r, 0, 4), Ok(None));
+        assert_eq!(r.missing_ranges(2), Some(vec![(4, 12)]));
+        assert_eq!(add(&mut r, 8, 4), Ok(None));
+        assert_eq!(r.missing_ranges(2), Some(vec![(4, 8)]));
+        let msg = add(&mut r, 4, 4).expect("reassembly failed");
+        assert_eq!(msg.map(|m| m.body), Some(BODY.to_vec()));
+        assert_eq!(r.missing_ranges(2), None);
+    }
+
+    #[test]
+    fn test_dtls_reassembly_overlapping() {
+        let mut r = DTLSFragmentReassembler::new();
+        assert_eq!(add(&mut r, 0, 8), Ok(None));
+        // duplicate
+        assert_eq!(add(&mut r, 0, 8), Ok(None));
+        // overlapping
+        assert_eq!(add(&mut r, 4, 8), Ok(None));
+        // overlapping with different data
+        let res = r.add_fragment(&header(10, 4), b"XXXX");
+        assert_eq!(res, Err(DTLSReassemblyError::ConflictingData));
+        let msg = add(&mut r, 10, 6).expect("reassembly failed");
+        assert_eq!(msg.map(|m| m.body), Some(BODY.to_vec()));
+    }
+
+    #[test]
+    fn test_dtls_reassembly_invalid() {
+        let mut r = DTLSFragmentReassembler::new().with_max_message_len(8);
+        let res = add(&mut r, 0, 4);
+        assert_eq!(res, Err(DTLSReassemblyError::MessageTooLarge));
+        let mut r = DTLSFragmentReassembler::new();
+        let res = r.add_fragment(&header(14, 4), b"abcd");
+        assert_eq!(res, Err(DTLSReassemblyError::OutOfBounds));
+        let res = r.add_fragment(&header(0, 4), b"abc");
+        assert_eq!(res, Err(DTLSReassemblyError::InvalidFragmentLength));
+        assert_eq!(add(&mut r, 0, 4), Ok(None));
+        let mut hdr = header(4, 4);
+        hdr.length = 20;
+        let res = r.add_fragment(&hdr, &BODY[4..8]);
+        assert_eq!(res, Err(DTLSReassemblyError::InconsistentHeader));
+    }
+
+    #[test]
+    fn test_dtls_reassembly_limits() {
+        // one small fragment for each new message sequence
+        let mut r = DTLSFragmentReassembler::new();
+        let mut hdr = header(0, 1);
+        let mut res = Ok(None);
+        for seq in 0..=u16::MAX {
+            hdr.message_seq = seq;
+            res = r.add_fragment(&hdr, &BODY[..1]);
+            if res.is_err() {
+                break;
+            }
+        }
+        assert_eq!(res, Err(DTLSReassemblyError::TooManyPendingMessages));
+        assert_eq!(
+            r.pending_messages().count(),
+            DTLS_DEFAULT_MAX_PENDING_MESSAGES
+        );
+        // fragments of pending messages are still accepted
+        hdr.message_seq = 0;
+        assert_eq!(r.add_fragment(&hdr, &BODY[..1]), Ok(None));
+        // total buffer size
+        let mut r = DTLSFragmentReassembler::new().with_max_buffered_len(40);
+        for seq in 0..2 {
+            hdr.message_seq = seq;
+            assert_eq!(r.add_fragment(&hdr, &BODY[..1]), Ok(None));
+        }
+        hdr.message_seq = 2;
+        let res = r.add_fragment(&hdr, &BODY[..1]);
+        assert_eq!(res, Err(DTLSReassemblyError::BufferLimitExceeded));
+        // completing a message releases its buffer
+        hdr.message_seq = 0;
+        hdr.fragment_offset = 1;
+        hdr.fragment_length = 15;
+        let msg = r.add_fragment(&hdr, &BODY[1..]).expect("reassembly failed");
+        assert!(msg.is_some());
+        hdr.message_seq = 2;
+        assert_eq!(r.add_fragment(&hdr, &BODY[1..]), Ok(None));
+    }
+
+    #[test]
+    fn test_dtls_reassembly_parse() {
+        let mut r = DTLSFragmentReassembler::new();
+        // HelloVerifyRequest, split in 2 fragments
+        let body = &[0xfe, 0xff, 0x04, 0x01, 0x02, 0x03, 0x04];
+        let mut hdr = DTLSHandshakeHeader {
+            msg_type: TlsHandshakeType::HelloVerifyRequest,
+            length: body.len() as u32,
+            message_seq: 0,
+            fragment_offset: 0,
+            fragment_length: 3,
+        };
+        assert_eq!(r.add_fragment(&hdr, &body[..3]), Ok(None));
+        hdr.fragment_offset = 3;
+        hdr.fragment_length = 4;
+        let msg = r
+            .add_fragment(&hdr, &body[3..])
+            .expect("reassembly failed")
+            .expect("message is not complete");
+        let bytes = msg.to_handshake_bytes();
+        let (rem, msg) = parse_dtls_message_handshake(&bytes).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert!(!msg.is_fragment());
+        match msg {
+            DTLSMessage::Handshake(ref h) => {
+                let expected = DTLSHelloVerifyRequest {
+                    server_version: TlsVersion::DTls10,
+                    cookie: &body[3..],
+                };
+                assert_eq!(
+                    h.body,
+                    DTLSMessageHandshakeBody::HelloVerifyRequest(expected)
+                );
+            }
+            _ => panic!("expected handshake message"),
+        }
+    }
+} // mod dtls_fragment
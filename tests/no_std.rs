@@ -0,0 +1,44 @@
+//! Smoke test for `no_std` builds
+//!
+//! This test only uses `core` and `alloc`, and can be run with
+//! `cargo test --no-default-features --test no_std`.
+
+#![no_std]
+
+extern crate alloc;
+
+mod no_std {
+    use alloc::format;
+    use tls_parser::*;
+
+    #[rustfmt::skip]
+static CLIENT_HELLO: &[u8] = &[
+    0x16, 0x03, 0x01, 0x00, 0x36, 0x01, 0x00, 0x00, 0x32, 0x03, 0x03,
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+    0x00, 0x00, 0x02, 0x13, 0x01, 0x01, 0x00, 0x00, 0x07, 0x00, 0x2b, 0x00, 0x03, 0x02, 0x03, 0x04,
+];
+
+    #[test]
+    fn test_no_std_client_hello() {
+        let (rem, record) = parse_tls_plaintext(CLIENT_HELLO).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(record.hdr.record_type, TlsRecordType::Handshake);
+        let ch = match record.msg[0] {
+            TlsMessage::Handshake(TlsMessageHandshake::ClientHello(ref ch)) => ch,
+            _ => panic!("expected ClientHello"),
+        };
+        assert_eq!(ch.ciphers, [TlsCipherSuiteID(0x1301)]);
+        let (_, exts) = parse_tls_client_hello_extensions(ch.ext.unwrap()).expect("extensions");
+        assert_eq!(
+            exts,
+            [TlsExtension::SupportedVersions(alloc::vec![
+                TlsVersion::Tls13
+            ])]
+        );
+        // Debug and Display impls only rely on core::fmt
+        let s = format!("{:?}", exts[0]);
+        assert!(s.contains("Tls13"));
+        assert_eq!(format!("{}", ch.version), "Tls12");
+    }
+} // mod no_std
@@ -0,0 +1,97 @@
+mod ssl2 {
+    use tls_parser::*;
+
+    // SSL 2.0 compatible ClientHello (openssl s_client -ssl2)
+    #[rustfmt::skip]
+static SSL2_CLIENT_HELLO: &[u8] = &[
+    0x80, 0x2e, 0x01, 0x00, 0x02, 0x00, 0x15, 0x00, 0x00, 0x00, 0x10, 0x07,
+    0x00, 0xc0, 0x05, 0x00, 0x80, 0x03, 0x00, 0x80, 0x01, 0x00, 0x80, 0x06,
+    0x00, 0x40, 0x04, 0x00, 0x80, 0x02, 0x00, 0x80, 0xd5, 0x1a, 0x6c, 0x0e,
+    0x8c, 0x43, 0x11, 0x3e, 0x1d, 0x9f, 0x20, 0x57, 0x7a, 0x0b, 0x52, 0xe3,
+];
+
+    // SSL 2.0 ClientHello advertising TLS 1.0, with TLS cipher suites
+    #[rustfmt::skip]
+static SSL2_COMPAT_CLIENT_HELLO: &[u8] = &[
+    0x80, 0x25, 0x01, 0x03, 0x01, 0x00, 0x0c, 0x00, 0x00, 0x00, 0x10, 0x00,
+    0x00, 0x2f, 0x00, 0x00, 0x35, 0x00, 0x00, 0xff, 0x01, 0x00, 0x80, 0x4d,
+    0x46, 0x2e, 0x6a, 0x9b, 0x01, 0x73, 0xe0, 0x55, 0x2c, 0x81, 0x3b, 0x00,
+    0x5a, 0xc7, 0x91,
+];
+
+    #[test]
+    fn test_ssl2_client_hello() {
+        let (rem, hello) = parse_ssl2_client_hello(SSL2_CLIENT_HELLO).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(hello.version, TlsVersion::Ssl20);
+        let expected = &[
+            Ssl2CipherSpec::Des192Ede3CbcWithMd5,
+            Ssl2CipherSpec::Idea128CbcWithMd5,
+            Ssl2CipherSpec::Rc2_128CbcWithMd5,
+            Ssl2CipherSpec::Rc4_128WithMd5,
+            Ssl2CipherSpec::Des64CbcWithMd5,
+            Ssl2CipherSpec::Rc2_128CbcExport40WithMd5,
+            Ssl2CipherSpec::Rc4_128Export40WithMd5,
+        ];
+        assert_eq!(hello.cipher_specs, expected);
+        assert!(hello.session_id.is_empty());
+        assert_eq!(hello.challenge, &SSL2_CLIENT_HELLO[32..]);
+        assert!(hello
+            .cipher_specs
+            .iter()
+            .all(|c| c.tls_cipher_suite_id().is_none()));
+    }
+
+    #[test]
+    fn test_ssl2_compat_client_hello() {
+        let (rem, hello) =
+            parse_ssl2_client_hello(SSL2_COMPAT_CLIENT_HELLO).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(hello.version, TlsVersion::Tls10);
+        let ciphers: Vec<_> = hello
+            .cipher_specs
+            .iter()
+            .filter_map(|c| c.tls_cipher_suite_id())
+            .collect();
+        let expected = &[
+            TlsCipherSuiteID(0x002f),
+            TlsCipherSuiteID(0x0035),
+            TlsCipherSuiteID(0x00ff),
+        ];
+        assert_eq!(ciphers, expected);
+        assert_eq!(hello.cipher_specs[3], Ssl2CipherSpec::Rc4_128WithMd5);
+        assert_eq!(hello.challenge.len(), 16);
+    }
+
+    #[test]
+    fn test_ssl2_client_hello_tls_record() {
+        // TLS record (Alert): the high bit of the first byte is not set
+        let bytes = &[0x15, 0x03, 0x01, 0x00, 0x02, 0x01, 0x00];
+        assert!(parse_ssl2_client_hello(bytes).is_err());
+        // TLS record header followed by a ClientHello
+        let mut v = vec![0x16, 0x03, 0x01, 0x00, 0x2e];
+        v.extend_from_slice(&SSL2_CLIENT_HELLO[2..]);
+        assert!(parse_ssl2_client_hello(&v).is_err());
+        assert!(parse_tls_plaintext(SSL2_CLIENT_HELLO).is_err());
+    }
+
+    #[test]
+    fn test_ssl2_client_hello_invalid() {
+        // invalid cipher specs length
+        let mut v = SSL2_CLIENT_HELLO.to_vec();
+        v[6] = 0x14;
+        assert!(parse_ssl2_client_hello(&v).is_err());
+        // not a ClientHello
+        let mut v = SSL2_CLIENT_HELLO.to_vec();
+        v[2] = 0x04;
+        assert!(parse_ssl2_client_hello(&v).is_err());
+        // trailing byte in the record
+        let mut v = SSL2_CLIENT_HELLO.to_vec();
+        v[1] = 0x2f;
+        v.push(0x00);
+        assert!(parse_ssl2_client_hello(&v).is_err());
+        // incomplete
+        let res = parse_ssl2_client_hello(&SSL2_CLIENT_HELLO[..20]);
+        assert!(matches!(res, Err(nom::Err::Incomplete(_))));
+    }
+} // mod ssl2
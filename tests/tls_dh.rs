@@ -54,6 +54,30 @@ static ECDHE_PARAMS: &[u8] = &[
         assert_eq!(res, Ok((empty, (expected1, expected2))));
     }
 
+    #[test]
+    fn test_tls_server_ecdh_params() {
+        let bytes = ECDHE_PARAMS;
+        let (rem, ske) =
+            parse_server_ecdh_params(bytes, TlsCipherKx::Ecdhe).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(
+            ske.params.curve_params.params_content,
+            ECParametersContent::NamedGroup(NamedGroup::Secp521r1)
+        );
+        assert_eq!(ske.params.public.point, &bytes[4..137]);
+        let signature = ske.signature.expect("missing signature");
+        assert_eq!(signature.data, &bytes[141..]);
+        // anonymous key exchange: the signature is not parsed
+        let (rem, ske) =
+            parse_server_ecdh_params(bytes, TlsCipherKx::Aecdh).expect("parsing failed");
+        assert_eq!(rem, &bytes[137..]);
+        assert!(ske.signature.is_none());
+        // not an ECDH key exchange
+        assert!(parse_server_ecdh_params(bytes, TlsCipherKx::Dhe).is_err());
+        // static ECDH: no ServerKeyExchange message
+        assert!(parse_server_ecdh_params(bytes, TlsCipherKx::Ecdh).is_err());
+    }
+
     #[rustfmt::skip]
 static DHE_PARAMS: &[u8] = &[
     0x01, 0x00, 0xad, 0x10, 0x7e, 0x1e, 0x91, 0x23, 0xa9, 0xd0, 0xd6, 0x60, 0xfa, 0xa7, 0x95, 0x59,
@@ -142,4 +166,34 @@ static DHE_PARAMS: &[u8] = &[
         let res = pair(parse_dh_params, parse_digitally_signed)(bytes);
         assert_eq!(res, Ok((empty, (expected1, expected2))));
     }
+
+    #[test]
+    fn test_tls_server_dhe_params() {
+        let bytes = DHE_PARAMS;
+        let (rem, ske) = parse_server_dhe_params(bytes, true).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(ske.params.dh_p, &bytes[2..258]);
+        assert_eq!(ske.params.dh_g, &bytes[260..516]);
+        assert_eq!(ske.params.dh_ys, &bytes[518..774]);
+        let signature = ske.signature.expect("missing signature");
+        assert_eq!(
+            signature.alg,
+            Some(SignatureAndHashAlgorithm {
+                hash: HashAlgorithm::Sha512,
+                sign: SignAlgorithm::Rsa,
+            })
+        );
+        assert_eq!(signature.data, &bytes[778..]);
+        // truncated signature
+        assert!(parse_server_dhe_params(&bytes[..bytes.len() - 1], true).is_err());
+    }
+
+    #[test]
+    fn test_tls_server_dhe_params_anon() {
+        let bytes = &DHE_PARAMS[..774];
+        let (rem, ske) = parse_server_dhe_params(bytes, false).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(ske.params.dh_ys, &bytes[518..]);
+        assert!(ske.signature.is_none());
+    }
 } // mod tls_dh
@@ -0,0 +1,176 @@
+mod tls_error {
+    use nom::error::ErrorKind;
+    use nom::Needed;
+    use tls_parser::*;
+
+    // ServerHelloDone
+    #[rustfmt::skip]
+static RECORD_SHD: &[u8] = &[
+    0x16, 0x03, 0x03, 0x00, 0x04, 0x0e, 0x00, 0x00, 0x00,
+];
+
+    #[test]
+    fn test_ctx_valid_record() {
+        let (rem, record) = parse_tls_plaintext_ctx(RECORD_SHD).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(
+            record.msg,
+            vec![TlsMessage::Handshake(TlsMessageHandshake::ServerDone(&[]))]
+        );
+    }
+
+    #[test]
+    fn test_ctx_incomplete_record() {
+        assert_eq!(
+            parse_tls_plaintext_ctx(&RECORD_SHD[..7]),
+            Err(TlsParseError::Incomplete(Needed::new(2)))
+        );
+    }
+
+    #[test]
+    fn test_ctx_invalid_record_type() {
+        let input = &[0x42, 0x03, 0x03, 0x00, 0x01, 0x01];
+        assert_eq!(
+            parse_tls_plaintext_ctx(input),
+            Err(TlsParseError::InvalidRecordType(0x42))
+        );
+    }
+
+    #[test]
+    fn test_ctx_unsupported_record_version() {
+        let input = &[0x14, 0x05, 0x00, 0x00, 0x01, 0x01];
+        assert_eq!(
+            parse_tls_raw_record_ctx(input),
+            Err(TlsParseError::UnsupportedVersion(0x0500))
+        );
+    }
+
+    #[test]
+    fn test_ctx_record_too_large() {
+        let input = &[0x17, 0x03, 0x03, 0xff, 0xff];
+        assert_eq!(
+            parse_tls_raw_record_ctx(input),
+            Err(TlsParseError::RecordTooLarge(0xffff))
+        );
+    }
+
+    #[test]
+    fn test_ctx_truncated_handshake() {
+        // declared length is 8, but the record only contains 4 bytes
+        let input = &[
+            0x16, 0x03, 0x03, 0x00, 0x08, 0x0e, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(
+            parse_tls_plaintext_ctx(input),
+            Err(TlsParseError::TruncatedHandshake)
+        );
+    }
+
+    #[test]
+    fn test_ctx_unknown_handshake_type() {
+        let input = &[0x42, 0x00, 0x00, 0x00];
+        assert_eq!(
+            parse_tls_message_handshake_ctx(input),
+            Err(TlsParseError::UnknownHandshakeType(0x42))
+        );
+    }
+
+    #[test]
+    fn test_ctx_server_hello_unsupported_version() {
+        let input = &[0x02, 0x00, 0x00, 0x02, 0x03, 0x07];
+        assert_eq!(
+            parse_tls_message_handshake_ctx(input),
+            Err(TlsParseError::UnsupportedVersion(0x0307))
+        );
+    }
+
+    #[test]
+    fn test_ctx_invalid_message() {
+        // KeyUpdate with invalid request_update value
+        let input = &[0x18, 0x00, 0x00, 0x01, 0x05];
+        assert_eq!(
+            parse_tls_message_handshake_ctx(input),
+            Err(TlsParseError::InvalidMessage(ErrorKind::Verify))
+        );
+    }
+
+    #[test]
+    fn test_ctx_extension_length_mismatch() {
+        // SNI extension, declared length 0x10 but only 2 bytes of content
+        let input = &[0x00, 0x00, 0x00, 0x10, 0x00, 0x00];
+        assert_eq!(
+            parse_tls_extensions_ctx(input),
+            Err(TlsParseError::ExtensionLengthMismatch)
+        );
+        // truncated extension header
+        let input = &[0x00, 0x17, 0x00, 0x00, 0x00];
+        assert_eq!(
+            parse_tls_extensions_ctx(input),
+            Err(TlsParseError::ExtensionLengthMismatch)
+        );
+    }
+
+    #[test]
+    fn test_ctx_extensions() {
+        let input = &[0x00, 0x17, 0x00, 0x00, 0x00, 0x16, 0x00, 0x00];
+        assert_eq!(
+            parse_tls_extensions_ctx(input),
+            Ok(vec![
+                TlsExtension::ExtendedMasterSecret,
+                TlsExtension::EncryptThenMac
+            ])
+        );
+    }
+
+    #[test]
+    fn test_record_version_consistency() {
+        let hdr = |v| TlsRecordHeader {
+            record_type: TlsRecordType::ApplicationData,
+            version: TlsVersion(v),
+            len: 0,
+        };
+        // TLS 1.3 pins the record version to TLS 1.2
+        assert_eq!(
+            check_record_version(&hdr(0x0303), TlsVersion::Tls13),
+            Ok(())
+        );
+        assert_eq!(
+            check_record_version(&hdr(0x0304), TlsVersion::Tls13),
+            Err(TlsParseError::UnexpectedRecordVersion(0x0304))
+        );
+        assert_eq!(
+            check_record_version(&hdr(0x0301), TlsVersion::Tls13),
+            Err(TlsParseError::UnexpectedRecordVersion(0x0301))
+        );
+        assert_eq!(
+            check_record_version(&hdr(0x0303), TlsVersion::Tls13Draft22),
+            Ok(())
+        );
+        // drafts before 22 pin the record version to TLS 1.0
+        assert_eq!(
+            check_record_version(&hdr(0x0301), TlsVersion::Tls13Draft18),
+            Ok(())
+        );
+        assert_eq!(
+            check_record_version(&hdr(0x0303), TlsVersion::Tls13Draft21),
+            Err(TlsParseError::UnexpectedRecordVersion(0x0303))
+        );
+        // other versions use the negotiated version
+        assert_eq!(
+            check_record_version(&hdr(0x0303), TlsVersion::Tls12),
+            Ok(())
+        );
+        assert_eq!(
+            check_record_version(&hdr(0x0301), TlsVersion::Tls10),
+            Ok(())
+        );
+        assert_eq!(
+            check_record_version(&hdr(0x0301), TlsVersion::Tls12),
+            Err(TlsParseError::UnexpectedRecordVersion(0x0301))
+        );
+        assert_eq!(
+            TlsParseError::UnexpectedRecordVersion(0x0301).to_string(),
+            "unexpected record version 0x0301"
+        );
+    }
+} // mod tls_error
@@ -5,6 +5,8 @@ extern crate nom;
 extern crate tls_parser;
 
 mod tls_extensions {
+    use nom::error::ErrorKind;
+    use nom::Err;
     use tls_parser::*;
 
     #[rustfmt::skip]
@@ -23,7 +25,11 @@ static CLIENT_EXTENSIONS1: &[u8] = &[
     fn test_tls_extensions() {
         let empty = &b""[..];
         let bytes = CLIENT_EXTENSIONS1;
-        let ec_point_formats = &[0, 1, 2];
+        let ec_point_formats = vec![
+            EcPointFormat::Uncompressed,
+            EcPointFormat::AnsiX962CompressedPrime,
+            EcPointFormat::AnsiX962CompressedChar2,
+        ];
         let ext1 = &[0, 0, 0, 0];
         let ecc: Vec<_> = [23, 25, 28, 27, 24, 26, 22, 14, 13, 11, 12, 9, 10]
             .iter()
@@ -32,7 +38,7 @@ static CLIENT_EXTENSIONS1: &[u8] = &[
         let expected = Ok((
             empty,
             vec![
-                TlsExtension::SNI(vec![(SNIType::HostName, b"www.google.com")]),
+                TlsExtension::SNI(vec![ServerName::host_name(b"www.google.com")]),
                 TlsExtension::EcPointFormats(ec_point_formats),
                 TlsExtension::EllipticCurves(ecc),
                 TlsExtension::SessionTicket(empty),
@@ -41,7 +47,7 @@ static CLIENT_EXTENSIONS1: &[u8] = &[
                     0x0302, 0x0303, 0x0201, 0x0202, 0x0203,
                 ]),
                 TlsExtension::StatusRequest(Some((CertificateStatusType::OCSP, ext1))),
-                TlsExtension::Heartbeat(1),
+                TlsExtension::Heartbeat(HeartbeatMode::PeerAllowedToSend),
             ],
         ));
 
@@ -50,17 +56,59 @@ static CLIENT_EXTENSIONS1: &[u8] = &[
         assert_eq!(res, expected);
     }
 
+    #[test]
+    fn test_tls_extension_sni_first_hostname() {
+        let (_, ext) = parse_tls_extension(CLIENT_EXTENSIONS1).expect("could not parse SNI");
+        assert_eq!(ext.first_hostname(), Some(&b"www.google.com"[..]));
+        if let TlsExtension::SNI(ref v) = ext {
+            assert_eq!(v[0].as_str(), Some("www.google.com"));
+        }
+        // empty SNI (sent by servers)
+        let (_, ext) = parse_tls_extension(&[0x00, 0x00, 0x00, 0x00]).expect("empty SNI");
+        assert_eq!(ext, TlsExtension::SNI(vec![]));
+        assert_eq!(ext.first_hostname(), None);
+        // not a SNI extension
+        assert_eq!(
+            TlsExtension::Heartbeat(HeartbeatMode::PeerAllowedToSend).first_hostname(),
+            None
+        );
+    }
+
     #[test]
     fn test_tls_extension_max_fragment_length() {
         let empty = &b""[..];
         let bytes = &[0x00, 0x01, 0x00, 0x01, 0x04];
-        let expected = Ok((empty, TlsExtension::MaxFragmentLength(4)));
+        let expected = Ok((
+            empty,
+            TlsExtension::MaxFragmentLength(MaxFragmentLength::Len4096),
+        ));
 
         let res = parse_tls_extension(bytes);
 
         assert_eq!(res, expected);
     }
 
+    #[test]
+    fn test_tls_extension_max_fragment_length_bytes() {
+        let values = [
+            (1, MaxFragmentLength::Len512, 512),
+            (2, MaxFragmentLength::Len1024, 1024),
+            (3, MaxFragmentLength::Len2048, 2048),
+            (4, MaxFragmentLength::Len4096, 4096),
+        ];
+        for (code, mfl, len) in values {
+            let bytes = &[0x00, 0x01, 0x00, 0x01, code];
+            let (_, ext) = parse_tls_extension(bytes).expect("parsing failed");
+            assert_eq!(ext, TlsExtension::MaxFragmentLength(mfl));
+            assert_eq!(mfl.bytes(), Some(len));
+        }
+        assert_eq!(MaxFragmentLength(0).bytes(), None);
+        assert_eq!(MaxFragmentLength(5).bytes(), None);
+        // extension data must be exactly 1 byte
+        assert!(parse_tls_extension(&[0x00, 0x01, 0x00, 0x00]).is_err());
+        assert!(parse_tls_extension(&[0x00, 0x01, 0x00, 0x02, 0x01, 0x02]).is_err());
+    }
+
     #[test]
     fn test_tls_extension_alpn() {
         let empty = &b""[..];
@@ -109,6 +157,376 @@ static CLIENT_EXTENSIONS1: &[u8] = &[
         assert_eq!(res, expected);
     }
 
+    #[test]
+    fn test_tls_extension_empty_strict() {
+        let empty = &b""[..];
+        let exts = [
+            (0x16, TlsExtension::EncryptThenMac),
+            (0x17, TlsExtension::ExtendedMasterSecret),
+            (0x31, TlsExtension::PostHandshakeAuth),
+            (0x3374, TlsExtension::NextProtocolNegotiation),
+        ];
+        for (ext_type, expected) in exts {
+            let [t0, t1] = u16::to_be_bytes(ext_type);
+            let bytes = &[t0, t1, 0x00, 0x00];
+            assert_eq!(parse_tls_extension(bytes), Ok((empty, expected.clone())));
+            assert_eq!(
+                parse_tls_client_hello_extension(bytes),
+                Ok((empty, expected.clone()))
+            );
+            if ext_type != 0x31 {
+                assert_eq!(
+                    parse_tls_server_hello_extension(bytes),
+                    Ok((empty, expected))
+                );
+            }
+            // a non-empty body is invalid
+            let bytes = &[t0, t1, 0x00, 0x01, 0x00];
+            assert!(parse_tls_extension(bytes).is_err());
+            assert!(parse_tls_client_hello_extension(bytes).is_err());
+            if ext_type != 0x31 {
+                assert!(parse_tls_server_hello_extension(bytes).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn test_tls_extension_post_handshake_auth() {
+        let empty = &b""[..];
+        // extended_master_secret, post_handshake_auth
+        let bytes = &[0x00, 0x17, 0x00, 0x00, 0x00, 0x31, 0x00, 0x00];
+        let expected = vec![
+            TlsExtension::ExtendedMasterSecret,
+            TlsExtension::PostHandshakeAuth,
+        ];
+        let res = parse_tls_client_hello_extensions(bytes);
+        assert_eq!(res, Ok((empty, expected)));
+        let ext = TlsExtensionType::from(&TlsExtension::PostHandshakeAuth);
+        assert_eq!(ext, TlsExtensionType::PostHandshakeAuth);
+        // malformed: non-empty body
+        let bytes = &[0x00, 0x31, 0x00, 0x02, 0x00, 0x00];
+        assert!(parse_tls_client_hello_extension(bytes).is_err());
+        // only sent by clients
+        let bytes = &[0x00, 0x31, 0x00, 0x00];
+        let expected = TlsExtension::Unknown(TlsExtensionType::PostHandshakeAuth, empty);
+        assert_eq!(
+            parse_tls_server_hello_extension(bytes),
+            Ok((empty, expected))
+        );
+    }
+
+    #[test]
+    fn test_tls_extension_oid_filters() {
+        let empty = &b""[..];
+        // extendedKeyUsage: serverAuth
+        let bytes = &[
+            0x00, 0x30, 0x00, 0x14, 0x00, 0x12, 0x03, 0x55, 0x1d, 0x25, 0x00, 0x0c, 0x30, 0x0a,
+            0x06, 0x08, 0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x01,
+        ];
+        let expected = TlsExtension::OidFilters(vec![OidFilter {
+            cert_ext_oid: &bytes[7..10],
+            cert_ext_val: &bytes[12..],
+        }]);
+        assert_eq!(parse_tls_extension(bytes), Ok((empty, expected)));
+        // empty list
+        let bytes = &[0x00, 0x30, 0x00, 0x02, 0x00, 0x00];
+        let expected = TlsExtension::OidFilters(vec![]);
+        assert_eq!(parse_tls_extension(bytes), Ok((empty, expected)));
+        // empty OID
+        let bytes = &[0x00, 0x30, 0x00, 0x05, 0x00, 0x03, 0x00, 0x00, 0x00];
+        assert!(parse_tls_extension(bytes).is_err());
+        // truncated filter
+        let bytes = &[0x00, 0x30, 0x00, 0x05, 0x00, 0x03, 0x01, 0x55, 0x00];
+        assert!(parse_tls_extension(bytes).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_connection_id() {
+        let empty = &b""[..];
+        let bytes = &[0x00, 0x36, 0x00, 0x05, 0x04, 0xde, 0xad, 0xbe, 0xef];
+        let expected = TlsExtension::ConnectionId(&bytes[5..]);
+        assert_eq!(parse_tls_extension(bytes), Ok((empty, expected.clone())));
+        assert_eq!(
+            parse_tls_client_hello_extension(bytes),
+            Ok((empty, expected.clone()))
+        );
+        assert_eq!(
+            parse_tls_server_hello_extension(bytes),
+            Ok((empty, expected))
+        );
+        // empty CID
+        let bytes = &[0x00, 0x36, 0x00, 0x01, 0x00];
+        let expected = TlsExtension::ConnectionId(empty);
+        assert_eq!(parse_tls_extension(bytes), Ok((empty, expected)));
+        // CID length does not match the extension length
+        let bytes = &[0x00, 0x36, 0x00, 0x03, 0x01, 0xde, 0xad];
+        assert!(parse_tls_extension(bytes).is_err());
+        let bytes = &[0x00, 0x36, 0x00, 0x02, 0x02, 0xde];
+        assert!(parse_tls_extension(bytes).is_err());
+        let bytes = &[0x00, 0x36, 0x00, 0x00];
+        assert!(parse_tls_extension(bytes).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_truncated_hmac() {
+        let empty = &b""[..];
+        let bytes = &[0x00, 0x04, 0x00, 0x00];
+        let expected = TlsExtension::TruncatedHmac;
+        assert_eq!(parse_tls_extension(bytes), Ok((empty, expected.clone())));
+        assert_eq!(
+            parse_tls_client_hello_extension(bytes),
+            Ok((empty, expected.clone()))
+        );
+        assert_eq!(
+            parse_tls_server_hello_extension(bytes),
+            Ok((empty, expected))
+        );
+        let bytes = &[0x00, 0x04, 0x00, 0x01, 0x00];
+        assert!(parse_tls_extension(bytes).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_user_mapping() {
+        let empty = &b""[..];
+        // one UPN_DOMAIN_HINT entry
+        let bytes = &[0x00, 0x06, 0x00, 0x02, 0x01, 0x40];
+        let expected = TlsExtension::UserMapping(vec![64]);
+        assert_eq!(parse_tls_extension(bytes), Ok((empty, expected.clone())));
+        assert_eq!(
+            parse_tls_client_hello_extension(bytes),
+            Ok((empty, expected))
+        );
+        // server acknowledgement
+        let bytes = &[0x00, 0x06, 0x00, 0x00];
+        let expected = TlsExtension::UserMapping(vec![]);
+        assert_eq!(
+            parse_tls_server_hello_extension(bytes),
+            Ok((empty, expected))
+        );
+        // empty list, and list length not matching the extension length
+        let bytes = &[0x00, 0x06, 0x00, 0x01, 0x00];
+        assert!(parse_tls_extension(bytes).is_err());
+        let bytes = &[0x00, 0x06, 0x00, 0x02, 0x02, 0x40];
+        assert!(parse_tls_extension(bytes).is_err());
+        let bytes = &[0x00, 0x06, 0x00, 0x03, 0x01, 0x40, 0x40];
+        assert!(parse_tls_extension(bytes).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_alpn_protocol_strings() {
+        let ext = TlsExtension::ALPN(vec![b"h2", b"\xffh3\x00", b"http/1.1"]);
+        assert_eq!(
+            ext.protocol_strings(),
+            vec!["h2", "\\xffh3\\x00", "http/1.1"]
+        );
+        assert!(TlsExtension::EncryptThenMac.protocol_strings().is_empty());
+    }
+
+    #[test]
+    fn test_tls_extension_padding() {
+        let empty = &b""[..];
+        let bytes = &[0x00, 0x15, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00];
+        let (rem, ext) = parse_tls_extension(bytes).expect("parsing padding failed");
+        assert_eq!(rem, empty);
+        assert_eq!(ext.padding(), Some(&bytes[4..]));
+        assert_eq!(ext.zero_padding_len(), Some(4));
+        // non-zero padding is accepted by the parser, but rejected by the strict accessor
+        let bytes = &[0x00, 0x15, 0x00, 0x04, 0x00, 0x00, 0x41, 0x00];
+        let (_, ext) = parse_tls_extension(bytes).expect("parsing padding failed");
+        assert_eq!(ext.padding(), Some(&bytes[4..]));
+        assert_eq!(ext.zero_padding_len(), None);
+        // empty padding
+        let bytes = &[0x00, 0x15, 0x00, 0x00];
+        let (_, ext) = parse_tls_extension(bytes).expect("parsing padding failed");
+        assert_eq!(ext.zero_padding_len(), Some(0));
+        assert_eq!(TlsExtension::EncryptThenMac.padding(), None);
+    }
+
+    #[test]
+    fn test_tls_extension_authz() {
+        let empty = &b""[..];
+        // client_authz, with a single saml_assertion format
+        let bytes = &[0x00, 0x07, 0x00, 0x02, 0x01, 0x01];
+        let expected = TlsExtension::ClientAuthz(vec![AuthzDataFormat::SamlAssertion]);
+        assert_eq!(parse_tls_extension(bytes), Ok((empty, expected.clone())));
+        assert_eq!(
+            parse_tls_client_hello_extension(bytes),
+            Ok((empty, expected))
+        );
+        // server_authz
+        let bytes = &[0x00, 0x08, 0x00, 0x03, 0x02, 0x00, 0x03];
+        let expected = TlsExtension::ServerAuthz(vec![
+            AuthzDataFormat::X509AttrCert,
+            AuthzDataFormat::SamlAssertionUrl,
+        ]);
+        assert_eq!(
+            parse_tls_server_hello_extension(bytes),
+            Ok((empty, expected))
+        );
+        // empty list, and list length not matching the extension length
+        let bytes = &[0x00, 0x07, 0x00, 0x01, 0x00];
+        assert!(parse_tls_extension(bytes).is_err());
+        let bytes = &[0x00, 0x07, 0x00, 0x02, 0x02, 0x01];
+        assert!(parse_tls_extension(bytes).is_err());
+        let bytes = &[0x00, 0x08, 0x00, 0x03, 0x01, 0x01, 0x01];
+        assert!(parse_tls_extension(bytes).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_type_id() {
+        assert_eq!(TlsExtension::SNI(vec![]).extension_type(), 0);
+        assert_eq!(TlsExtension::EncryptThenMac.extension_type(), 22);
+        assert_eq!(
+            TlsExtension::SupportedVersions(vec![TlsVersion::Tls13]).extension_type(),
+            43
+        );
+        assert_eq!(
+            TlsExtension::NextProtocolNegotiationServer(vec![]).extension_type(),
+            0x3374
+        );
+        assert_eq!(TlsExtension::Grease(0x1a1a, &[]).extension_type(), 0x1a1a);
+        assert_eq!(
+            TlsExtension::Unknown(TlsExtensionType(0x1234), &[0x00]).extension_type(),
+            0x1234
+        );
+        // round trip with the parser
+        let bytes = &[0x12, 0x34, 0x00, 0x00, 0x00, 0x17, 0x00, 0x00];
+        let (_, exts) = parse_tls_extensions(bytes).expect("parsing failed");
+        let ids: Vec<_> = exts.iter().map(|e| e.extension_type()).collect();
+        assert_eq!(ids, vec![0x1234, 0x17]);
+        // ALPS, using both code points
+        let bytes = &[
+            0x44, 0x69, 0x00, 0x05, 0x00, 0x03, 0x02, 0x68, 0x32, 0x44, 0xcd, 0x00, 0x05, 0x00,
+            0x03, 0x02, 0x68, 0x32,
+        ];
+        let (_, exts) = parse_tls_client_hello_extensions(bytes).expect("parsing failed");
+        assert!(matches!(exts[0], TlsExtension::ApplicationSettingsOld(_)));
+        let ids: Vec<_> = exts.iter().map(|e| e.extension_type()).collect();
+        assert_eq!(ids, vec![17513, 17613]);
+    }
+
+    #[test]
+    fn test_audit_extensions() {
+        let exts = vec![
+            TlsExtension::EncryptThenMac,
+            TlsExtension::Heartbeat(HeartbeatMode::PeerAllowedToSend),
+            TlsExtension::NextProtocolNegotiation,
+            TlsExtension::TruncatedHmac,
+        ];
+        let findings = audit_extensions(&exts);
+        let types: Vec<_> = findings.iter().map(|f| f.ext_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                TlsExtensionType::Heartbeat,
+                TlsExtensionType::NextProtocolNegotiation,
+                TlsExtensionType::TruncatedHMac,
+            ]
+        );
+        assert_eq!(findings[0].severity, FindingSeverity::Medium);
+        assert_eq!(findings[1].severity, FindingSeverity::Low);
+        assert!(audit_extensions(&exts[..1]).is_empty());
+    }
+
+    #[test]
+    fn test_tls_extension_certificate_type() {
+        let empty = &b""[..];
+        // client: list of supported types
+        let bytes = &[0x00, 0x13, 0x00, 0x03, 0x02, 0x02, 0x00];
+        let expected = TlsExtension::ClientCertificateType(vec![
+            CertificateType::RawPublicKey,
+            CertificateType::X509,
+        ]);
+        assert_eq!(
+            parse_tls_client_hello_extension(bytes),
+            Ok((empty, expected.clone()))
+        );
+        assert_eq!(parse_tls_extension(bytes), Ok((empty, expected)));
+        // server: selected type
+        let bytes = &[0x00, 0x14, 0x00, 0x01, 0x02];
+        let expected = TlsExtension::ServerCertificateType(vec![CertificateType::RawPublicKey]);
+        assert_eq!(
+            parse_tls_server_hello_extension(bytes),
+            Ok((empty, expected.clone()))
+        );
+        assert_eq!(parse_tls_extension(bytes), Ok((empty, expected)));
+        // a list is not valid in a ServerHello, and a single value is not valid in a ClientHello
+        let bytes = &[0x00, 0x14, 0x00, 0x02, 0x01, 0x02];
+        assert!(parse_tls_server_hello_extension(bytes).is_err());
+        let bytes = &[0x00, 0x13, 0x00, 0x01, 0x02];
+        assert!(parse_tls_client_hello_extension(bytes).is_err());
+        // empty list, and list length not matching the extension length
+        let bytes = &[0x00, 0x13, 0x00, 0x01, 0x00];
+        assert!(parse_tls_client_hello_extension(bytes).is_err());
+        let bytes = &[0x00, 0x13, 0x00, 0x02, 0x00, 0x00];
+        assert!(parse_tls_extension(bytes).is_err());
+        let bytes = &[0x00, 0x13, 0x00, 0x03, 0x01, 0x02, 0x00];
+        assert!(parse_tls_extension(bytes).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_status_request_v2() {
+        let empty = &b""[..];
+        // one ocsp_multi item, with an empty responder list and no extensions
+        let bytes = &[
+            0x00, 0x11, 0x00, 0x09, 0x00, 0x07, 0x02, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let expected = TlsExtension::StatusRequestV2(Some(vec![CertificateStatusRequestItemV2 {
+            status_type: CertificateStatusType::OCSPMulti,
+            request: &bytes[9..],
+        }]));
+        assert_eq!(
+            parse_tls_client_hello_extension(bytes),
+            Ok((empty, expected.clone()))
+        );
+        assert_eq!(parse_tls_extension(bytes), Ok((empty, expected.clone())));
+        if let TlsExtension::StatusRequestV2(Some(ref v)) = expected {
+            let req = v[0].ocsp_status_request().expect("invalid OCSP request");
+            assert!(req.responder_id_list.is_empty());
+            assert!(req.request_extensions.is_empty());
+        }
+        // empty, as sent by servers
+        let bytes = &[0x00, 0x11, 0x00, 0x00];
+        let expected = TlsExtension::StatusRequestV2(None);
+        assert_eq!(
+            parse_tls_server_hello_extension(bytes),
+            Ok((empty, expected))
+        );
+        // empty list
+        let bytes = &[0x00, 0x11, 0x00, 0x02, 0x00, 0x00];
+        assert!(parse_tls_extension(bytes).is_err());
+        // request length exceeds the list
+        let bytes = &[
+            0x00, 0x11, 0x00, 0x07, 0x00, 0x05, 0x02, 0x00, 0x04, 0x00, 0x00,
+        ];
+        assert!(parse_tls_extension(bytes).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_session_ticket() {
+        let empty = &b""[..];
+        // ClientHello, with a ticket
+        let bytes = &[0x00, 0x23, 0x00, 0x04, 0xde, 0xad, 0xbe, 0xef];
+        let (_, ext) = parse_tls_client_hello_extension(bytes).expect("parsing failed");
+        assert_eq!(ext, TlsExtension::SessionTicket(&bytes[4..]));
+        assert_eq!(ext.session_ticket(), Some(&bytes[4..]));
+        assert!(!ext.is_empty_ack());
+        // ClientHello, without ticket
+        let bytes = &[0x00, 0x23, 0x00, 0x00];
+        let (_, ext) = parse_tls_client_hello_extension(bytes).expect("parsing failed");
+        assert_eq!(ext.session_ticket(), None);
+        assert!(ext.is_empty_ack());
+        // ServerHello acknowledgment
+        let (_, ext) = parse_tls_server_hello_extension(bytes).expect("parsing failed");
+        assert_eq!(ext, TlsExtension::SessionTicket(empty));
+        assert!(ext.is_empty_ack());
+        // the ServerHello extension must be empty
+        let bytes = &[0x00, 0x23, 0x00, 0x02, 0xde, 0xad];
+        assert!(parse_tls_server_hello_extension(bytes).is_err());
+        assert!(!TlsExtension::EncryptThenMac.is_empty_ack());
+        assert_eq!(TlsExtension::EncryptThenMac.session_ticket(), None);
+    }
+
     #[test]
     fn test_tls_extension_npn() {
         let empty = &b""[..];
@@ -120,6 +538,31 @@ static CLIENT_EXTENSIONS1: &[u8] = &[
         assert_eq!(res, expected);
     }
 
+    #[test]
+    fn test_tls_extension_npn_server() {
+        let empty = &b""[..];
+        // spdy/3, http/1.1
+        let bytes = &[
+            0x33, 0x74, 0x00, 0x10, 0x06, 0x73, 0x70, 0x64, 0x79, 0x2f, 0x33, 0x08, 0x68, 0x74,
+            0x74, 0x70, 0x2f, 0x31, 0x2e, 0x31,
+        ];
+        let expected = TlsExtension::NextProtocolNegotiationServer(vec![b"spdy/3", b"http/1.1"]);
+        assert_eq!(
+            parse_tls_server_hello_extension(bytes),
+            Ok((empty, expected.clone()))
+        );
+        assert_eq!(parse_tls_extension(bytes), Ok((empty, expected.clone())));
+        assert_eq!(
+            TlsExtensionType::from(&expected),
+            TlsExtensionType::NextProtocolNegotiation
+        );
+        // the ClientHello extension must be empty
+        assert!(parse_tls_client_hello_extension(bytes).is_err());
+        // truncated protocol name
+        let bytes = &[0x33, 0x74, 0x00, 0x04, 0x06, 0x73, 0x70, 0x64];
+        assert!(parse_tls_server_hello_extension(bytes).is_err());
+    }
+
     #[test]
     fn test_tls_extension_list() {
         let empty = &b""[..];
@@ -171,6 +614,37 @@ static CLIENT_EXTENSIONS1: &[u8] = &[
         assert_eq!(res, expected);
     }
 
+    #[test]
+    fn test_tls_extension_signed_certificate_timestamp_list() {
+        let bytes = &[
+            0x00, 0x12, 0x00, 0x0b, 0x00, 0x09, // list length
+            0x00, 0x03, 0x01, 0x02, 0x03, // SCT 1
+            0x00, 0x02, 0x04, 0x05, // SCT 2
+        ];
+        let (rem, ext) = parse_tls_extension(bytes).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(
+            ext,
+            TlsExtension::SignedCertificateTimestamp(Some(vec![
+                &[0x01, 0x02, 0x03],
+                &[0x04, 0x05]
+            ]))
+        );
+        // truncated SCT: the last SCT overruns the list
+        let mut v = bytes.to_vec();
+        v[3] -= 1;
+        v[5] -= 1;
+        v.pop();
+        assert!(parse_tls_extension(&v).is_err());
+        // list length does not match the extension length
+        let mut v = bytes.to_vec();
+        v[3] += 1;
+        v.push(0x00);
+        assert!(parse_tls_extension(&v).is_err());
+        // empty list
+        assert!(parse_tls_extension(&[0x00, 0x12, 0x00, 0x02, 0x00, 0x00]).is_err());
+    }
+
     #[test]
     fn test_tls_extension_grease() {
         let empty = &b""[..];
@@ -204,6 +678,718 @@ static CLIENT_EXTENSIONS1: &[u8] = &[
         let bytes = &[0x00, 0x1c, 0x00, 0x02, 0x40, 0x01];
         let expected = TlsExtension::RecordSizeLimit(16385);
         let res = parse_tls_extension(bytes);
+        assert_eq!(res, Ok((empty, expected.clone())));
+        let res = parse_tls_extension_record_size_limit(bytes);
+        assert_eq!(res, Ok((empty, expected)));
+    }
+
+    #[test]
+    fn test_tls_extension_record_size_limit_invalid() {
+        // truncated (1 byte)
+        let bytes = &[0x00, 0x1c, 0x00, 0x01, 0x40];
+        assert!(parse_tls_extension(bytes).is_err());
+        // too long (3 bytes)
+        let bytes = &[0x00, 0x1c, 0x00, 0x03, 0x40, 0x01, 0x00];
+        assert!(parse_tls_extension(bytes).is_err());
+        // limit lower than 64
+        let bytes = &[0x00, 0x1c, 0x00, 0x02, 0x00, 0x3f];
+        assert!(parse_tls_extension(bytes).is_err());
+    }
+
+    #[test]
+    fn test_tls_extensions_iter() {
+        let ids: Vec<u16> = parse_tls_extensions_iter(CLIENT_EXTENSIONS1)
+            .map(|r| r.expect("could not parse extension").0)
+            .collect();
+        assert_eq!(
+            ids,
+            vec![0x0000, 0x000b, 0x000a, 0x0023, 0x000d, 0x0005, 0x000f]
+        );
+    }
+
+    #[test]
+    fn test_tls_extensions_iter_truncated() {
+        // second extension announces 4 bytes, but only 2 are present
+        let bytes = &[0x00, 0x17, 0x00, 0x00, 0x00, 0x1c, 0x00, 0x04, 0x40, 0x01];
+        let mut it = parse_tls_extensions_iter(bytes);
+        assert_eq!(it.next(), Some(Ok((0x0017, &b""[..]))));
+        assert!(matches!(it.next(), Some(Err(_))));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_tls_extension_encrypted_client_hello_outer() {
+        let empty = &b""[..];
+        let bytes = &[
+            0xfe, 0x0d, 0x00, 0x10, 0x00, 0x00, 0x01, 0x00, 0x01, 0x2a, 0x00, 0x02, 0xaa, 0xbb,
+            0x00, 0x04, 0x01, 0x02, 0x03, 0x04,
+        ];
+        let expected = TlsExtension::EncryptedClientHello(ECHClientHello::Outer {
+            kdf_id: 1,
+            aead_id: 1,
+            config_id: 0x2a,
+            enc: &[0xaa, 0xbb],
+            payload: &[0x01, 0x02, 0x03, 0x04],
+        });
+        let res = parse_tls_client_hello_extension(bytes);
+        assert_eq!(res, Ok((empty, expected)));
+        assert_eq!(
+            TlsExtensionType::from(&res.unwrap().1),
+            TlsExtensionType::EncryptedClientHello
+        );
+        // trailing byte after the payload
+        let bytes = &[
+            0xfe, 0x0d, 0x00, 0x11, 0x00, 0x00, 0x01, 0x00, 0x01, 0x2a, 0x00, 0x02, 0xaa, 0xbb,
+            0x00, 0x04, 0x01, 0x02, 0x03, 0x04, 0xff,
+        ];
+        assert!(parse_tls_client_hello_extension(bytes).is_err());
+        assert!(parse_tls_extension_encrypted_client_hello_content(&bytes[4..]).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_encrypted_client_hello_inner() {
+        let empty = &b""[..];
+        let bytes = &[0xfe, 0x0d, 0x00, 0x01, 0x01];
+        let expected = TlsExtension::EncryptedClientHello(ECHClientHello::Inner);
+        let res = parse_tls_extension(bytes);
+        assert_eq!(res, Ok((empty, expected)));
+        // inner form must not have a body
+        let bytes = &[0xfe, 0x0d, 0x00, 0x02, 0x01, 0x00];
+        assert!(parse_tls_extension(bytes).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_status_request_ocsp() {
+        let empty = &b""[..];
+        // typical request: OCSP, no responder ID, no extensions
+        let bytes = &[0x00, 0x05, 0x00, 0x05, 0x01, 0x00, 0x00, 0x00, 0x00];
+        let (rem, ext) = parse_tls_extension(bytes).expect("parsing status_request failed");
+        assert_eq!(rem, empty);
+        assert_eq!(
+            ext,
+            TlsExtension::StatusRequest(Some((CertificateStatusType::OCSP, &bytes[5..])))
+        );
+        let expected = OCSPStatusRequest {
+            responder_id_list: vec![],
+            request_extensions: empty,
+        };
+        assert_eq!(ext.ocsp_status_request(), Some(expected));
+    }
+
+    #[test]
+    fn test_ocsp_status_request() {
+        let empty = &b""[..];
+        let bytes = &[
+            0x00, 0x08, 0x00, 0x02, 0xaa, 0xbb, 0x00, 0x02, 0xcc, 0xdd, 0x00, 0x03, 0x30, 0x01,
+            0x00,
+        ];
+        let expected = OCSPStatusRequest {
+            responder_id_list: vec![&bytes[4..6], &bytes[8..10]],
+            request_extensions: &bytes[12..],
+        };
+        assert_eq!(parse_ocsp_status_request(bytes), Ok((empty, expected)));
+        assert!(parse_ocsp_status_request(&bytes[..13]).is_err());
+        // not OCSP
+        let ext = TlsExtension::StatusRequest(Some((CertificateStatusType(2), &bytes[..])));
+        assert_eq!(ext.ocsp_status_request(), None);
+    }
+
+    #[rustfmt::skip]
+static CH_SIG_ALGS_CERT: &[u8] = &[
+    0x01, 0x00, 0x00, 0x3f, 0x03, 0x03,
+    0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55,
+    0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55,
+    0x00, 0x00, 0x02, 0x13, 0x01, 0x01, 0x00,
+    0x00, 0x14,
+    // signature_algorithms
+    0x00, 0x0d, 0x00, 0x08, 0x00, 0x06, 0x04, 0x03, 0x08, 0x04, 0x04, 0x01,
+    // signature_algorithms_cert
+    0x00, 0x32, 0x00, 0x04, 0x00, 0x02, 0x04, 0x01,
+];
+
+    #[test]
+    fn test_tls_extension_signature_algorithms_cert() {
+        let empty = &b""[..];
+        let (_, msg) = parse_tls_message_handshake(CH_SIG_ALGS_CERT).expect("parsing CH failed");
+        let ext = match msg {
+            TlsMessage::Handshake(TlsMessageHandshake::ClientHello(ref ch)) => ch.ext.unwrap(),
+            _ => panic!("expected ClientHello"),
+        };
+        let expected = vec![
+            TlsExtension::SignatureAlgorithms(vec![0x0403, 0x0804, 0x0401]),
+            TlsExtension::SignatureAlgorithmsCert(vec![0x0401]),
+        ];
+        let res = parse_tls_client_hello_extensions(ext);
+        assert_eq!(res, Ok((empty, expected)));
+        let res = parse_tls_extensions(ext).expect("parsing extensions failed");
+        assert_eq!(
+            TlsExtensionType::from(&res.1[1]),
+            TlsExtensionType::SigAlgorithmsCert
+        );
+        assert_eq!(
+            format!("{:?}", res.1[1]),
+            "TlsExtension::SignatureAlgorithmsCert([\"rsa_pkcs1_sha256\"])"
+        );
+        // odd list length
+        let bytes = &[0x00, 0x32, 0x00, 0x05, 0x00, 0x03, 0x04, 0x01, 0x05];
+        assert!(parse_tls_extension(bytes).is_err());
+        // trailing byte after the list
+        let bytes = &[0x00, 0x32, 0x00, 0x05, 0x00, 0x02, 0x04, 0x01, 0xff];
+        assert!(parse_tls_extension(bytes).is_err());
+        assert!(parse_tls_extension_signature_algorithms_cert_content(&bytes[4..]).is_err());
+        // empty list
+        let bytes = &[0x00, 0x32, 0x00, 0x02, 0x00, 0x00];
+        assert!(parse_tls_extension(bytes).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_signature_schemes() {
+        // modern schemes: ed25519, rsa_pss_rsae_sha256, and an unknown value
+        let ext = TlsExtension::SignatureAlgorithms(vec![0x0807, 0x0804, 0xfe00]);
+        assert_eq!(
+            ext.signature_schemes(),
+            Some(vec![
+                SignatureScheme::ed25519,
+                SignatureScheme::rsa_pss_rsae_sha256,
+                SignatureScheme(0xfe00)
+            ])
+        );
+        assert_eq!(
+            SignatureScheme::from_u16(0x0807),
+            Some(SignatureScheme::ed25519)
+        );
+        assert_eq!(SignatureScheme::from_u16(0xfe00), None);
+        // legacy (hash, signature) pairs: (sha1, rsa), (sha256, ecdsa), (md5, rsa)
+        let ext = TlsExtension::SignatureAlgorithmsCert(vec![0x0201, 0x0403, 0x0101]);
+        let schemes = ext.signature_schemes().expect("no signature schemes");
+        assert_eq!(schemes[0], SignatureScheme::rsa_pkcs1_sha1);
+        assert_eq!(schemes[1], SignatureScheme::ecdsa_secp256r1_sha256);
+        assert_eq!((schemes[1].hash_alg(), schemes[1].sign_alg()), (4, 3));
+        assert_eq!(SignatureScheme::from_u16(schemes[2].0), None);
+        assert_eq!(
+            format!("{:?}", ext),
+            "TlsExtension::SignatureAlgorithmsCert([\"rsa_pkcs1_sha1\", \"ecdsa_secp256r1_sha256\", \"HashSign(Md5,Rsa)\"])"
+        );
+        // not a signature algorithms extension
+        assert_eq!(TlsExtension::EncryptThenMac.signature_schemes(), None);
+    }
+
+    #[test]
+    fn test_display_registry_names() {
+        assert_eq!(HashAlgorithm::Sha256.to_string(), "sha256");
+        assert_eq!(HashAlgorithm::None.to_string(), "none");
+        assert_eq!(format!("{:?}", HashAlgorithm::Sha256), "Sha256");
+        assert_eq!(HashAlgorithm(42).to_string(), "HashAlgorithm(42 / 0x2a)");
+        assert_eq!(SignAlgorithm::Ecdsa.to_string(), "ecdsa");
+        assert_eq!(SignAlgorithm::Ed25519.to_string(), "ed25519");
+        assert_eq!(format!("{:?}", SignAlgorithm::Rsa), "Rsa");
+        assert_eq!(
+            SignatureScheme::rsa_pss_rsae_sha256.to_string(),
+            "rsa_pss_rsae_sha256"
+        );
+        assert_eq!(SignatureScheme::ed448.to_string(), "ed448");
+        assert_eq!(NamedGroup::EcdhX25519.to_string(), "x25519");
+        assert_eq!(NamedGroup::Secp256r1.to_string(), "secp256r1");
+        assert_eq!(TlsAlertSeverity::Fatal.to_string(), "fatal");
+        assert_eq!(TlsAlertSeverity::Warning.to_string(), "warning");
+    }
+
+    #[test]
+    fn test_tls_extension_certificate_authorities() {
+        let empty = &b""[..];
+        let bytes = &[
+            0x00, 0x2f, 0x00, 0x0e, 0x00, 0x0c, 0x00, 0x04, 0x30, 0x02, 0x31, 0x00, 0x00, 0x04,
+            0x30, 0x02, 0x31, 0x01,
+        ];
+        let expected = TlsExtension::CertificateAuthorities(vec![&bytes[8..12], &bytes[14..18]]);
+        let res = parse_tls_extension(bytes);
+        assert_eq!(res, Ok((empty, expected)));
+        // truncated entry
+        let bytes = &[
+            0x00, 0x2f, 0x00, 0x0e, 0x00, 0x0c, 0x00, 0x04, 0x30, 0x02, 0x31, 0x00, 0x00, 0x05,
+            0x30, 0x02, 0x31, 0x01,
+        ];
+        assert!(parse_tls_extension(bytes).is_err());
+        // trailing byte after the list
+        let bytes = &[
+            0x00, 0x2f, 0x00, 0x09, 0x00, 0x06, 0x00, 0x04, 0x30, 0x02, 0x31, 0x00, 0xff,
+        ];
+        assert!(parse_tls_extension(bytes).is_err());
+        assert!(parse_tls_extension_certificate_authorities_content(&bytes[4..]).is_err());
+        // empty list
+        let bytes = &[0x00, 0x2f, 0x00, 0x02, 0x00, 0x00];
+        assert!(parse_tls_extension(bytes).is_err());
+    }
+
+    #[test]
+    fn test_quic_varint() {
+        let empty = &b""[..];
+        // examples from RFC9000 appendix A.1
+        let tests: &[(&[u8], u64)] = &[
+            (&[0x25], 37),
+            (&[0x40, 0x25], 37),
+            (&[0x7b, 0xbd], 15293),
+            (&[0x9d, 0x7f, 0x3e, 0x7d], 494_878_333),
+            (
+                &[0xc2, 0x19, 0x7c, 0x5e, 0xff, 0x14, 0xe8, 0x8c],
+                151_288_809_941_952_652,
+            ),
+        ];
+        for &(bytes, v) in tests {
+            assert_eq!(parse_quic_varint(bytes), Ok((empty, v)));
+        }
+        assert!(parse_quic_varint(&[0x9d, 0x7f, 0x3e]).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_quic_transport_parameters() {
+        let empty = &b""[..];
+        let bytes = &[
+            0x00, 0x39, 0x00, 0x14, // type, length
+            0x01, 0x04, 0x80, 0x00, 0x75, 0x30, // max_idle_timeout = 30000
+            0x04, 0x04, 0x80, 0x10, 0x00, 0x00, // initial_max_data
+            0x40, 0x0c, 0x00, // disable_active_migration (2-byte id)
+            0x0f, 0x02, 0xab, 0xcd, // initial_source_connection_id
+            0x80,
+        ];
+        // last parameter is truncated
+        assert!(parse_tls_extension(bytes).is_err());
+        let mut v = bytes[..bytes.len() - 1].to_vec();
+        v[3] = 0x13;
+        let expected = TlsExtension::QuicTransportParameters(vec![
+            (0x01, &v[6..10]),
+            (0x04, &v[12..16]),
+            (0x0c, empty),
+            (0x0f, &v[21..23]),
+        ]);
+        let res = parse_tls_extension(&v);
         assert_eq!(res, Ok((empty, expected)));
     }
+
+    #[test]
+    fn test_tls_extension_delegated_credentials() {
+        let empty = &b""[..];
+        let bytes = &[
+            0x00, 0x22, 0x00, 0x0a, 0x00, 0x08, 0x04, 0x03, 0x05, 0x03, 0x06, 0x03, 0x02, 0x03,
+        ];
+        let expected = TlsExtension::DelegatedCredentials(vec![0x0403, 0x0503, 0x0603, 0x0203]);
+        let res = parse_tls_client_hello_extension(bytes);
+        assert_eq!(res, Ok((empty, expected)));
+        assert_eq!(
+            format!("{:?}", res.unwrap().1),
+            "TlsExtension::DelegatedCredentials([\"ecdsa_secp256r1_sha256\", \"ecdsa_secp384r1_sha384\", \"ecdsa_secp521r1_sha512\", \"ecdsa_sha1\"])"
+        );
+        // odd list length
+        let bytes = &[0x00, 0x22, 0x00, 0x05, 0x00, 0x03, 0x04, 0x03, 0x05];
+        assert!(parse_tls_client_hello_extension(bytes).is_err());
+        // empty list
+        let bytes = &[0x00, 0x22, 0x00, 0x02, 0x00, 0x00];
+        assert!(parse_tls_client_hello_extension(bytes).is_err());
+        // trailing byte after the list
+        let bytes = &[0x00, 0x22, 0x00, 0x05, 0x00, 0x02, 0x04, 0x03, 0xff];
+        assert!(parse_tls_client_hello_extension(bytes).is_err());
+        assert!(parse_tls_extension_delegated_credentials_content(&bytes[4..]).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_application_settings() {
+        let empty = &b""[..];
+        let bytes = &[0x44, 0xcd, 0x00, 0x05, 0x00, 0x03, 0x02, 0x68, 0x32];
+        let expected = TlsExtension::ApplicationSettings(vec![b"h2"]);
+        let res = parse_tls_client_hello_extension(bytes);
+        assert_eq!(res, Ok((empty, expected)));
+        // previous code point
+        let bytes = &[0x44, 0x69, 0x00, 0x05, 0x00, 0x03, 0x02, 0x68, 0x32];
+        let expected = TlsExtension::ApplicationSettingsOld(vec![b"h2"]);
+        let res = parse_tls_client_hello_extension(bytes);
+        assert_eq!(res, Ok((empty, expected.clone())));
+        assert_eq!(
+            TlsExtensionType::from(&expected),
+            TlsExtensionType::ApplicationSettingsOld
+        );
+        let (_, ext) = parse_tls_client_hello_extension(&[
+            0x44, 0xcd, 0x00, 0x05, 0x00, 0x03, 0x02, 0x68, 0x32,
+        ])
+        .expect("parsing extension failed");
+        assert_eq!(
+            format!("{:?}", ext),
+            "TlsExtension::ApplicationSettings([\"h2\"])"
+        );
+        // protocol name length larger than the list
+        let bytes = &[0x44, 0xcd, 0x00, 0x05, 0x00, 0x03, 0x03, 0x68, 0x32];
+        assert!(parse_tls_client_hello_extension(bytes).is_err());
+        // list length smaller than the extension data
+        let bytes = &[0x44, 0xcd, 0x00, 0x05, 0x00, 0x02, 0x02, 0x68, 0x32];
+        assert!(parse_tls_client_hello_extension(bytes).is_err());
+        // trailing data after a valid list
+        let bytes = &[0x44, 0xcd, 0x00, 0x06, 0x00, 0x03, 0x02, 0x68, 0x32, 0xff];
+        assert!(parse_tls_client_hello_extension(bytes).is_err());
+        assert!(parse_tls_extension_application_settings_content(&bytes[4..]).is_err());
+        // empty list
+        let bytes = &[0x44, 0xcd, 0x00, 0x02, 0x00, 0x00];
+        assert!(parse_tls_client_hello_extension(bytes).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_token_binding() {
+        let empty = &b""[..];
+        let bytes = &[0x00, 0x18, 0x00, 0x04, 0x00, 0x0d, 0x01, 0x02];
+        let expected = TlsExtension::TokenBinding {
+            major: 0,
+            minor: 13,
+            key_params: vec![2],
+        };
+        let res = parse_tls_server_hello_extension(bytes);
+        assert_eq!(res, Ok((empty, expected)));
+        assert_eq!(
+            format!("{:?}", res.unwrap().1),
+            "TlsExtension::TokenBinding(version=0.13, key_params=[2])"
+        );
+        // key_params length larger than the extension
+        let bytes = &[0x00, 0x18, 0x00, 0x04, 0x00, 0x0d, 0x02, 0x02];
+        assert!(parse_tls_extension(bytes).is_err());
+        // empty key_params list
+        let bytes = &[0x00, 0x18, 0x00, 0x03, 0x00, 0x0d, 0x00];
+        assert!(parse_tls_extension(bytes).is_err());
+        // trailing byte after key_params
+        let bytes = &[0x00, 0x18, 0x00, 0x05, 0x00, 0x0d, 0x01, 0x02, 0xff];
+        assert!(parse_tls_extension(bytes).is_err());
+        assert!(parse_tls_extension_token_binding_content(&bytes[4..]).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_psk_key_exchange_modes() {
+        let empty = &b""[..];
+        let bytes = &[0x00, 0x2d, 0x00, 0x04, 0x03, 0x00, 0x01, 0x05];
+        let expected = TlsExtension::PskExchangeModes(vec![
+            PskKeyExchangeMode::Psk,
+            PskKeyExchangeMode::PskDhe,
+            PskKeyExchangeMode(5),
+        ]);
+        let res = parse_tls_extension_psk_key_exchange_modes(bytes);
+        assert_eq!(res, Ok((empty, expected)));
+        assert_eq!(
+            format!("{:?}", res.unwrap().1),
+            "TlsExtension::PskExchangeModes([Psk, PskDhe, PskKeyExchangeMode(5 / 0x5)])"
+        );
+        // list length larger than the extension
+        let bytes = &[0x00, 0x2d, 0x00, 0x02, 0x02, 0x01];
+        assert!(parse_tls_extension_psk_key_exchange_modes(bytes).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_supported_versions() {
+        let empty = &b""[..];
+        let client = &[
+            0x00, 0x2b, 0x00, 0x07, 0x06, 0x7f, 0x12, 0x03, 0x03, 0x03, 0x02,
+        ];
+        let server = &[0x00, 0x2b, 0x00, 0x02, 0x03, 0x04];
+        let expected = TlsExtension::SupportedVersions(vec![
+            TlsVersion::Tls13Draft18,
+            TlsVersion::Tls12,
+            TlsVersion::Tls11,
+        ]);
+        assert_eq!(
+            parse_tls_client_hello_extension(client),
+            Ok((empty, expected.clone()))
+        );
+        assert_eq!(parse_tls_extension(client), Ok((empty, expected)));
+        let expected = TlsExtension::SupportedVersions(vec![TlsVersion::Tls13]);
+        assert_eq!(
+            parse_tls_server_hello_extension(server),
+            Ok((empty, expected.clone()))
+        );
+        assert_eq!(parse_tls_extension(server), Ok((empty, expected)));
+        // layouts are not interchangeable
+        assert!(parse_tls_client_hello_extension(server).is_err());
+        assert!(parse_tls_server_hello_extension(client).is_err());
+        // trailing byte after the list
+        let bytes = &[0x00, 0x2b, 0x00, 0x04, 0x02, 0x03, 0x04, 0xff];
+        assert!(parse_tls_client_hello_extension(bytes).is_err());
+        assert!(parse_tls_extension(bytes).is_err());
+    }
+
+    #[test]
+    fn test_named_group_display() {
+        assert_eq!(format!("{}", NamedGroup::EcdhX25519), "x25519");
+        assert_eq!(format!("{:?}", NamedGroup::EcdhX25519), "EcdhX25519");
+        assert_eq!(NamedGroup::EcdhX25519.key_share_length(), Some(32));
+        assert_eq!(format!("{}", NamedGroup::Secp256r1), "secp256r1");
+        assert_eq!(NamedGroup::Secp256r1.key_share_length(), Some(65));
+        assert_eq!(format!("{}", NamedGroup::Ffdhe2048), "ffdhe2048");
+        assert_eq!(NamedGroup::Ffdhe2048.key_share_length(), Some(256));
+        // not allowed in TLS 1.3
+        assert_eq!(NamedGroup::Sect163k1.key_share_length(), None);
+        let unknown = NamedGroup(0x1234);
+        assert_eq!(unknown.iana_name(), None);
+        assert_eq!(unknown.key_share_length(), None);
+        assert_eq!(format!("{}", unknown), "NamedGroup(4660 / 0x1234)");
+        assert_eq!(format!("{:?}", unknown), "NamedGroup(4660 / 0x1234)");
+    }
+
+    #[test]
+    fn test_key_share_entries() {
+        let empty = &b""[..];
+        // ClientHello: x25519 and secp256r1 (truncated key exchange data)
+        let bytes = &[
+            0x00, 0x0e, 0x00, 0x1d, 0x00, 0x04, 0x01, 0x02, 0x03, 0x04, 0x00, 0x17, 0x00, 0x02,
+            0x04, 0x05,
+        ];
+        let expected = vec![
+            KeyShareEntry {
+                group: NamedGroup::EcdhX25519,
+                kx: &bytes[6..10],
+            },
+            KeyShareEntry {
+                group: NamedGroup::Secp256r1,
+                kx: &bytes[14..],
+            },
+        ];
+        assert_eq!(parse_key_share_client_hello(bytes), Ok((empty, expected)));
+        // empty list is allowed
+        assert_eq!(parse_key_share_client_hello(&[0, 0]), Ok((empty, vec![])));
+        // trailing data in list
+        let bytes = &[0x00, 0x07, 0x00, 0x1d, 0x00, 0x02, 0x01, 0x02, 0x03];
+        assert!(parse_key_share_client_hello(bytes).is_err());
+        // ServerHello
+        let bytes = &[0x00, 0x1d, 0x00, 0x04, 0x01, 0x02, 0x03, 0x04];
+        let expected = KeyShareEntry {
+            group: NamedGroup::EcdhX25519,
+            kx: &bytes[4..],
+        };
+        assert_eq!(parse_key_share_server_hello(bytes), Ok((empty, expected)));
+        // empty key exchange data
+        assert!(parse_key_share_server_hello(&[0x00, 0x1d, 0x00, 0x00]).is_err());
+        // HelloRetryRequest
+        let res = parse_key_share_hello_retry_request(&[0x00, 0x17]);
+        assert_eq!(res, Ok((empty, NamedGroup::Secp256r1)));
+    }
+
+    #[test]
+    fn test_tls_extension_pre_shared_key() {
+        let empty = &b""[..];
+        let mut bytes = vec![
+            0x00, 0x29, 0x00, 0x2f, 0x00, 0x0a, 0x00, 0x04, 0x61, 0x62, 0x63, 0x64, 0x01, 0x02,
+            0x03, 0x04, 0x00, 0x21, 0x20,
+        ];
+        bytes.extend_from_slice(&[0xaa; 32]);
+        let expected = TlsExtension::PreSharedKeyOffered(OfferedPsks {
+            identities: vec![PskIdentity {
+                identity: b"abcd",
+                obfuscated_ticket_age: 0x0102_0304,
+            }],
+            binders: vec![&[0xaa; 32]],
+        });
+        let res = parse_tls_client_hello_extension(&bytes);
+        assert_eq!(res, Ok((empty, expected)));
+        // the generic parser keeps the raw content
+        let res = parse_tls_extension(&bytes);
+        assert_eq!(res, Ok((empty, TlsExtension::PreSharedKey(&bytes[4..]))));
+        // the ticket age is read as a big-endian u32
+        let mut psks = bytes[4..].to_vec();
+        psks[8..12].copy_from_slice(&[0xff, 0x00, 0x00, 0x01]);
+        let (_, offered) = parse_offered_psks(&psks).expect("parsing failed");
+        assert_eq!(offered.identities[0].obfuscated_ticket_age, 0xff00_0001);
+        // trailing byte after the binders
+        let mut psks = bytes[4..].to_vec();
+        psks.push(0xff);
+        assert!(parse_offered_psks(&psks).is_err());
+        // one identity, two binders
+        let mut psks = bytes[4..16].to_vec();
+        psks.extend_from_slice(&[0x00, 0x42, 0x20]);
+        psks.extend_from_slice(&[0xaa; 32]);
+        psks.push(0x20);
+        psks.extend_from_slice(&[0xbb; 32]);
+        assert!(parse_offered_psks(&psks).is_err());
+        // binder too short
+        bytes[3] -= 1;
+        bytes[17] -= 1;
+        bytes[18] -= 1;
+        assert!(parse_tls_client_hello_extension(&bytes[..bytes.len() - 1]).is_err());
+        // ServerHello
+        let bytes = &[0x00, 0x29, 0x00, 0x02, 0x00, 0x00];
+        let res = parse_tls_server_hello_extension(bytes);
+        assert_eq!(res, Ok((empty, TlsExtension::PreSharedKeySelected(0))));
+        let bytes = &[0x00, 0x29, 0x00, 0x03, 0x00, 0x00, 0x00];
+        assert!(parse_tls_server_hello_extension(bytes).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_early_data() {
+        let empty = &b""[..];
+        let bytes = &[0x00, 0x2a, 0x00, 0x00];
+        let res = parse_tls_extension_early_data(bytes);
+        assert_eq!(res, Ok((empty, TlsExtension::EarlyData(None))));
+        let bytes = &[0x00, 0x2a, 0x00, 0x04, 0x00, 0x00, 0x40, 0x00];
+        let res = parse_tls_extension_early_data(bytes);
+        assert_eq!(res, Ok((empty, TlsExtension::EarlyData(Some(0x4000)))));
+        let bytes = &[0x00, 0x2a, 0x00, 0x02, 0x40, 0x00];
+        assert!(parse_tls_extension_early_data(bytes).is_err());
+        let bytes = &[0x00, 0x2a, 0x00, 0x05, 0x00, 0x00, 0x40, 0x00, 0x00];
+        assert!(parse_tls_extension(bytes).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_heartbeat() {
+        let empty = &b""[..];
+        let bytes = &[0x00, 0x0f, 0x00, 0x01, 0x01];
+        let expected = TlsExtension::Heartbeat(HeartbeatMode::PeerAllowedToSend);
+        assert_eq!(parse_tls_extension(bytes), Ok((empty, expected.clone())));
+        assert_eq!(parse_tls_extension_heartbeat(bytes), Ok((empty, expected)));
+        let bytes = &[0x00, 0x0f, 0x00, 0x01, 0x02];
+        let (_, ext) = parse_tls_client_hello_extension(bytes).expect("parsing failed");
+        assert_eq!(
+            ext,
+            TlsExtension::Heartbeat(HeartbeatMode::PeerNotAllowedToSend)
+        );
+        assert_eq!(
+            format!("{:?}", ext),
+            "TlsExtension::Heartbeat(mode=PeerNotAllowedToSend)"
+        );
+        // unknown mode is kept
+        let (_, ext) =
+            parse_tls_extension(&[0x00, 0x0f, 0x00, 0x01, 0x07]).expect("parsing failed");
+        assert_eq!(ext, TlsExtension::Heartbeat(HeartbeatMode(7)));
+        // invalid length
+        assert!(parse_tls_extension(&[0x00, 0x0f, 0x00, 0x02, 0x01, 0x01]).is_err());
+        assert!(parse_tls_extension(&[0x00, 0x0f, 0x00, 0x00]).is_err());
+        assert!(parse_tls_extension_heartbeat(&[0x00, 0x0f, 0x00, 0x02, 0x01, 0x01]).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_ec_point_formats() {
+        let empty = &b""[..];
+        let bytes = &[0x00, 0x0b, 0x00, 0x02, 0x01, 0x00];
+        let expected = TlsExtension::EcPointFormats(vec![EcPointFormat::Uncompressed]);
+        assert_eq!(parse_tls_extension(bytes), Ok((empty, expected.clone())));
+        assert_eq!(
+            parse_tls_extension_ec_point_formats(bytes),
+            Ok((empty, expected.clone()))
+        );
+        assert_eq!(
+            format!("{:?}", expected),
+            "TlsExtension::EcPointFormats([Uncompressed])"
+        );
+        // unknown format is kept
+        let (_, ext) =
+            parse_tls_extension(&[0x00, 0x0b, 0x00, 0x02, 0x01, 0x07]).expect("parsing failed");
+        assert_eq!(ext, TlsExtension::EcPointFormats(vec![EcPointFormat(7)]));
+        // list length does not match extension length
+        assert!(parse_tls_extension(&[0x00, 0x0b, 0x00, 0x03, 0x01, 0x00, 0x01]).is_err());
+        assert!(parse_tls_extension(&[0x00, 0x0b, 0x00, 0x02, 0x02, 0x00]).is_err());
+        // empty list
+        assert!(parse_tls_extension(&[0x00, 0x0b, 0x00, 0x01, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_named_groups() {
+        let bytes = &[
+            0x00, 0x0a, 0x00, 0x08, 0x00, 0x06, 0x00, 0x1d, 0x00, 0x17, 0x12, 0x34,
+        ];
+        let (_, ext) = parse_tls_extension(bytes).expect("parsing failed");
+        let groups = ext
+            .named_groups()
+            .expect("not a supported groups extension");
+        assert_eq!(
+            groups,
+            &[
+                NamedGroup::EcdhX25519,
+                NamedGroup::Secp256r1,
+                NamedGroup(0x1234)
+            ]
+        );
+        let names: Vec<_> = groups.iter().map(|g| g.iana_name()).collect();
+        assert_eq!(names, vec![Some("x25519"), Some("secp256r1"), None]);
+        assert_eq!(groups[2].0, 0x1234);
+        assert_eq!(
+            TlsExtension::Heartbeat(HeartbeatMode::PeerAllowedToSend).named_groups(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_tls_extension_renegotiation_info() {
+        let empty = &b""[..];
+        // initial handshake
+        let bytes = &[0xff, 0x01, 0x00, 0x01, 0x00];
+        let res = parse_tls_client_hello_extension(bytes);
+        assert_eq!(res, Ok((empty, TlsExtension::RenegotiationInfo(empty))));
+        // renegotiation (client and server verify_data)
+        let mut v = vec![0xff, 0x01, 0x00, 0x19, 0x18];
+        v.extend_from_slice(&[0xa5; 24]);
+        let (_, ext) = parse_tls_server_hello_extension(&v).expect("parsing failed");
+        assert_eq!(ext, TlsExtension::RenegotiationInfo(&[0xa5; 24]));
+        // length mismatch
+        assert!(parse_tls_extension(&[0xff, 0x01, 0x00, 0x02, 0x00, 0x00]).is_err());
+        assert!(parse_tls_extension(&[0xff, 0x01, 0x00, 0x02, 0x0c, 0x00]).is_err());
+        assert!(parse_tls_extension(&[0xff, 0x01, 0x00, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_tls_extension_alpn_strict() {
+        let bytes = &[
+            0x00, 0x10, 0x00, 0x0e, 0x00, 0x0c, 0x02, 0x68, 0x32, 0x08, 0x68, 0x74, 0x74, 0x70,
+            0x2f, 0x31, 0x2e, 0x31,
+        ];
+        let (_, ext) = parse_tls_extension(bytes).expect("parsing failed");
+        assert_eq!(ext, TlsExtension::ALPN(vec![b"h2", b"http/1.1"]));
+        assert!(ext.alpn_contains(b"h2"));
+        assert!(ext.alpn_contains(b"http/1.1"));
+        assert!(!ext.alpn_contains(b"h3"));
+        assert!(!ext.alpn_contains(b"http"));
+        assert!(!TlsExtension::SNI(vec![]).alpn_contains(b"h2"));
+        // non-UTF8 names are preserved
+        let (_, ext) = parse_tls_extension(&[0x00, 0x10, 0x00, 0x05, 0x00, 0x03, 0x02, 0xff, 0xfe])
+            .expect("parsing failed");
+        assert_eq!(ext, TlsExtension::ALPN(vec![&[0xff, 0xfe]]));
+        // truncated list: last name overruns the list length
+        let mut v = bytes.to_vec();
+        v[5] = 0x0b;
+        v[3] = 0x0d;
+        v.pop();
+        assert!(parse_tls_extension(&v).is_err());
+        // list length does not match extension length
+        let mut v = bytes.to_vec();
+        v[3] = 0x0f;
+        v.push(0x00);
+        assert!(parse_tls_extension(&v).is_err());
+        // empty protocol name
+        assert!(parse_tls_extension(&[0x00, 0x10, 0x00, 0x03, 0x00, 0x01, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_tls_extensions_limited() {
+        // 1000 empty extended_master_secret extensions
+        let bytes: Vec<u8> = [0x00, 0x17, 0x00, 0x00].repeat(1000);
+        let res = parse_tls_extensions_limited(&bytes, 16);
+        assert!(matches!(
+            res,
+            Err(Err::Error(e)) if e.code == ErrorKind::TooLarge && e.input.len() == bytes.len() - 64
+        ));
+        let (rem, exts) = parse_tls_extensions_limited(&bytes, 1000).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(exts.len(), 1000);
+        assert!(exts
+            .iter()
+            .all(|e| *e == TlsExtension::ExtendedMasterSecret));
+        // the limit is inclusive
+        let (_, exts) = parse_tls_extensions_limited(&bytes[..64], 16).expect("parsing failed");
+        assert_eq!(exts.len(), 16);
+    }
+
+    #[test]
+    fn test_tls_extension_cookie() {
+        let bytes = &[0x00, 0x2c, 0x00, 0x05, 0x00, 0x03, 0x01, 0x02, 0x03];
+        let (rem, ext) = parse_tls_extension(bytes).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(ext, TlsExtension::Cookie(&[0x01, 0x02, 0x03]));
+        let (_, ext) = parse_tls_extension_cookie(bytes).expect("parsing failed");
+        assert_eq!(ext, TlsExtension::Cookie(&[0x01, 0x02, 0x03]));
+        // cookie length exceeds the extension length
+        assert!(parse_tls_extension(&[0x00, 0x2c, 0x00, 0x04, 0x00, 0x03, 0x01, 0x02]).is_err());
+        // cookie length is shorter than the extension length
+        assert!(parse_tls_extension(&[0x00, 0x2c, 0x00, 0x04, 0x00, 0x01, 0x01, 0x02]).is_err());
+        // empty cookie
+        assert!(parse_tls_extension(&[0x00, 0x2c, 0x00, 0x02, 0x00, 0x00]).is_err());
+    }
 } // mod tls_extensions
@@ -0,0 +1,272 @@
+#![cfg(feature = "fingerprint")]
+
+extern crate tls_parser;
+
+mod tls_fingerprint {
+    use tls_parser::*;
+
+    #[rustfmt::skip]
+static CH: &[u8] = &[
+    0x16, 0x03, 0x01, 0x01, 0x2c, 0x01, 0x00, 0x01, 0x28, 0x03, 0x03, 0xb2,
+    0x9d, 0xd7, 0x87, 0xff, 0x21, 0xeb, 0x04, 0xc8, 0xa5, 0x38, 0x39, 0x9a,
+    0xcf, 0xb7, 0xa3, 0x82, 0x1f, 0x82, 0x6c, 0x49, 0xbc, 0x8b, 0xb8, 0xa9,
+    0x03, 0x0a, 0x2d, 0xce, 0x38, 0x0b, 0xf4, 0x00, 0x00, 0xaa, 0xc0, 0x30,
+    0xc0, 0x2c, 0xc0, 0x28, 0xc0, 0x24, 0xc0, 0x14, 0xc0, 0x0a, 0x00, 0xa5,
+    0x00, 0xa3, 0x00, 0xa1, 0x00, 0x9f, 0x00, 0x6b, 0x00, 0x6a, 0x00, 0x69,
+    0x00, 0x68, 0x00, 0x39, 0x00, 0x38, 0x00, 0x37, 0x00, 0x36, 0x00, 0x88,
+    0x00, 0x87, 0x00, 0x86, 0x00, 0x85, 0xc0, 0x32, 0xc0, 0x2e, 0xc0, 0x2a,
+    0xc0, 0x26, 0xc0, 0x0f, 0xc0, 0x05, 0x00, 0x9d, 0x00, 0x3d, 0x00, 0x35,
+    0x00, 0x84, 0xc0, 0x2f, 0xc0, 0x2b, 0xc0, 0x27, 0xc0, 0x23, 0xc0, 0x13,
+    0xc0, 0x09, 0x00, 0xa4, 0x00, 0xa2, 0x00, 0xa0, 0x00, 0x9e, 0x00, 0x67,
+    0x00, 0x40, 0x00, 0x3f, 0x00, 0x3e, 0x00, 0x33, 0x00, 0x32, 0x00, 0x31,
+    0x00, 0x30, 0x00, 0x9a, 0x00, 0x99, 0x00, 0x98, 0x00, 0x97, 0x00, 0x45,
+    0x00, 0x44, 0x00, 0x43, 0x00, 0x42, 0xc0, 0x31, 0xc0, 0x2d, 0xc0, 0x29,
+    0xc0, 0x25, 0xc0, 0x0e, 0xc0, 0x04, 0x00, 0x9c, 0x00, 0x3c, 0x00, 0x2f,
+    0x00, 0x96, 0x00, 0x41, 0xc0, 0x11, 0xc0, 0x07, 0xc0, 0x0c, 0xc0, 0x02,
+    0x00, 0x05, 0x00, 0x04, 0xc0, 0x12, 0xc0, 0x08, 0x00, 0x16, 0x00, 0x13,
+    0x00, 0x10, 0x00, 0x0d, 0xc0, 0x0d, 0xc0, 0x03, 0x00, 0x0a, 0x00, 0xff,
+    0x01, 0x00, 0x00, 0x55, 0x00, 0x0b, 0x00, 0x04, 0x03, 0x00, 0x01, 0x02,
+    0x00, 0x0a, 0x00, 0x1c, 0x00, 0x1a, 0x00, 0x17, 0x00, 0x19, 0x00, 0x1c,
+    0x00, 0x1b, 0x00, 0x18, 0x00, 0x1a, 0x00, 0x16, 0x00, 0x0e, 0x00, 0x0d,
+    0x00, 0x0b, 0x00, 0x0c, 0x00, 0x09, 0x00, 0x0a, 0x00, 0x23, 0x00, 0x00,
+    0x00, 0x0d, 0x00, 0x20, 0x00, 0x1e, 0x06, 0x01, 0x06, 0x02, 0x06, 0x03,
+    0x05, 0x01, 0x05, 0x02, 0x05, 0x03, 0x04, 0x01, 0x04, 0x02, 0x04, 0x03,
+    0x03, 0x01, 0x03, 0x02, 0x03, 0x03, 0x02, 0x01, 0x02, 0x02, 0x02, 0x03,
+    0x00, 0x0f, 0x00, 0x01, 0x01
+];
+
+    fn parse_client_hello(bytes: &[u8]) -> TlsClientHelloContents<'_> {
+        let (_, record) = parse_tls_plaintext(bytes).expect("could not parse record");
+        match record.msg.into_iter().next() {
+            Some(TlsMessage::Handshake(TlsMessageHandshake::ClientHello(ch))) => ch,
+            _ => panic!("expected a ClientHello"),
+        }
+    }
+
+    #[rustfmt::skip]
+static SERVER_HELLO_TLS12: &[u8] = &[
+    0x16, 0x03, 0x03, 0x00, 0x3b, 0x02, 0x00, 0x00, 0x37, 0x03, 0x03, 0x57,
+    0xc4, 0x57, 0xda, 0x9c, 0xd3, 0x24, 0x6d, 0x9d, 0x02, 0x26, 0xa2, 0xe5,
+    0x9a, 0xe8, 0xa5, 0x6f, 0x40, 0xad, 0x94, 0x30, 0xba, 0x49, 0x05, 0x3a,
+    0x1e, 0x1b, 0xe1, 0x94, 0xa1, 0xba, 0x41, 0x00, 0xc0, 0x2f, 0x00, 0x00,
+    0x0f, 0xff, 0x01, 0x00, 0x01, 0x00, 0x00, 0x23, 0x00, 0x00, 0x00, 0x0b,
+    0x00, 0x02, 0x01, 0x00,
+];
+
+    // ServerHello from RFC8448 section 3 (simple 1-RTT handshake)
+    #[rustfmt::skip]
+static SERVER_HELLO_TLS13: &[u8] = &[
+    0x16, 0x03, 0x03, 0x00, 0x5a, 0x02, 0x00, 0x00, 0x56, 0x03, 0x03, 0xa6,
+    0xaf, 0x06, 0xa4, 0x12, 0x18, 0x60, 0xdc, 0x5e, 0x6e, 0x60, 0x24, 0x9c,
+    0xd3, 0x4c, 0x95, 0x93, 0x0c, 0x8a, 0xc5, 0xcb, 0x14, 0x34, 0xda, 0xc1,
+    0x55, 0x77, 0x2e, 0xd3, 0xe2, 0x69, 0x28, 0x00, 0x13, 0x01, 0x00, 0x00,
+    0x2e, 0x00, 0x33, 0x00, 0x24, 0x00, 0x1d, 0x00, 0x20, 0xc9, 0x82, 0x88,
+    0x76, 0x11, 0x20, 0x95, 0xfe, 0x66, 0x76, 0x2b, 0xdb, 0xf7, 0xc6, 0x72,
+    0xe1, 0x56, 0xd6, 0xcc, 0x25, 0x3b, 0x83, 0x3d, 0xf1, 0xdd, 0x69, 0xb1,
+    0xb0, 0x4e, 0x75, 0x1f, 0x0f, 0x00, 0x2b, 0x00, 0x02, 0x03, 0x04,
+];
+
+    fn parse_handshake(bytes: &[u8]) -> TlsMessageHandshake<'_> {
+        let (_, record) = parse_tls_plaintext(bytes).expect("could not parse record");
+        match record.msg.into_iter().next() {
+            Some(TlsMessage::Handshake(msg)) => msg,
+            _ => panic!("expected a handshake message"),
+        }
+    }
+
+    fn to_hex(digest: &[u8]) -> String {
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_ja3_client_hello() {
+        let ch = parse_client_hello(CH);
+        let expected = "771,\
+49200-49196-49192-49188-49172-49162-165-163-161-159-107-106-105-104-57-56-55-54-136-135-134-\
+133-49202-49198-49194-49190-49167-49157-157-61-53-132-49199-49195-49191-49187-49171-49161-164-\
+162-160-158-103-64-63-62-51-50-49-48-154-153-152-151-69-68-67-66-49201-49197-49193-49189-\
+49166-49156-156-60-47-150-65-49169-49159-49164-49154-5-4-49170-49160-22-19-16-13-49165-49155-\
+10-255,11-10-35-13-15,23-25-28-27-24-26-22-14-13-11-12-9-10,0-1-2";
+        assert_eq!(ja3_string(&ch), expected);
+        assert_eq!(to_hex(&ja3_hash(&ch)), "1949310ab64717817ba98300d889efb3");
+    }
+
+    #[test]
+    fn test_ja3_grease() {
+        let random = &[0u8; 32];
+        #[rustfmt::skip]
+        let ext = &[
+            0x2a, 0x2a, 0x00, 0x00, // GREASE extension
+            0x00, 0x0a, 0x00, 0x06, 0x00, 0x04, 0x4a, 0x4a, 0x00, 0x1d, // supported groups
+            0x00, 0x0b, 0x00, 0x02, 0x01, 0x00, // ec point formats
+        ];
+        let ch = TlsClientHelloContents::new(
+            0x0303,
+            random,
+            None,
+            vec![TlsCipherSuiteID(0x0a0a), TlsCipherSuiteID(0x1301)],
+            vec![TlsCompressionID(0)],
+            Some(ext),
+        );
+        assert_eq!(ja3_string(&ch), "771,4865,10-11,29,0");
+    }
+
+    #[test]
+    fn test_ja3_no_extensions() {
+        let random = &[0u8; 32];
+        let ch = TlsClientHelloContents::new(
+            0x0301,
+            random,
+            None,
+            vec![TlsCipherSuiteID(0x002f), TlsCipherSuiteID(0x0035)],
+            vec![TlsCompressionID(0)],
+            None,
+        );
+        assert_eq!(ja3_string(&ch), "769,47-53,,,");
+    }
+
+    #[test]
+    fn test_ja3s_tls12() {
+        let sh = match parse_handshake(SERVER_HELLO_TLS12) {
+            TlsMessageHandshake::ServerHello(sh) => sh,
+            _ => panic!("expected a ServerHello"),
+        };
+        assert_eq!(ja3s_string(&sh), "771,49199,65281-35-11");
+        assert_eq!(to_hex(&ja3s_hash(&sh)), "8ba84e601ebaf7869044acdccb816514");
+    }
+
+    #[test]
+    fn test_ja3s_tls13() {
+        let sh = match parse_handshake(SERVER_HELLO_TLS13) {
+            TlsMessageHandshake::ServerHello(sh) => sh,
+            _ => panic!("expected a ServerHello"),
+        };
+        assert_eq!(ja3s_string(&sh), "771,4865,51-43");
+        assert_eq!(to_hex(&ja3s_hash(&sh)), "eb1d94daa7e0344597e756a1fb6e7054");
+    }
+
+    #[test]
+    fn test_ja3s_tls13_draft18() {
+        let sh = TlsServerHelloV13Draft18Contents {
+            version: TlsVersion::Tls13Draft18,
+            random: &[0u8; 32],
+            cipher: TlsCipherSuiteID(0x1301),
+            ext: Some(&[0x00, 0x28, 0x00, 0x00]),
+        };
+        assert_eq!(ja3s_string(&sh), "32530,4865,40");
+    }
+
+    #[test]
+    fn test_ja3s_grease() {
+        let sh = TlsServerHelloContents::new(
+            0x0303,
+            &[0u8; 32],
+            None,
+            0xc02f,
+            0,
+            Some(&[0x1a, 0x1a, 0x00, 0x00, 0xff, 0x01, 0x00, 0x01, 0x00]),
+        );
+        assert_eq!(ja3s_string(&sh), "771,49199,65281");
+    }
+
+    fn ext(ext_type: u16, data: &[u8]) -> Vec<u8> {
+        let mut v = ext_type.to_be_bytes().to_vec();
+        v.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        v.extend_from_slice(data);
+        v
+    }
+
+    #[test]
+    fn test_ja4_client_hello() {
+        // Chrome ClientHello, from the JA4 specification example
+        #[rustfmt::skip]
+        let ciphers = [
+            0x3a3a, 0x1301, 0x1302, 0x1303, 0xc02b, 0xc02f, 0xc02c, 0xc030, 0xcca9, 0xcca8,
+            0xc013, 0xc014, 0x009c, 0x009d, 0x002f, 0x0035,
+        ];
+        let ciphers = ciphers.iter().map(|&c| TlsCipherSuiteID(c)).collect();
+        let mut exts = Vec::new();
+        exts.extend(ext(0x1a1a, &[]));
+        exts.extend(ext(0x0000, b"\x00\x0e\x00\x00\x0bexample.com"));
+        exts.extend(ext(0x0017, &[]));
+        exts.extend(ext(0xff01, &[0x00]));
+        exts.extend(ext(0x000a, &[0x00, 0x04, 0xba, 0xba, 0x00, 0x1d]));
+        exts.extend(ext(0x000b, &[0x01, 0x00]));
+        exts.extend(ext(0x0023, &[]));
+        exts.extend(ext(0x0010, b"\x00\x0c\x02h2\x08http/1.1"));
+        exts.extend(ext(0x0005, &[0x01, 0x00, 0x00, 0x00, 0x00]));
+        #[rustfmt::skip]
+        exts.extend(ext(0x000d, &[
+            0x00, 0x10, 0x04, 0x03, 0x08, 0x04, 0x04, 0x01, 0x05, 0x03, 0x08, 0x05, 0x05, 0x01,
+            0x08, 0x06, 0x06, 0x01,
+        ]));
+        exts.extend(ext(0x0012, &[]));
+        exts.extend(ext(0x0033, &[0x00, 0x05, 0xba, 0xba, 0x00, 0x01, 0x00]));
+        exts.extend(ext(0x002d, &[0x01, 0x01]));
+        exts.extend(ext(0x002b, &[0x06, 0x8a, 0x8a, 0x03, 0x04, 0x03, 0x03]));
+        exts.extend(ext(0x001b, &[0x02, 0x00, 0x02]));
+        exts.extend(ext(0x4469, &[0x00, 0x03, 0x02, 0x68, 0x32]));
+        exts.extend(ext(0x0015, &[0x00; 16]));
+        exts.extend(ext(0x2a2a, &[0x00]));
+        let ch = TlsClientHelloContents::new(
+            0x0303,
+            &[0u8; 32],
+            None,
+            ciphers,
+            vec![TlsCompressionID(0)],
+            Some(&exts),
+        );
+        let expected = "t13d1516h2_\
+002f,0035,009c,009d,1301,1302,1303,c013,c014,c02b,c02c,c02f,c030,cca8,cca9_\
+0005,000a,000b,000d,0012,0015,0017,001b,0023,002b,002d,0033,4469,ff01_\
+0403,0804,0401,0503,0805,0501,0806,0601";
+        assert_eq!(ja4_raw(&ch), expected);
+        assert_eq!(ja4(&ch), "t13d1516h2_8daaf6152771_e5627efa2ab1");
+    }
+
+    #[test]
+    fn test_ja4_no_sni_no_alpn() {
+        let ch = parse_client_hello(CH);
+        assert_eq!(ja4(&ch), "t12i850500_972b7b87df62_a1e935682795");
+        assert!(ja4_raw(&ch).ends_with(
+            "_000a,000b,000d,000f,0023_\
+0601,0602,0603,0501,0502,0503,0401,0402,0403,0301,0302,0303,0201,0202,0203"
+        ));
+    }
+
+    #[test]
+    fn test_ja4_alpn_placeholders() {
+        let ch = |ext: &[u8]| {
+            let ch =
+                TlsClientHelloContents::new(0x0301, &[0u8; 32], None, vec![], vec![], Some(ext));
+            ja4(&ch)
+        };
+        // non-alphanumeric protocol: first and last hex digits
+        let e = ext(0x0010, &[0x00, 0x03, 0x02, 0xab, 0xcd]);
+        assert_eq!(ch(&e), "t10i0001ad_000000000000_000000000000");
+        // single character
+        let e = ext(0x0010, &[0x00, 0x02, 0x01, b'h']);
+        assert_eq!(ch(&e), "t10i0001hh_000000000000_000000000000");
+        // empty ALPN list
+        let e = ext(0x0010, &[0x00, 0x00]);
+        assert_eq!(ch(&e), "t10i000100_000000000000_000000000000");
+        // no extensions
+        let ch = TlsClientHelloContents::new(0x0303, &[0u8; 32], None, vec![], vec![], None);
+        assert_eq!(ja4(&ch), "t12i000000_000000000000_000000000000");
+    }
+
+    #[test]
+    fn test_ja4s() {
+        let sh = match parse_handshake(SERVER_HELLO_TLS12) {
+            TlsMessageHandshake::ServerHello(sh) => sh,
+            _ => panic!("expected a ServerHello"),
+        };
+        assert_eq!(ja4s_raw(&sh), "t120300_c02f_ff01,0023,000b");
+        assert_eq!(ja4s(&sh), "t120300_c02f_c734e0011098");
+        let sh = match parse_handshake(SERVER_HELLO_TLS13) {
+            TlsMessageHandshake::ServerHello(sh) => sh,
+            _ => panic!("expected a ServerHello"),
+        };
+        assert_eq!(ja4s(&sh), "t130200_1301_234ea6891581");
+    }
+} // mod tls_fingerprint
@@ -440,6 +440,299 @@ static SERVER_REPLY1: &[u8] = &[
         assert_eq!(parse_tls_plaintext(bytes), Ok((empty, expected)));
     }
 
+    #[test]
+    fn test_tls_client_hello_extensions_block() {
+        let mut msg = vec![0x01, 0x00, 0x00, 0x00, 0x03, 0x03];
+        msg.extend_from_slice(&[0x55; 32]);
+        msg.extend_from_slice(&[0x00, 0x00, 0x02, 0x13, 0x01, 0x01, 0x00]);
+        let parse = |msg: &mut Vec<u8>| {
+            msg[3] = (msg.len() - 4) as u8;
+            match parse_tls_message_handshake(msg) {
+                Ok((_, TlsMessage::Handshake(TlsMessageHandshake::ClientHello(ch)))) => {
+                    Ok(ch.ext.map(|e| e.to_vec()))
+                }
+                Ok(_) => panic!("expected ClientHello"),
+                Err(_) => Err(()),
+            }
+        };
+        // no extensions field
+        assert_eq!(parse(&mut msg), Ok(None));
+        // extensions field present, but empty
+        msg.extend_from_slice(&[0x00, 0x00]);
+        assert_eq!(parse(&mut msg), Ok(Some(vec![])));
+        // extensions field with one extension
+        let l = msg.len();
+        msg[l - 1] = 4;
+        msg.extend_from_slice(&[0x00, 0x17, 0x00, 0x00]);
+        assert_eq!(parse(&mut msg), Ok(Some(vec![0x00, 0x17, 0x00, 0x00])));
+        // truncated extensions field is an error, not an absent block
+        msg.truncate(l - 1);
+        assert!(parse(&mut msg).is_err());
+        msg.truncate(l - 2);
+        msg.extend_from_slice(&[0x00, 0x04, 0x00, 0x17]);
+        assert!(parse(&mut msg).is_err());
+    }
+
+    #[test]
+    fn test_tls_handshake_summary() {
+        let random = [0x55; 32];
+        #[rustfmt::skip]
+        let client_ext = &[
+            // server_name: example.com
+            0x00, 0x00, 0x00, 0x10, 0x00, 0x0e, 0x00, 0x00, 0x0b, 0x65, 0x78, 0x61, 0x6d, 0x70,
+            0x6c, 0x65, 0x2e, 0x63, 0x6f, 0x6d,
+            // alpn: h2, http/1.1
+            0x00, 0x10, 0x00, 0x0e, 0x00, 0x0c, 0x02, 0x68, 0x32, 0x08, 0x68, 0x74, 0x74, 0x70,
+            0x2f, 0x31, 0x2e, 0x31,
+            // supported_versions: TLS 1.3, TLS 1.2
+            0x00, 0x2b, 0x00, 0x05, 0x04, 0x03, 0x04, 0x03, 0x03,
+        ];
+        let ch = TlsClientHelloContents::new(
+            0x0303,
+            &random,
+            None,
+            vec![TlsCipherSuiteID(0x1301), TlsCipherSuiteID(0x1302)],
+            vec![TlsCompressionID(0)],
+            Some(client_ext),
+        );
+        #[rustfmt::skip]
+        let server_ext = &[
+            // supported_versions: TLS 1.3
+            0x00, 0x2b, 0x00, 0x02, 0x03, 0x04,
+            // key_share: x25519
+            0x00, 0x33, 0x00, 0x08, 0x00, 0x1d, 0x00, 0x04, 0x01, 0x02, 0x03, 0x04,
+            // alpn: h2
+            0x00, 0x10, 0x00, 0x05, 0x00, 0x03, 0x02, 0x68, 0x32,
+        ];
+        let sh = TlsServerHelloContents::new(0x0303, &random, None, 0x1302, 0, Some(server_ext));
+        let summary = summarize(&ch, &sh);
+        let expected = TlsHandshakeSummary {
+            version: TlsVersion::Tls13,
+            cipher: TlsCipherSuiteID(0x1302),
+            group: Some(NamedGroup::EcdhX25519),
+            alpn: Some(b"h2"),
+            sni: Some(b"example.com"),
+        };
+        assert_eq!(summary, expected);
+        assert_eq!(
+            summary.get_cipher().map(|c| c.name),
+            Some("TLS_AES_256_GCM_SHA384")
+        );
+        // TLS 1.2 server, without extensions
+        let sh = TlsServerHelloContents::new(0x0303, &random, None, 0xc02f, 0, None);
+        let summary = summarize(&ch, &sh);
+        assert_eq!(summary.version, TlsVersion::Tls12);
+        assert_eq!(summary.cipher, TlsCipherSuiteID(0xc02f));
+        assert_eq!(summary.group, None);
+        assert_eq!(summary.alpn, None);
+        assert_eq!(summary.sni, Some(&b"example.com"[..]));
+        // a malformed extension is ignored, the other ones are still used
+        #[rustfmt::skip]
+        let server_ext = &[
+            // supported_versions: TLS 1.3
+            0x00, 0x2b, 0x00, 0x02, 0x03, 0x04,
+            // max_fragment_length: empty
+            0x00, 0x01, 0x00, 0x00,
+            // key_share: x25519
+            0x00, 0x33, 0x00, 0x08, 0x00, 0x1d, 0x00, 0x04, 0x01, 0x02, 0x03, 0x04,
+            // alpn: h2
+            0x00, 0x10, 0x00, 0x05, 0x00, 0x03, 0x02, 0x68, 0x32,
+        ];
+        let sh = TlsServerHelloContents::new(0x0303, &random, None, 0x1302, 0, Some(server_ext));
+        let summary = summarize(&ch, &sh);
+        assert_eq!(summary, expected);
+    }
+
+    #[test]
+    fn test_tls_non_grease_versions() {
+        // supported_versions: GREASE, TLS 1.3, GREASE, TLS 1.2
+        let ext = &[
+            0x00, 0x2b, 0x00, 0x09, 0x08, 0x3a, 0x3a, 0x03, 0x04, 0xfa, 0xfa, 0x03, 0x03,
+        ];
+        let random = [0u8; 32];
+        let ch = TlsClientHelloContents::new(0x0303, &random, None, vec![], vec![], Some(ext));
+        assert_eq!(
+            ch.supported_versions(),
+            Some(vec![
+                TlsVersion(0x3a3a),
+                TlsVersion::Tls13,
+                TlsVersion(0xfafa),
+                TlsVersion::Tls12
+            ])
+        );
+        assert_eq!(
+            ch.non_grease_versions(),
+            Some(vec![TlsVersion::Tls13, TlsVersion::Tls12])
+        );
+        let ch = TlsClientHelloContents::new(0x0303, &random, None, vec![], vec![], None);
+        assert_eq!(ch.non_grease_versions(), None);
+    }
+
+    #[test]
+    fn test_tls_extension_ids() {
+        // SNI with an invalid body, GREASE, unknown type 0x1234, ec_point_formats
+        let ext = &[
+            0x00, 0x00, 0x00, 0x01, 0xff, 0x0a, 0x0a, 0x00, 0x00, 0x12, 0x34, 0x00, 0x02, 0xab,
+            0xcd, 0x00, 0x0b, 0x00, 0x02, 0x01, 0x00,
+        ];
+        let random = [0u8; 32];
+        let ch = TlsClientHelloContents::new(0x0303, &random, None, vec![], vec![], Some(ext));
+        assert_eq!(ch.extension_ids(), vec![0x0000, 0x0a0a, 0x1234, 0x000b]);
+        // parsing the extensions stops at the invalid SNI
+        assert_eq!(ch.extensions(), Some(vec![]));
+        // truncated block: the types read before the error are returned
+        let ch =
+            TlsClientHelloContents::new(0x0303, &random, None, vec![], vec![], Some(&ext[..12]));
+        assert_eq!(ch.extension_ids(), vec![0x0000, 0x0a0a]);
+        let ch = TlsClientHelloContents::new(0x0303, &random, None, vec![], vec![], None);
+        assert!(ch.extension_ids().is_empty());
+    }
+
+    #[test]
+    fn test_tls_max_supported_version() {
+        let random = [0u8; 32];
+        let build = |v, ext| TlsClientHelloContents::new(v, &random, None, vec![], vec![], ext);
+        // no extension: legacy version
+        assert_eq!(
+            build(0x0303, None).max_supported_version(),
+            TlsVersion::Tls12
+        );
+        assert_eq!(
+            build(0x0301, None).max_supported_version(),
+            TlsVersion::Tls10
+        );
+        // supported_versions: GREASE, TLS 1.2, TLS 1.3
+        let ext = &[
+            0x00, 0x2b, 0x00, 0x07, 0x06, 0x3a, 0x3a, 0x03, 0x03, 0x03, 0x04,
+        ];
+        let ch = build(0x0303, Some(ext));
+        assert_eq!(ch.max_supported_version(), TlsVersion::Tls13);
+        // supported_versions: TLS 1.3 draft 23, TLS 1.3, TLS 1.2
+        let ext = &[
+            0x00, 0x2b, 0x00, 0x07, 0x06, 0x7f, 0x17, 0x03, 0x04, 0x03, 0x03,
+        ];
+        let ch = build(0x0303, Some(ext));
+        assert_eq!(ch.max_supported_version(), TlsVersion::Tls13);
+        // supported_versions: TLS 1.3 draft 23, TLS 1.2
+        let ext = &[0x00, 0x2b, 0x00, 0x05, 0x04, 0x7f, 0x17, 0x03, 0x03];
+        let ch = build(0x0303, Some(ext));
+        assert_eq!(ch.max_supported_version(), TlsVersion(0x7f17));
+        // extensions without supported_versions
+        let ext = &[0x00, 0x17, 0x00, 0x00];
+        let ch = build(0x0303, Some(ext));
+        assert_eq!(ch.max_supported_version(), TlsVersion::Tls12);
+    }
+
+    #[test]
+    fn test_tls_hello_random() {
+        let (_, record) = parse_tls_plaintext(CH).expect("parsing failed");
+        let ch = match record.msg[0] {
+            TlsMessage::Handshake(TlsMessageHandshake::ClientHello(ref ch)) => ch.clone(),
+            _ => panic!("expected ClientHello"),
+        };
+        assert_eq!(ch.rand_time(), 0xb29d_d787);
+        assert_eq!(ch.rand_bytes(), &CH[15..43]);
+        let mut random = ch.rand_time().to_be_bytes().to_vec();
+        random.extend_from_slice(ch.rand_bytes());
+        assert_eq!(&random[..], ClientHello::random(&ch));
+        assert_eq!(ch.random_array().map(|r| &r[..]), Some(&CH[11..43]));
+
+        let (_, record) = parse_tls_plaintext(&SERVER_REPLY1[0..64]).expect("parsing failed");
+        let sh = match record.msg[0] {
+            TlsMessage::Handshake(TlsMessageHandshake::ServerHello(ref sh)) => sh.clone(),
+            _ => panic!("expected ServerHello"),
+        };
+        let mut random = sh.rand_time().to_be_bytes().to_vec();
+        random.extend_from_slice(sh.rand_bytes());
+        assert_eq!(&random[..], &SERVER_REPLY1[11..43]);
+        assert_eq!(
+            sh.random_array().map(|r| &r[..]),
+            Some(&SERVER_REPLY1[11..43])
+        );
+        // random with an invalid size
+        let sh = TlsServerHelloContents::new(0x0303, &[0x01, 0x02], None, 0xc02f, 0, None);
+        assert_eq!(sh.random_array(), None);
+        assert_eq!(sh.rand_time(), 0);
+        assert!(sh.rand_bytes().is_empty());
+    }
+
+    #[test]
+    fn test_tls_handshake_with_raw() {
+        // ClientHello (with extensions) followed by a ServerHelloDone
+        let mut buf = CH[5..].to_vec();
+        buf.extend_from_slice(&[0x0e, 0x00, 0x00, 0x00]);
+        let (rem, res) = parse_tls_message_handshake_with_raw(&buf).expect("parsing failed");
+        assert_eq!(res.raw, &CH[5..]);
+        assert_eq!(rem, &[0x0e, 0x00, 0x00, 0x00]);
+        let (_, msg) = parse_tls_message_handshake(res.raw).expect("re-parsing failed");
+        assert_eq!(msg, TlsMessage::Handshake(res.msg));
+        let (rem, res) = parse_tls_message_handshake_with_raw(rem).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(res.raw, &buf[buf.len() - 4..]);
+        assert_eq!(res.msg, TlsMessageHandshake::ServerDone(&[]));
+        // the transcript is the concatenation of the raw messages
+        let mut transcript = Vec::new();
+        let mut i = &buf[..];
+        while !i.is_empty() {
+            let (rem, res) = parse_tls_message_handshake_with_raw(i).expect("parsing failed");
+            transcript.extend_from_slice(res.raw);
+            i = rem;
+        }
+        assert_eq!(transcript, buf);
+    }
+
+    #[test]
+    fn test_tls_handshake_messages_iter() {
+        // ClientHello (with extensions) followed by a ServerHelloDone
+        let mut buf = CH[5..].to_vec();
+        buf.extend_from_slice(&[0x0e, 0x00, 0x00, 0x00]);
+        let msgs: Vec<_> = parse_tls_handshake_messages(&buf)
+            .collect::<Result<_, _>>()
+            .expect("parsing failed");
+        assert_eq!(msgs.len(), 2);
+        match msgs[0] {
+            TlsMessageHandshake::ClientHello(ref ch) => {
+                assert_eq!(ch.version, TlsVersion::Tls12);
+                assert!(ch.ext.is_some());
+            }
+            _ => panic!("expected ClientHello"),
+        }
+        assert_eq!(msgs[1], TlsMessageHandshake::ServerDone(&[]));
+        // a truncated message is an error, and ends the iteration
+        let mut it = parse_tls_handshake_messages(&buf[..buf.len() - 5]);
+        assert!(it.next().unwrap().is_err());
+        assert!(it.next().is_none());
+        assert!(parse_tls_handshake_messages(&[]).next().is_none());
+    }
+
+    #[test]
+    fn test_tls_downgrade_sentinel() {
+        let (_, record) = parse_tls_plaintext(&SERVER_REPLY1[0..64]).expect("parsing failed");
+        let mut sh = match record.msg[0] {
+            TlsMessage::Handshake(TlsMessageHandshake::ServerHello(ref sh)) => sh.clone(),
+            _ => panic!("expected ServerHello"),
+        };
+        assert_eq!(sh.downgrade_sentinel(), None);
+        let mut random = [0x55; 32];
+        random[24..].copy_from_slice(b"DOWNGRD\x01");
+        sh.random = &random;
+        assert_eq!(sh.downgrade_sentinel(), Some(DowngradeSentinel::Tls12));
+        let mut random = [0x55; 32];
+        random[24..].copy_from_slice(b"DOWNGRD\x00");
+        sh.random = &random;
+        assert_eq!(
+            sh.downgrade_sentinel(),
+            Some(DowngradeSentinel::Tls11OrBelow)
+        );
+        // the sentinel must be at the end of the random
+        let mut random = [0x55; 32];
+        random[..8].copy_from_slice(b"DOWNGRD\x01");
+        sh.random = &random;
+        assert_eq!(sh.downgrade_sentinel(), None);
+        sh.random = &[];
+        assert_eq!(sh.downgrade_sentinel(), None);
+    }
+
     #[test]
     fn test_tls_record_certificate() {
         let empty = &b""[..];
@@ -560,6 +853,20 @@ static CLIENT_REPLY1: &[u8] = &[
         assert_eq!(parse_tls_plaintext(bytes), Ok((empty, expected)));
     }
 
+    #[test]
+    fn test_tls_message_change_cipher_spec() {
+        let empty = &b""[..];
+        assert_eq!(
+            parse_tls_message_change_cipher_spec(&[0x01]),
+            Ok((empty, TlsMessage::ChangeCipherSpec))
+        );
+        // invalid value
+        assert!(parse_tls_message_change_cipher_spec(&[0x02]).is_err());
+        // invalid length
+        assert!(parse_tls_message_change_cipher_spec(&[]).is_err());
+        assert!(parse_tls_message_change_cipher_spec(&[0x01, 0x01]).is_err());
+    }
+
     #[test]
     fn test_tls_record_encryptedhandshake() {
         let empty = &b""[..];
@@ -850,4 +1157,517 @@ static SERVER_STATUS_RESPONSE: &[u8] = &[
         let res = parse_tls_record_with_header(bytes, &hdr);
         assert_eq!(res, Ok((empty, expected)));
     }
+
+    #[test]
+    fn test_tls_version_conversions() {
+        assert_eq!(TlsVersion::from(0x0303), TlsVersion::Tls12);
+        assert_eq!(u16::from(TlsVersion::DTls12), 0xfefd);
+        assert_eq!(format!("{}", TlsVersion::Tls13), "Tls13");
+        assert_eq!(
+            format!("{:?}", TlsVersion(0x1234)),
+            "TlsVersion(4660 / 0x1234)"
+        );
+        let v: TlsVersion = 0x0301.into();
+        assert!(matches!(v, TlsVersion::Tls10));
+    }
+
+    #[test]
+    fn test_tls_message_finished() {
+        let empty = &b""[..];
+        let bytes = &[
+            0x14, 0x00, 0x00, 0x0c, 0x5b, 0x1c, 0x3e, 0x9a, 0x50, 0x6d, 0x2d, 0x38, 0xc2, 0x46,
+            0x7f, 0x13,
+        ];
+        let expected = TlsMessage::Handshake(TlsMessageHandshake::Finished(TlsFinishedContents {
+            verify_data: &bytes[4..],
+        }));
+        assert_eq!(parse_tls_message_handshake(bytes), Ok((empty, expected)));
+    }
+
+    #[test]
+    fn test_grease_values() {
+        for n in 0..16u16 {
+            let v = (n << 12) | 0x0a00 | (n << 4) | 0x0a;
+            assert!(is_grease_u16(v), "{:04x} should be GREASE", v);
+            assert!(!is_grease_u16(v + 1));
+            assert!(!is_grease_u16(v - 1));
+        }
+        for &v in &[0x0a1a, 0x1a0a, 0x0b0b, 0xabab, 0x0000, 0x1301, 0xfa0a] {
+            assert!(!is_grease_u16(v), "{:04x} should not be GREASE", v);
+        }
+        for &v in &[0x0b, 0x2a, 0x49, 0x68, 0x87, 0xa6, 0xc5, 0xe4] {
+            assert!(is_grease_u8(v));
+        }
+        for &v in &[0x00, 0x01, 0x0a, 0x0c, 0x2b, 0xff] {
+            assert!(!is_grease_u8(v));
+        }
+    }
+
+    #[test]
+    fn test_non_grease_ciphers() {
+        let ch = TlsClientHelloContents::new(
+            0x0303,
+            &[0; 32],
+            None,
+            vec![
+                TlsCipherSuiteID(0x2a2a),
+                TlsCipherSuiteID(0x1301),
+                TlsCipherSuiteID(0xc02f),
+                TlsCipherSuiteID(0xfafa),
+            ],
+            vec![TlsCompressionID::Null],
+            None,
+        );
+        let v: Vec<_> = ch.non_grease_ciphers().collect();
+        assert_eq!(v, vec![TlsCipherSuiteID(0x1301), TlsCipherSuiteID(0xc02f)]);
+    }
+
+    #[test]
+    fn test_tls_record_owned() {
+        let record = {
+            let bytes = SERVER_REPLY1[0..64].to_vec();
+            let (rem, record) = parse_tls_plaintext_owned(&bytes).expect("parsing failed");
+            assert!(rem.is_empty());
+            record
+        };
+        // input buffer has been dropped
+        assert_eq!(record.hdr.record_type, TlsRecordType::Handshake);
+        assert_eq!(record.data, &SERVER_REPLY1[5..64]);
+        let (_, msg) = record.parse_messages().expect("parsing messages failed");
+        let (_, expected) = parse_tls_plaintext(&SERVER_REPLY1[0..64]).expect("parsing failed");
+        assert_eq!(msg, expected.msg);
+        // raw record
+        let (_, raw) = parse_tls_raw_record(&SERVER_REPLY1[0..64]).expect("parsing failed");
+        assert_eq!(raw.to_owned_record(), record);
+        // invalid messages are rejected
+        let mut v = SERVER_REPLY1[0..64].to_vec();
+        v[5] = 0xff;
+        assert!(parse_tls_plaintext_owned(&v).is_err());
+    }
+
+    #[test]
+    fn test_tls_handshake_reassembly_split() {
+        // ClientHello split across two records
+        let mut r = TlsHandshakeReassembler::new();
+        let data = &CH[5..];
+        assert_eq!(r.add_record_data(&data[..2]), Ok(()));
+        assert_eq!(r.next_message(), None);
+        assert_eq!(r.add_record_data(&data[2..100]), Ok(()));
+        assert_eq!(r.next_message(), None);
+        assert_eq!(r.buffered_len(), 100);
+        assert_eq!(r.add_record_data(&data[100..]), Ok(()));
+        let msg = r.next_message().expect("message is not complete");
+        assert_eq!(msg, data);
+        assert!(r.is_empty());
+        let (rem, msg) = parse_tls_message_handshake(&msg).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert!(matches!(
+            msg,
+            TlsMessage::Handshake(TlsMessageHandshake::ClientHello(_))
+        ));
+    }
+
+    #[test]
+    fn test_tls_handshake_reassembly_coalesced() {
+        // ServerHello and ServerHelloDone in the same record
+        let mut r = TlsHandshakeReassembler::default();
+        let server_hello = &SERVER_REPLY1[5..64];
+        let server_done = &SERVER_REPLY1[SERVER_REPLY1.len() - 4..];
+        let mut data = server_hello.to_vec();
+        data.extend_from_slice(server_done);
+        // and the start of the next message
+        data.extend_from_slice(&[0x0b, 0x00]);
+        assert_eq!(r.add_record_data(&data), Ok(()));
+        assert_eq!(r.next_message().as_deref(), Some(server_hello));
+        assert_eq!(r.next_message().as_deref(), Some(server_done));
+        assert_eq!(r.next_message(), None);
+        assert_eq!(r.buffered_len(), 2);
+        r.clear();
+        assert!(r.is_empty());
+    }
+
+    #[test]
+    fn test_tls_handshake_reassembly_too_large() {
+        let mut r = TlsHandshakeReassembler::new().with_max_message_len(1024);
+        assert_eq!(r.add_record_data(&SERVER_REPLY1[5..64]), Ok(()));
+        // Certificate message (3077 bytes)
+        let res = r.add_record_data(&SERVER_REPLY1[69..100]);
+        assert_eq!(res, Err(TlsReassemblyError::MessageTooLarge));
+        // previous data is kept
+        assert_eq!(r.buffered_len(), 59);
+    }
+
+    #[test]
+    fn test_tls_stream_byte_by_byte() {
+        let mut state = TlsParserState::new();
+        assert!(state.needs_more_data());
+        for (idx, b) in CH.iter().enumerate() {
+            assert_eq!(state.next_record(), Ok(None));
+            assert_eq!(state.buffered_len(), idx);
+            state.push(&[*b]);
+        }
+        let (_, expected) = parse_tls_raw_record(CH).expect("parsing failed");
+        assert_eq!(state.next_record(), Ok(Some(expected)));
+        assert_eq!(state.consumed(), CH.len());
+        assert_eq!(state.buffered_len(), 0);
+        assert_eq!(state.next_record(), Ok(None));
+    }
+
+    #[test]
+    fn test_tls_stream_chunks() {
+        // all records of SERVER_REPLY1, pushed in chunks not aligned with records
+        let mut expected = Vec::new();
+        let mut i = SERVER_REPLY1;
+        while !i.is_empty() {
+            let (rem, record) = parse_tls_raw_record(i).expect("parsing failed");
+            expected.push(record.to_owned_record());
+            i = rem;
+        }
+        let mut state = TlsParserState::default();
+        let mut records = Vec::new();
+        for chunk in SERVER_REPLY1.chunks(100) {
+            state.push(chunk);
+            while let Some(record) = state.next_record().expect("invalid record") {
+                records.push(record.to_owned_record());
+            }
+        }
+        assert_eq!(records, expected);
+        assert_eq!(state.consumed(), SERVER_REPLY1.len());
+        assert!(state.needs_more_data());
+    }
+
+    #[test]
+    fn test_tls_stream_invalid() {
+        let mut state = TlsParserState::new();
+        // record length larger than MAX_RECORD_LEN
+        state.push(&[0x16, 0x03, 0x01, 0xff, 0xff, 0x00]);
+        assert_eq!(state.next_record(), Err(TlsStreamError::InvalidRecord));
+        assert!(!state.needs_more_data());
+        assert_eq!(state.buffered_len(), 6);
+        state.clear();
+        state.push(CH);
+        assert!(state.next_record().expect("invalid record").is_some());
+    }
+
+    #[test]
+    fn test_looks_like_tls() {
+        assert!(looks_like_tls(CH));
+        assert!(looks_like_tls(SERVER_REPLY1));
+        assert!(looks_like_tls(&CH[..3]));
+        // not enough bytes
+        assert!(!looks_like_tls(&CH[..2]));
+        // HTTP request
+        assert!(!looks_like_tls(
+            b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n"
+        ));
+        // random bytes
+        assert!(!looks_like_tls(&[0x9c, 0x2f, 0x51, 0xe0, 0x03, 0x7a, 0x44]));
+        // invalid version, or zero/too large length
+        assert!(!looks_like_tls(&[0x16, 0x03, 0x05, 0x00, 0x10]));
+        assert!(!looks_like_tls(&[0x16, 0x01, 0x00, 0x00, 0x10]));
+        assert!(!looks_like_tls(&[0x16, 0x03, 0x01, 0x00, 0x00]));
+        assert!(!looks_like_tls(&[0x17, 0x03, 0x03, 0xff, 0xff]));
+        // SSL 2.0 framing is not a TLS record
+        assert!(!looks_like_tls(&[0x80, 0x2e, 0x01, 0x00, 0x02]));
+    }
+
+    #[test]
+    fn test_tls_record_application_data() {
+        let bytes = &[0x17, 0x03, 0x03, 0x00, 0x05, 0xde, 0xad, 0xbe, 0xef, 0x42];
+        let (rem, record) = parse_tls_plaintext(bytes).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(record.msg.len(), 1);
+        match record.msg[0] {
+            TlsMessage::ApplicationData(ref app) => {
+                assert_eq!(app.blob.len(), record.hdr.len as usize);
+                assert_eq!(app.blob, &bytes[5..]);
+            }
+            _ => panic!("expected ApplicationData"),
+        }
+        // empty record
+        let (_, record) =
+            parse_tls_plaintext(&[0x17, 0x03, 0x03, 0x00, 0x00]).expect("parsing failed");
+        assert_eq!(
+            record.msg,
+            vec![TlsMessage::ApplicationData(TlsMessageApplicationData {
+                blob: &[]
+            })]
+        );
+    }
+
+    #[test]
+    fn test_tls_heartbleed() {
+        // request, with a 3-bytes payload and 16 bytes of padding
+        let mut bytes = vec![
+            0x18, 0x03, 0x02, 0x00, 0x16, 0x01, 0x00, 0x03, b'a', b'b', b'c',
+        ];
+        bytes.extend_from_slice(&[0; 16]);
+        let (_, record) = parse_tls_plaintext(&bytes).expect("parsing failed");
+        match record.msg[0] {
+            TlsMessage::Heartbeat(ref hb) => {
+                assert_eq!(hb.payload, b"abc");
+                assert!(!is_potential_heartbleed(hb, record.hdr.len as usize));
+                // padding is shorter than the 16 bytes minimum
+                assert!(is_potential_heartbleed(hb, record.hdr.len as usize - 1));
+            }
+            _ => panic!("expected Heartbeat"),
+        }
+        // request declaring a 16kB payload, in a 3-bytes record
+        let bytes = &[0x18, 0x03, 0x02, 0x00, 0x03, 0x01, 0x40, 0x00];
+        let (_, record) = parse_tls_plaintext(bytes).expect("parsing failed");
+        match record.msg[0] {
+            TlsMessage::Heartbeat(ref hb) => {
+                assert!(hb.payload.is_empty());
+                assert!(is_potential_heartbleed(hb, record.hdr.len as usize));
+            }
+            _ => panic!("expected Heartbeat"),
+        }
+    }
+
+    #[test]
+    fn test_tls_records_iter() {
+        let bytes = &[
+            0x14, 0x03, 0x03, 0x00, 0x01, 0x01, // ChangeCipherSpec
+            0x17, 0x03, 0x03, 0x00, 0x02, 0xde, 0xad, // ApplicationData
+            0x15, 0x03, 0x03, 0x00, 0x02, 0x01, 0x00, // Alert
+            0x17, 0x03, 0x03, 0x00, 0x04, 0xbe, 0xef, // partial record
+        ];
+        let mut iter = parse_tls_records(bytes);
+        let types: Vec<_> = iter
+            .by_ref()
+            .map(|r| r.expect("parsing failed").hdr.record_type)
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                TlsRecordType::ChangeCipherSpec,
+                TlsRecordType::ApplicationData,
+                TlsRecordType::Alert
+            ]
+        );
+        assert_eq!(iter.remaining(), &bytes[20..]);
+        assert!(iter.next().is_none());
+        // only complete records
+        let mut iter = parse_tls_records(&bytes[..20]);
+        assert_eq!(iter.by_ref().count(), 3);
+        assert!(iter.remaining().is_empty());
+        // invalid record: error, then stop
+        let mut iter = parse_tls_records(&[0x17, 0x03, 0x03, 0xff, 0xff, 0x00]);
+        assert!(matches!(iter.next(), Some(Err(_))));
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_tls_record_type() {
+        assert_eq!(
+            TlsRecordType::from_u8(20),
+            Some(TlsRecordType::ChangeCipherSpec)
+        );
+        assert_eq!(TlsRecordType::from_u8(21), Some(TlsRecordType::Alert));
+        assert_eq!(TlsRecordType::from_u8(22), Some(TlsRecordType::Handshake));
+        assert_eq!(
+            TlsRecordType::from_u8(23),
+            Some(TlsRecordType::ApplicationData)
+        );
+        assert_eq!(TlsRecordType::from_u8(24), Some(TlsRecordType::Heartbeat));
+        assert_eq!(TlsRecordType::from_u8(25), None);
+        assert_eq!(TlsRecordType::from_u8(0), None);
+        let (_, record) = parse_tls_plaintext(CH).expect("parsing failed");
+        assert_eq!(record.hdr.record_type(), TlsRecordType::Handshake);
+    }
+
+    #[test]
+    fn test_tls_handshake_type() {
+        assert_eq!(
+            TlsHandshakeType::from_u8(1),
+            Some(TlsHandshakeType::ClientHello)
+        );
+        assert_eq!(
+            TlsHandshakeType::from_u8(0x43),
+            Some(TlsHandshakeType::NextProtocol)
+        );
+        assert_eq!(TlsHandshakeType::from_u8(0x07), None);
+        assert_eq!(TlsHandshakeType::from_u8(0xff), None);
+        let mut hello = [0u8; 38];
+        hello[..2].copy_from_slice(&[0x03, 0x03]);
+        #[rustfmt::skip]
+        let messages: &[(u8, &[u8])] = &[
+            (0x00, &[]),
+            (0x01, &hello),
+            (0x02, &hello),
+            (0x04, &[0x00, 0x00, 0x00, 0x01]),
+            (0x05, &[]),
+            (0x06, &[0x03, 0x04, 0x13, 0x01]),
+            (0x08, &[0x00, 0x00]),
+            (0x0b, &[0x00, 0x00, 0x00]),
+            (0x0c, &[0x01, 0x02]),
+            (0x0d, &[0x00, 0x00, 0x00, 0x00, 0x00]),
+            (0x0e, &[]),
+            (0x0f, &[0x01, 0x02]),
+            (0x10, &[0x01, 0x02]),
+            (0x14, &[0x01, 0x02]),
+            (0x16, &[0x01, 0x00, 0x00, 0x00]),
+            (0x18, &[0x00]),
+            (0x19, &[0x00, 0x01, 0x00, 0x00, 0x10, 0x00, 0x00, 0x01, 0xff]),
+            (0x43, &[0x00, 0x00]),
+        ];
+        for (ht, content) in messages {
+            let mut bytes = vec![*ht, 0x00, 0x00, content.len() as u8];
+            bytes.extend_from_slice(content);
+            let (_, msg) = parse_tls_message_handshake(&bytes).expect("parsing failed");
+            match msg {
+                TlsMessage::Handshake(m) => assert_eq!(m.handshake_type(), TlsHandshakeType(*ht)),
+                _ => panic!("not a handshake message"),
+            }
+        }
+        // TLS 1.3 variants
+        #[rustfmt::skip]
+        let messages: &[(u8, &[u8])] = &[
+            (0x04, &[0, 0, 0, 1, 0, 0, 0, 2, 0x00, 0x00, 0x01, 0xff, 0x00, 0x00]),
+            (0x0b, &[0x00, 0x00, 0x00, 0x00]),
+            (0x0d, &[0x00, 0x00, 0x04, 0x00, 0x0d, 0x00, 0x00]),
+        ];
+        for (ht, content) in messages {
+            let mut bytes = vec![*ht, 0x00, 0x00, content.len() as u8];
+            bytes.extend_from_slice(content);
+            let (_, msg) = parse_tls_message_handshake_with_version(&bytes, TlsVersion::Tls13)
+                .expect("parsing failed");
+            match msg {
+                TlsMessage::Handshake(m) => assert_eq!(m.handshake_type(), TlsHandshakeType(*ht)),
+                _ => panic!("not a handshake message"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_tls_message_heartbeat() {
+        let mut bytes = vec![0x18, 0x03, 0x03, 0x00, 0x17, 0x01, 0x00, 0x04];
+        bytes.extend_from_slice(b"abcd");
+        bytes.extend_from_slice(&[0x5a; 16]);
+        let (rem, record) = parse_tls_plaintext(&bytes).expect("parsing failed");
+        assert!(rem.is_empty());
+        let expected = TlsMessageHeartbeat {
+            heartbeat_type: TlsHeartbeatMessageType::HeartBeatRequest,
+            payload_len: 4,
+            payload: b"abcd",
+            padding: &[0x5a; 16],
+        };
+        assert_eq!(record.msg, vec![TlsMessage::Heartbeat(expected)]);
+    }
+
+    #[test]
+    fn test_tls_message_heartbeat_overread() {
+        // declared payload length (16384) exceeds the record length (heartbleed)
+        let bytes = &[
+            0x18, 0x03, 0x02, 0x00, 0x06, 0x01, 0x40, 0x00, 0x41, 0x42, 0x43,
+        ];
+        let (rem, record) = parse_tls_plaintext(bytes).expect("parsing failed");
+        assert!(rem.is_empty());
+        match record.msg[0] {
+            TlsMessage::Heartbeat(ref hb) => {
+                assert_eq!(hb.payload_len, 0x4000);
+                assert_eq!(hb.payload, b"ABC");
+                assert!(hb.padding.is_empty());
+                assert!((hb.payload_len as usize) > hb.payload.len());
+            }
+            _ => panic!("expected heartbeat message"),
+        }
+        // record too short for a heartbeat header
+        assert!(parse_tls_plaintext(&[0x18, 0x03, 0x02, 0x00, 0x02, 0x01, 0x40]).is_err());
+    }
+
+    #[test]
+    fn test_tls_record_alert_all() {
+        // warning (close_notify) and fatal (handshake_failure)
+        let bytes = &[0x01, 0x00, 0x02, 0x28];
+        let (rem, alerts) = parse_tls_record_alert_all(bytes).expect("parsing failed");
+        assert!(rem.is_empty());
+        let expected = vec![
+            TlsMessageAlert {
+                severity: TlsAlertSeverity::Warning,
+                code: TlsAlertDescription::CloseNotify,
+            },
+            TlsMessageAlert {
+                severity: TlsAlertSeverity::Fatal,
+                code: TlsAlertDescription::HandshakeFailure,
+            },
+        ];
+        assert_eq!(alerts, expected);
+        // trailing odd byte
+        assert!(parse_tls_record_alert_all(&bytes[..3]).is_err());
+        // empty payload
+        assert!(parse_tls_record_alert_all(&[]).is_err());
+    }
+
+    #[test]
+    fn test_tls_client_hello_finders() {
+        #[rustfmt::skip]
+        let ext = &[
+            // server_name
+            0x00, 0x00, 0x00, 0x10, 0x00, 0x0e, 0x00, 0x00, 0x0b, 0x65, 0x78, 0x61,
+            0x6d, 0x70, 0x6c, 0x65, 0x2e, 0x63, 0x6f, 0x6d,
+            // application_layer_protocol_negotiation
+            0x00, 0x10, 0x00, 0x0e, 0x00, 0x0c, 0x02, 0x68, 0x32, 0x08, 0x68, 0x74,
+            0x74, 0x70, 0x2f, 0x31, 0x2e, 0x31,
+            // supported_versions
+            0x00, 0x2b, 0x00, 0x05, 0x04, 0x03, 0x04, 0x03, 0x03,
+            // signature_algorithms
+            0x00, 0x0d, 0x00, 0x06, 0x00, 0x04, 0x04, 0x03, 0x08, 0x04,
+        ];
+        let random = &[0; 32];
+        let ch = TlsClientHelloContents::new(0x0303, random, None, vec![], vec![], Some(ext));
+        assert_eq!(ch.extensions().map(|v| v.len()), Some(4));
+        assert_eq!(
+            ch.find_sni(),
+            Some(vec![ServerName::host_name(b"example.com")])
+        );
+        assert_eq!(ch.find_alpn(), Some(vec![&b"h2"[..], &b"http/1.1"[..]]));
+        assert_eq!(
+            ch.supported_versions(),
+            Some(vec![TlsVersion::Tls13, TlsVersion::Tls12])
+        );
+        assert_eq!(ch.signature_algorithms(), Some(vec![0x0403, 0x0804]));
+        // missing extensions
+        let ch =
+            TlsClientHelloContents::new(0x0303, random, None, vec![], vec![], Some(&ext[..20]));
+        assert!(ch.find_sni().is_some());
+        assert_eq!(ch.find_alpn(), None);
+        assert_eq!(ch.supported_versions(), None);
+        let ch = TlsClientHelloContents::new(0x0303, random, None, vec![], vec![], None);
+        assert_eq!(ch.extensions(), None);
+        assert_eq!(ch.find_sni(), None);
+    }
+
+    #[test]
+    fn test_tls_message_status_response_ocsp() {
+        let (rem, msg) =
+            parse_tls_message_handshake(SERVER_STATUS_RESPONSE).expect("parsing failed");
+        assert!(rem.is_empty());
+        match msg {
+            TlsMessage::Handshake(TlsMessageHandshake::CertificateStatus(ref status)) => {
+                let response = status.ocsp_response().expect("not an OCSP response");
+                // DER SEQUENCE
+                assert_eq!(response[0], 0x30);
+                assert_eq!(response.len(), 0x063b);
+            }
+            _ => panic!("expected CertificateStatus message"),
+        }
+        let status = TlsCertificateStatusContents {
+            status_type: 2,
+            blob: &[],
+        };
+        assert_eq!(status.ocsp_response(), None);
+    }
+
+    #[test]
+    fn test_tls_message_status_response_length_mismatch() {
+        // response length is longer than the message
+        let bytes = &[0x16, 0x00, 0x00, 0x06, 0x01, 0x00, 0x00, 0x03, 0x30, 0x00];
+        assert!(parse_tls_message_handshake(bytes).is_err());
+        // response length is shorter than the message
+        let bytes = &[0x16, 0x00, 0x00, 0x06, 0x01, 0x00, 0x00, 0x01, 0x30, 0x00];
+        assert!(parse_tls_message_handshake(bytes).is_err());
+        let bytes = &[0x16, 0x00, 0x00, 0x06, 0x01, 0x00, 0x00, 0x02, 0x30, 0x00];
+        assert!(parse_tls_message_handshake(bytes).is_ok());
+    }
 } // mod tls_handshake
@@ -0,0 +1,116 @@
+mod tls_limits {
+    use tls_parser::*;
+
+    // ServerHelloDone
+    #[rustfmt::skip]
+static RECORD_SHD: &[u8] = &[
+    0x16, 0x03, 0x03, 0x00, 0x04, 0x0e, 0x00, 0x00, 0x00,
+];
+
+    // ChangeCipherSpec
+    #[rustfmt::skip]
+static RECORD_CCS: &[u8] = &[
+    0x14, 0x03, 0x03, 0x00, 0x01, 0x01,
+];
+
+    // ClientHello with 3 cipher suites, 1 compression method and no extensions
+    #[rustfmt::skip]
+static RECORD_CH: &[u8] = &[
+    0x16, 0x03, 0x01, 0x00, 0x31, 0x01, 0x00, 0x00, 0x2d, 0x03, 0x03, 0x00,
+    0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c,
+    0x0d, 0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18,
+    0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f, 0x00, 0x00, 0x06, 0xc0, 0x2f,
+    0xc0, 0x30, 0x00, 0x9c, 0x01, 0x00,
+];
+
+    fn concat(records: &[&[u8]]) -> Vec<u8> {
+        records.iter().flat_map(|r| r.iter().copied()).collect()
+    }
+
+    #[test]
+    fn test_safe_parse_records() {
+        let mut input = concat(&[RECORD_CH, RECORD_CCS, RECORD_SHD]);
+        // add an incomplete record
+        input.extend_from_slice(&RECORD_SHD[..4]);
+        let (rem, records) =
+            safe_parse_tls_records(&input, &TlsParseLimits::default()).expect("parsing failed");
+        assert_eq!(records.len(), 3);
+        assert_eq!(rem, &RECORD_SHD[..4]);
+    }
+
+    #[test]
+    fn test_safe_parse_too_many_records() {
+        let input = concat(&[RECORD_CCS; 4]);
+        let limits = TlsParseLimits {
+            max_records: 3,
+            ..TlsParseLimits::default()
+        };
+        assert_eq!(
+            safe_parse_tls_records(&input, &limits),
+            Err(TlsSafeParseError::TooManyRecords)
+        );
+    }
+
+    #[test]
+    fn test_safe_parse_too_many_handshake_messages() {
+        let input = concat(&[RECORD_SHD; 3]);
+        let limits = TlsParseLimits {
+            max_handshake_messages: 2,
+            ..TlsParseLimits::default()
+        };
+        assert_eq!(
+            safe_parse_tls_records(&input, &limits),
+            Err(TlsSafeParseError::TooManyHandshakeMessages)
+        );
+    }
+
+    #[test]
+    fn test_safe_parse_too_many_list_items() {
+        let limits = TlsParseLimits {
+            max_list_items: 2,
+            ..TlsParseLimits::default()
+        };
+        assert_eq!(
+            safe_parse_tls_records(RECORD_CH, &limits),
+            Err(TlsSafeParseError::TooManyListItems)
+        );
+    }
+
+    #[test]
+    fn test_safe_parse_invalid_record() {
+        let input = &[0x16, 0x03, 0x03, 0xff, 0xff, 0x00];
+        assert_eq!(
+            safe_parse_tls_records(input, &TlsParseLimits::default()),
+            Err(TlsSafeParseError::InvalidRecord)
+        );
+    }
+
+    // Simple deterministic fuzzer: mutate valid records and check that parsing never panics
+    #[test]
+    fn test_safe_parse_fuzz() {
+        let seed = concat(&[RECORD_CH, RECORD_CCS, RECORD_SHD]);
+        let limits = TlsParseLimits::default();
+        let mut state: u32 = 0x1234_5678;
+        let mut next = || {
+            // xorshift32
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        };
+        for _ in 0..10_000 {
+            let mut input = seed.clone();
+            let n_mutations = 1 + next() % 8;
+            for _ in 0..n_mutations {
+                let pos = next() as usize % input.len();
+                input[pos] = next() as u8;
+            }
+            let truncate = next() as usize % (input.len() + 1);
+            input.truncate(truncate);
+            if let Ok((rem, records)) = safe_parse_tls_records(&input, &limits) {
+                assert!(records.len() <= limits.max_records);
+                assert!(rem.len() <= input.len());
+            }
+        }
+    }
+} // mod tls_limits
@@ -0,0 +1,98 @@
+#![cfg(feature = "serde")]
+
+extern crate tls_parser;
+
+mod tls_serde {
+    use tls_parser::*;
+
+    #[rustfmt::skip]
+static CH: &[u8] = &[
+    0x16, 0x03, 0x01, 0x01, 0x2c, 0x01, 0x00, 0x01, 0x28, 0x03, 0x03, 0xb2,
+    0x9d, 0xd7, 0x87, 0xff, 0x21, 0xeb, 0x04, 0xc8, 0xa5, 0x38, 0x39, 0x9a,
+    0xcf, 0xb7, 0xa3, 0x82, 0x1f, 0x82, 0x6c, 0x49, 0xbc, 0x8b, 0xb8, 0xa9,
+    0x03, 0x0a, 0x2d, 0xce, 0x38, 0x0b, 0xf4, 0x00, 0x00, 0xaa, 0xc0, 0x30,
+    0xc0, 0x2c, 0xc0, 0x28, 0xc0, 0x24, 0xc0, 0x14, 0xc0, 0x0a, 0x00, 0xa5,
+    0x00, 0xa3, 0x00, 0xa1, 0x00, 0x9f, 0x00, 0x6b, 0x00, 0x6a, 0x00, 0x69,
+    0x00, 0x68, 0x00, 0x39, 0x00, 0x38, 0x00, 0x37, 0x00, 0x36, 0x00, 0x88,
+    0x00, 0x87, 0x00, 0x86, 0x00, 0x85, 0xc0, 0x32, 0xc0, 0x2e, 0xc0, 0x2a,
+    0xc0, 0x26, 0xc0, 0x0f, 0xc0, 0x05, 0x00, 0x9d, 0x00, 0x3d, 0x00, 0x35,
+    0x00, 0x84, 0xc0, 0x2f, 0xc0, 0x2b, 0xc0, 0x27, 0xc0, 0x23, 0xc0, 0x13,
+    0xc0, 0x09, 0x00, 0xa4, 0x00, 0xa2, 0x00, 0xa0, 0x00, 0x9e, 0x00, 0x67,
+    0x00, 0x40, 0x00, 0x3f, 0x00, 0x3e, 0x00, 0x33, 0x00, 0x32, 0x00, 0x31,
+    0x00, 0x30, 0x00, 0x9a, 0x00, 0x99, 0x00, 0x98, 0x00, 0x97, 0x00, 0x45,
+    0x00, 0x44, 0x00, 0x43, 0x00, 0x42, 0xc0, 0x31, 0xc0, 0x2d, 0xc0, 0x29,
+    0xc0, 0x25, 0xc0, 0x0e, 0xc0, 0x04, 0x00, 0x9c, 0x00, 0x3c, 0x00, 0x2f,
+    0x00, 0x96, 0x00, 0x41, 0xc0, 0x11, 0xc0, 0x07, 0xc0, 0x0c, 0xc0, 0x02,
+    0x00, 0x05, 0x00, 0x04, 0xc0, 0x12, 0xc0, 0x08, 0x00, 0x16, 0x00, 0x13,
+    0x00, 0x10, 0x00, 0x0d, 0xc0, 0x0d, 0xc0, 0x03, 0x00, 0x0a, 0x00, 0xff,
+    0x01, 0x00, 0x00, 0x55, 0x00, 0x0b, 0x00, 0x04, 0x03, 0x00, 0x01, 0x02,
+    0x00, 0x0a, 0x00, 0x1c, 0x00, 0x1a, 0x00, 0x17, 0x00, 0x19, 0x00, 0x1c,
+    0x00, 0x1b, 0x00, 0x18, 0x00, 0x1a, 0x00, 0x16, 0x00, 0x0e, 0x00, 0x0d,
+    0x00, 0x0b, 0x00, 0x0c, 0x00, 0x09, 0x00, 0x0a, 0x00, 0x23, 0x00, 0x00,
+    0x00, 0x0d, 0x00, 0x20, 0x00, 0x1e, 0x06, 0x01, 0x06, 0x02, 0x06, 0x03,
+    0x05, 0x01, 0x05, 0x02, 0x05, 0x03, 0x04, 0x01, 0x04, 0x02, 0x04, 0x03,
+    0x03, 0x01, 0x03, 0x02, 0x03, 0x03, 0x02, 0x01, 0x02, 0x02, 0x02, 0x03,
+    0x00, 0x0f, 0x00, 0x01, 0x01
+];
+
+    #[test]
+    fn test_serde_client_hello() {
+        let (_, record) = parse_tls_plaintext(CH).expect("parsing failed");
+        let ch = match &record.msg[0] {
+            TlsMessage::Handshake(TlsMessageHandshake::ClientHello(ch)) => ch,
+            _ => panic!("expected ClientHello"),
+        };
+        let v = serde_json::to_value(ch).expect("serialization failed");
+        assert_eq!(v["version"], 0x0303);
+        assert_eq!(
+            v["random"],
+            "b29dd787ff21eb04c8a538399acfb7a3821f826c49bc8bb8a9030a2dce380bf4"
+        );
+        assert!(v["session_id"].is_null());
+        assert_eq!(v["ciphers"][0], 0xc030);
+        assert_eq!(v["comp"], serde_json::json!([0]));
+        let v = serde_json::to_value(record.hdr).expect("serialization failed");
+        assert_eq!(
+            v,
+            serde_json::json!({"record_type": 22, "version": 0x0301, "len": 300})
+        );
+    }
+
+    #[test]
+    fn test_serde_extensions() {
+        let ext = TlsExtension::SNI(vec![ServerName::host_name(b"example.com")]);
+        let s = serde_json::to_string(&ext).expect("serialization failed");
+        assert_eq!(
+            s,
+            r#"{"SNI":[{"name_type":0,"name":"6578616d706c652e636f6d"}]}"#
+        );
+        let ext = TlsExtension::ALPN(vec![b"h2", b"http/1.1"]);
+        let s = serde_json::to_string(&ext).expect("serialization failed");
+        assert_eq!(s, r#"{"ALPN":["6832","687474702f312e31"]}"#);
+        let ext = TlsExtension::Unknown(TlsExtensionType(0x1234), &[0xde, 0xad]);
+        let s = serde_json::to_string(&ext).expect("serialization failed");
+        assert_eq!(s, r#"{"Unknown":[4660,"dead"]}"#);
+    }
+
+    #[test]
+    fn test_serde_ssl2_client_hello() {
+        #[rustfmt::skip]
+        let bytes = &[
+            0x80, 0x1c, 0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0x00, 0x00, 0x10,
+            0x01, 0x00, 0x80,
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b,
+            0x0c, 0x0d, 0x0e, 0x0f,
+        ];
+        let (_, hello) = parse_ssl2_client_hello(bytes).expect("parsing failed");
+        let v = serde_json::to_value(hello).expect("serialization failed");
+        assert_eq!(
+            v,
+            serde_json::json!({
+                "version": 2,
+                "cipher_specs": [0x01_0080],
+                "session_id": "",
+                "challenge": "000102030405060708090a0b0c0d0e0f",
+            })
+        );
+    }
+} // mod tls_serde
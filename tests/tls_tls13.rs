@@ -118,4 +118,303 @@ static TV_SERVER_HELLO_1: &[u8] = &[
         let res_ext = parse_tls_extensions(ext_raw);
         assert_eq!(res_ext, Ok((empty, expected_ext)));
     }
+
+    // Decrypted EncryptedExtensions, from RFC8448 section 3
+    #[rustfmt::skip]
+static RFC8448_ENCRYPTED_EXTENSIONS: &[u8] = &[
+    0x08, 0x00, 0x00, 0x24, 0x00, 0x22, 0x00, 0x0a, 0x00, 0x14, 0x00, 0x12,
+    0x00, 0x1d, 0x00, 0x17, 0x00, 0x18, 0x00, 0x19, 0x01, 0x00, 0x01, 0x01,
+    0x01, 0x02, 0x01, 0x03, 0x01, 0x04, 0x00, 0x1c, 0x00, 0x02, 0x40, 0x01,
+    0x00, 0x00, 0x00, 0x00,
+];
+
+    #[test]
+    fn test_tls13_encrypted_extensions() {
+        let empty = &b""[..];
+        let bytes = RFC8448_ENCRYPTED_EXTENSIONS;
+        let expected = TlsMessage::Handshake(TlsMessageHandshake::EncryptedExtensions(
+            TlsEncryptedExtensionsContents { ext: &bytes[6..] },
+        ));
+        let res = parse_tls_message_handshake(bytes);
+        assert_eq!(res, Ok((empty, expected)));
+
+        let groups = [0x1d, 0x17, 0x18, 0x19, 0x100, 0x101, 0x102, 0x103, 0x104];
+        let expected_ext = vec![
+            TlsExtension::EllipticCurves(groups.iter().map(|&g| NamedGroup(g)).collect()),
+            TlsExtension::RecordSizeLimit(0x4001),
+            TlsExtension::SNI(vec![]),
+        ];
+        let res_ext = parse_tls_extensions(&bytes[6..]);
+        assert_eq!(res_ext, Ok((empty, expected_ext)));
+    }
+
+    #[test]
+    fn test_tls13_encrypted_extensions_empty() {
+        let empty = &b""[..];
+        let bytes = &[0x08, 0x00, 0x00, 0x02, 0x00, 0x00];
+        let expected = TlsMessage::Handshake(TlsMessageHandshake::EncryptedExtensions(
+            TlsEncryptedExtensionsContents { ext: empty },
+        ));
+        assert_eq!(parse_tls_message_handshake(bytes), Ok((empty, expected)));
+        // extensions length is mandatory
+        let bytes = &[0x08, 0x00, 0x00, 0x00];
+        assert!(parse_tls_message_handshake(bytes).is_err());
+    }
+
+    #[test]
+    fn test_tls13_certificate_verify() {
+        let empty = &b""[..];
+        let bytes = &[
+            0x0f, 0x00, 0x00, 0x0a, 0x08, 0x04, 0x00, 0x06, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06,
+        ];
+        let (_, msg) = parse_tls_message_handshake(bytes).expect("parsing failed");
+        let raw = match msg {
+            TlsMessage::Handshake(TlsMessageHandshake::CertificateVerify(raw)) => raw,
+            _ => panic!("expected CertificateVerify"),
+        };
+        let (rem, cv) =
+            parse_tls_certificate_verify(raw).expect("parsing CertificateVerify failed");
+        assert_eq!(rem, empty);
+        assert_eq!(cv.scheme, SignatureScheme::rsa_pss_rsae_sha256);
+        assert_eq!(cv.signature.len(), 6);
+        assert_eq!(cv.signature, &bytes[8..]);
+        // truncated signature
+        let res = parse_tls_certificate_verify(&raw[..raw.len() - 1]);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_tls13_key_update() {
+        let empty = &b""[..];
+        for &(b, request_update) in &[
+            (0, KeyUpdateRequest::NotRequested),
+            (1, KeyUpdateRequest::Requested),
+        ] {
+            let bytes = &[0x18, 0x00, 0x00, 0x01, b];
+            let expected =
+                TlsMessage::Handshake(TlsMessageHandshake::KeyUpdate(TlsKeyUpdateContents {
+                    request_update,
+                }));
+            assert_eq!(parse_tls_message_handshake(bytes), Ok((empty, expected)));
+        }
+        let bytes = &[0x18, 0x00, 0x00, 0x01, 0x02];
+        assert!(parse_tls_message_handshake(bytes).is_err());
+    }
+
+    #[rustfmt::skip]
+static NEW_SESSION_TICKET_V13: &[u8] = &[
+    0x04, 0x00, 0x00, 0x1e, 0x00, 0x00, 0x1c, 0x20, 0xfa, 0xd6, 0xaa, 0xc5,
+    0x01, 0x07, 0x00, 0x08, 0x2c, 0x03, 0x5d, 0x82, 0x93, 0x59, 0xee, 0x5f,
+    0x00, 0x08, 0x00, 0x2a, 0x00, 0x04, 0x00, 0x00, 0x04, 0x00,
+];
+
+    #[test]
+    fn test_tls13_new_session_ticket() {
+        let empty = &b""[..];
+        let bytes = NEW_SESSION_TICKET_V13;
+        let expected = TlsMessage::Handshake(TlsMessageHandshake::NewSessionTicketV13(
+            TlsNewSessionTicketV13Contents {
+                ticket_lifetime: 7200,
+                ticket_age_add: 0xfad6_aac5,
+                ticket_nonce: &[0x07],
+                ticket: &bytes[16..24],
+                ext: &bytes[26..],
+            },
+        ));
+        let res = parse_tls_message_handshake_with_version(bytes, TlsVersion::Tls13);
+        assert_eq!(res, Ok((empty, expected)));
+        let (_, ext) = parse_tls_extensions(&bytes[26..]).expect("parsing extensions failed");
+        assert_eq!(ext, vec![TlsExtension::EarlyData(Some(1024))]);
+    }
+
+    #[test]
+    fn test_tls13_new_session_ticket_empty_nonce() {
+        let empty = &b""[..];
+        let bytes = &[
+            0x04, 0x00, 0x00, 0x0f, 0x00, 0x00, 0x1c, 0x20, 0x01, 0x02, 0x03, 0x04, 0x00, 0x00,
+            0x02, 0xab, 0xcd, 0x00, 0x00,
+        ];
+        let expected = TlsMessage::Handshake(TlsMessageHandshake::NewSessionTicketV13(
+            TlsNewSessionTicketV13Contents {
+                ticket_lifetime: 7200,
+                ticket_age_add: 0x0102_0304,
+                ticket_nonce: empty,
+                ticket: &[0xab, 0xcd],
+                ext: empty,
+            },
+        ));
+        let res = parse_tls_message_handshake_with_version(bytes, TlsVersion::Tls13);
+        assert_eq!(res, Ok((empty, expected)));
+    }
+
+    #[test]
+    fn test_tls12_new_session_ticket_with_version() {
+        let empty = &b""[..];
+        let bytes = &[
+            0x04, 0x00, 0x00, 0x08, 0x00, 0x00, 0x1c, 0x20, 0x00, 0x02, 0xab, 0xcd,
+        ];
+        let expected = TlsMessage::Handshake(TlsMessageHandshake::NewSessionTicket(
+            TlsNewSessionTicketContent {
+                ticket_lifetime_hint: 7200,
+                ticket: &bytes[8..],
+            },
+        ));
+        let res = parse_tls_message_handshake_with_version(bytes, TlsVersion::Tls12);
+        assert_eq!(res, Ok((empty, expected.clone())));
+        assert_eq!(parse_tls_message_handshake(bytes), Ok((empty, expected)));
+    }
+
+    // Certificate message with 2 (fake) certificates, the first one with a stapled OCSP response
+    #[rustfmt::skip]
+static CERTIFICATE_V13: &[u8] = &[
+    0x0b, 0x00, 0x00, 0x21, 0x00, 0x00, 0x00, 0x1d,
+    // entry 1
+    0x00, 0x00, 0x04, 0x30, 0x02, 0x05, 0x00,
+    0x00, 0x0c, 0x00, 0x05, 0x00, 0x08, 0x01, 0x00, 0x00, 0x04, 0xde, 0xad, 0xbe, 0xef,
+    // entry 2
+    0x00, 0x00, 0x03, 0x30, 0x01, 0x00,
+    0x00, 0x00,
+];
+
+    #[test]
+    fn test_tls13_certificate() {
+        let empty = &b""[..];
+        let bytes = CERTIFICATE_V13;
+        let expected = TlsMessage::Handshake(TlsMessageHandshake::CertificateV13(
+            TlsCertificateV13Contents {
+                certificate_request_context: empty,
+                cert_list: vec![
+                    CertificateEntry {
+                        cert_data: &bytes[11..15],
+                        ext: &bytes[17..29],
+                    },
+                    CertificateEntry {
+                        cert_data: &bytes[32..35],
+                        ext: empty,
+                    },
+                ],
+            },
+        ));
+        let res = parse_tls_message_handshake_with_version(bytes, TlsVersion::Tls13);
+        assert_eq!(res, Ok((empty, expected)));
+        let (_, ext) = parse_tls_extensions(&bytes[17..29]).expect("parsing extensions failed");
+        assert_eq!(
+            ext,
+            vec![TlsExtension::StatusRequest(Some((
+                CertificateStatusType::OCSP,
+                &bytes[22..29]
+            )))]
+        );
+    }
+
+    #[test]
+    fn test_tls13_certificate_entry_extensions() {
+        let bytes = CERTIFICATE_V13;
+        let (rem, ext) =
+            parse_certificate_entry_extensions(&bytes[17..29]).expect("parsing extensions failed");
+        assert!(rem.is_empty());
+        assert_eq!(
+            ext,
+            vec![TlsExtension::CertificateStatus(
+                CertificateStatusType::OCSP,
+                &[0xde, 0xad, 0xbe, 0xef][..]
+            )]
+        );
+        // SCT list, and an extension not allowed in a CertificateEntry
+        let bytes = &[
+            0x00, 0x12, 0x00, 0x06, 0x00, 0x04, 0x00, 0x02, 0xab, 0xcd, 0x00, 0x0a, 0x00, 0x02,
+            0x00, 0x00,
+        ];
+        let (_, ext) =
+            parse_certificate_entry_extensions(bytes).expect("parsing extensions failed");
+        assert_eq!(
+            ext,
+            vec![
+                TlsExtension::SignedCertificateTimestamp(Some(vec![&[0xab, 0xcd]])),
+                TlsExtension::Unknown(TlsExtensionType::SupportedGroups, &[0x00, 0x00]),
+            ]
+        );
+        // OCSP response length does not match the extension length
+        let bytes = &[0x00, 0x05, 0x00, 0x06, 0x01, 0x00, 0x00, 0x04, 0xde, 0xad];
+        assert!(parse_certificate_entry_extensions(bytes).is_err());
+        // a malformed extension after a valid one
+        let bytes = &[
+            0x00, 0x0a, 0x00, 0x02, 0x00, 0x00, 0x00, 0x05, 0x00, 0x06, 0x01, 0x00, 0x00, 0x04,
+            0xde, 0xad,
+        ];
+        assert!(parse_certificate_entry_extensions(bytes).is_err());
+    }
+
+    // CompressedCertificate message, zlib-compressed empty Certificate message
+    #[rustfmt::skip]
+static COMPRESSED_CERTIFICATE: &[u8] = &[
+    0x19, 0x00, 0x00, 0x14, 0x00, 0x01, 0x00, 0x00, 0x04, 0x00, 0x00, 0x0c,
+    0x78, 0x9c, 0x63, 0x60, 0x60, 0x60, 0x00, 0x00, 0x00, 0x04, 0x00, 0x01,
+];
+
+    #[test]
+    fn test_tls13_compressed_certificate() {
+        let empty = &b""[..];
+        let bytes = COMPRESSED_CERTIFICATE;
+        let expected = TlsMessage::Handshake(TlsMessageHandshake::CompressedCertificate(
+            TlsCompressedCertificateContents {
+                algorithm: CertificateCompressionAlgorithm::Zlib,
+                uncompressed_length: 4,
+                compressed_certificate_message: &bytes[12..],
+            },
+        ));
+        assert_eq!(parse_tls_message_handshake(bytes), Ok((empty, expected)));
+        // empty compressed data
+        let bytes = &[
+            0x19, 0x00, 0x00, 0x08, 0x00, 0x02, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00,
+        ];
+        assert!(parse_tls_message_handshake(bytes).is_err());
+    }
+
+    #[test]
+    fn test_tls13_certificate_request() {
+        let empty = &b""[..];
+        // empty context, signature_algorithms extension
+        let bytes = &[
+            0x0d, 0x00, 0x00, 0x11, 0x00, 0x00, 0x0e, 0x00, 0x0d, 0x00, 0x0a, 0x00, 0x08, 0x04,
+            0x03, 0x08, 0x04, 0x04, 0x01, 0x05, 0x03,
+        ];
+        let expected = TlsMessage::Handshake(TlsMessageHandshake::CertificateRequestV13(
+            TlsCertificateRequestV13Contents {
+                certificate_request_context: empty,
+                ext: &bytes[7..],
+            },
+        ));
+        let res = parse_tls_message_handshake_with_version(bytes, TlsVersion::Tls13);
+        assert_eq!(res, Ok((empty, expected)));
+        let (_, ext) = parse_tls_extensions(&bytes[7..]).expect("parsing extensions failed");
+        assert_eq!(
+            ext,
+            vec![TlsExtension::SignatureAlgorithms(vec![
+                0x0403, 0x0804, 0x0401, 0x0503
+            ])]
+        );
+        // extensions cannot be empty
+        let bytes = &[0x0d, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00];
+        assert!(parse_tls_message_handshake_with_version(bytes, TlsVersion::Tls13).is_err());
+    }
+
+    #[test]
+    fn test_tls12_certificate_request_with_version() {
+        let empty = &b""[..];
+        // rsa_sign and ecdsa_sign, 2 signature algorithms, no CA
+        let bytes = &[
+            0x0d, 0x00, 0x00, 0x0b, 0x02, 0x01, 0x40, 0x00, 0x04, 0x04, 0x01, 0x04, 0x03, 0x00,
+            0x00,
+        ];
+        let expected = TlsMessage::Handshake(TlsMessageHandshake::CertificateRequest(
+            TlsCertificateRequestContents {
+                cert_types: vec![0x01, 0x40],
+                sig_hash_algs: Some(vec![0x0401, 0x0403]),
+                unparsed_ca: vec![],
+            },
+        ));
+        let res = parse_tls_message_handshake_with_version(bytes, TlsVersion::Tls12);
+        assert_eq!(res, Ok((empty, expected)));
+    }
 } // mod tls_13